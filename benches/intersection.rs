@@ -0,0 +1,123 @@
+/*!
+Ray intersection benchmarks. Run with `cargo bench`.
+
+Every scene and ray is generated from a fixed seed, so numbers are comparable between runs.
+*/
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rt_weekend::{
+    bounds::Bounds3,
+    bvh::Bvh,
+    color::Color,
+    interval::Interval,
+    material::{Lambertian, Material},
+    object::{IsObject, Object},
+    ray::Ray,
+    real::Real,
+    sphere::Sphere,
+    texture::{self, Texture, UvTransform},
+    vec3::Vec3,
+};
+
+const SEED: u64 = 0;
+
+/// The number of rays traced in each iteration.
+const RAYS: usize = 1000;
+
+fn material() -> Material {
+    Material::new(Lambertian {
+        albedo: Texture::new(texture::Constant {
+            color: Color {
+                r: 0.5,
+                g: 0.5,
+                b: 0.5,
+            },
+        }),
+    })
+}
+
+/// Small spheres scattered through a cube around the origin.
+fn spheres<R: Rng>(rng: &mut R, count: usize) -> Vec<Object> {
+    let material = material();
+    (0..count)
+        .map(|_| {
+            Object::new(Sphere {
+                center: Vec3::gen_range(rng, -10.0..10.0),
+                radius: rng.gen_range(0.1..0.5),
+                material: material.clone(),
+                uv_transform: UvTransform::default(),
+            })
+        })
+        .collect()
+}
+
+/// Rays from points on a sphere around the scene toward points near its middle.
+fn rays<R: Rng>(rng: &mut R, count: usize) -> Vec<Ray> {
+    (0..count)
+        .map(|_| {
+            let origin = 20.0 * Vec3::gen_range(rng, -1.0..1.0).unit();
+            let target = Vec3::gen_range(rng, -5.0..5.0);
+            Ray::new(origin, target - origin)
+        })
+        .collect()
+}
+
+fn bvh(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let objects = spheres(&mut rng, 10_000);
+    let bvh = Bvh::from(objects.as_slice());
+    let rays = rays(&mut rng, RAYS);
+    c.bench_function("bvh hit", |b| {
+        b.iter(|| {
+            for ray in &rays {
+                black_box(bvh.hit(ray, Interval::new(0.0, Real::INFINITY)));
+            }
+        })
+    });
+}
+
+fn sphere(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let sphere = Sphere {
+        center: Vec3::ZERO,
+        radius: 5.0,
+        material: material(),
+        uv_transform: UvTransform::default(),
+    };
+    let rays = rays(&mut rng, RAYS);
+    c.bench_function("sphere hit", |b| {
+        b.iter(|| {
+            for ray in &rays {
+                black_box(sphere.hit(ray, Interval::new(0.0, Real::INFINITY)));
+            }
+        })
+    });
+}
+
+fn bounds(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let bounds = Bounds3::new(
+        Vec3 {
+            x: -5.0,
+            y: -3.0,
+            z: -4.0,
+        },
+        Vec3 {
+            x: 5.0,
+            y: 3.0,
+            z: 4.0,
+        },
+    );
+    let rays = rays(&mut rng, RAYS);
+    c.bench_function("bounds hit_by", |b| {
+        b.iter(|| {
+            for ray in &rays {
+                black_box(bounds.hit_by(ray, Interval::new(0.0, Real::INFINITY)));
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bvh, sphere, bounds);
+criterion_main!(benches);
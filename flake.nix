@@ -32,7 +32,7 @@
         };
         
         rustPkgs = { release }: pkgs.rustBuilder.makePackageSet' {
-          rustChannel = "1.56.1";
+          rustChannel = "1.82.0";
           packageFun = import "${self}/Cargo.nix";
           inherit release;
         };
@@ -1,6 +1,46 @@
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Axis3 {
     X,
     Y,
     Z,
 }
+
+impl Axis3 {
+    /// Every axis, in order.
+    pub fn all() -> [Axis3; 3] {
+        [Axis3::X, Axis3::Y, Axis3::Z]
+    }
+
+    /// The following axis, wrapping around from Z to X.
+    #[must_use]
+    pub fn next(self) -> Axis3 {
+        match self {
+            Axis3::X => Axis3::Y,
+            Axis3::Y => Axis3::Z,
+            Axis3::Z => Axis3::X,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec3::Vec3;
+
+    #[test]
+    fn all_is_in_order() {
+        assert_eq!(Axis3::all(), [Axis3::X, Axis3::Y, Axis3::Z]);
+        for axis in Axis3::all() {
+            assert_eq!(axis.next().next().next(), axis);
+        }
+
+        // Vectors index their components in the same order.
+        let v = Vec3 {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+        };
+        let components = Axis3::all().map(|axis| v[axis]);
+        assert_eq!(components, [1.0, 2.0, 3.0]);
+    }
+}
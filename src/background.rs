@@ -0,0 +1,317 @@
+use crate::{
+    color::Color,
+    ray::Ray,
+    real::{consts, Real},
+    sampling::Discrete,
+    vec3::Vec3,
+};
+use image::{codecs::hdr::HdrDecoder, GenericImageView, ImageFormat, ImageResult};
+use rand::Rng;
+use std::{fs::File, io::BufReader, sync::Arc};
+
+/// The color seen by rays that don't hit anything.
+pub enum Background {
+    /// A vertical gradient from `bottom` (rays pointing straight down) to `top` (rays pointing
+    /// straight up).
+    Gradient {
+        top: Color,
+        bottom: Color,
+    },
+    Solid(Color),
+    Black,
+    Environment(EnvironmentMap),
+    /**
+    Any color for each ray, for skies built in code, like a sun disk or a nebula.
+
+    ```
+    use rt_weekend::{background::Background, color::Color, ray::Ray, vec3::Vec3};
+    use std::sync::Arc;
+
+    let teal = Color {
+        r: 0.0,
+        g: 0.5,
+        b: 0.5,
+    };
+    let background = Background::Function(Arc::new(move |_: &Ray| teal));
+    let up = Vec3 {
+        x: 0.0,
+        y: 1.0,
+        z: 0.0,
+    };
+    let ray = Ray::new(Vec3::ZERO, up);
+    assert_eq!(background.color(&ray).g, 0.5);
+    ```
+    */
+    Function(Arc<dyn Fn(&Ray) -> Color + Send + Sync>),
+}
+
+impl Background {
+    pub fn color(&self, ray: &Ray) -> Color {
+        match self {
+            Background::Gradient { top, bottom } => {
+                let unit_direction = ray.direction.unit();
+                let t = 0.5 * (unit_direction.y + 1.0);
+                (1.0 - t) * *bottom + t * *top
+            }
+            Background::Solid(color) => *color,
+            Background::Black => Color::BLACK,
+            Background::Environment(environment) => environment.color(&ray.direction),
+            Background::Function(function) => function(ray),
+        }
+    }
+}
+
+/// A white-to-blue sky.
+impl Default for Background {
+    fn default() -> Self {
+        Background::Gradient {
+            top: Color {
+                r: 0.5,
+                g: 0.7,
+                b: 1.0,
+            },
+            bottom: Color {
+                r: 1.0,
+                g: 1.0,
+                b: 1.0,
+            },
+        }
+    }
+}
+
+/**
+An equirectangular image that surrounds the scene.
+
+Directions can be sampled in proportion to how bright the map is in them, so that small, bright
+areas such as the sun can be found without tracing lots of random rays.
+*/
+pub struct EnvironmentMap {
+    width: usize,
+    height: usize,
+    /// Linear RGB, row-major from the top of the image.
+    pixels: Vec<Color>,
+    /// Which row to sample a direction from.
+    rows: Discrete,
+    /// Which pixel to sample a direction from, within each row.
+    columns: Vec<Discrete>,
+}
+
+impl EnvironmentMap {
+    /**
+    An environment map from `width * height` linear `pixels`, row-major from the top of the image.
+
+    Each pixel is sampled in proportion to its luminance times the solid angle it covers, which
+    shrinks toward the poles.
+    */
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert_eq!(pixels.len(), width * height, "wrong number of pixels");
+
+        let columns: Vec<Discrete> = pixels
+            .chunks_exact(width)
+            .map(|row| Discrete::new(row.iter().map(|pixel| pixel.luminance().max(0.0))))
+            .collect();
+        let rows = Discrete::new(pixels.chunks_exact(width).enumerate().map(|(y, row)| {
+            let theta = consts::PI * (1.0 - (y as Real + 0.5) / height as Real);
+            let luminance: Real = row.iter().map(|pixel| pixel.luminance().max(0.0)).sum();
+            luminance * theta.sin()
+        }));
+
+        EnvironmentMap {
+            width,
+            height,
+            pixels,
+            rows,
+            columns,
+        }
+    }
+
+    /**
+    Load an environment map from an image file.
+
+    Radiance HDR (`.hdr`) files are already linear. Any other format is assumed to be sRGB-encoded,
+    and is converted to linear.
+    */
+    pub fn open(path: &str) -> ImageResult<Self> {
+        let reader = image::io::Reader::open(path)?;
+
+        if reader.format() == Some(ImageFormat::Hdr) {
+            let decoder = HdrDecoder::new(BufReader::new(File::open(path)?))?;
+            let metadata = decoder.metadata();
+            let pixels = decoder
+                .read_image_hdr()?
+                .into_iter()
+                .map(|pixel| Color {
+                    r: pixel[0] as Real,
+                    g: pixel[1] as Real,
+                    b: pixel[2] as Real,
+                })
+                .collect();
+            Ok(EnvironmentMap::new(
+                metadata.width as usize,
+                metadata.height as usize,
+                pixels,
+            ))
+        } else {
+            let image = reader.decode()?;
+            let width = image.width() as usize;
+            let height = image.height() as usize;
+            let pixels = image
+                .to_rgb8()
+                .pixels()
+                .map(|pixel| {
+                    Color {
+                        r: pixel[0] as Real / 255.0,
+                        g: pixel[1] as Real / 255.0,
+                        b: pixel[2] as Real / 255.0,
+                    }
+                    .srgb_to_linear()
+                })
+                .collect();
+            Ok(EnvironmentMap::new(width, height, pixels))
+        }
+    }
+
+    /// The pixel seen when looking in `direction`.
+    fn pixel(&self, direction: &Vec3) -> (usize, usize) {
+        let direction = direction.unit();
+
+        // The same spherical coordinates that `Sphere::hit` uses for texture mapping.
+        let phi = (-direction.z).atan2(direction.x) + consts::PI;
+        let theta = (-direction.y).acos();
+        let u = phi / (2.0 * consts::PI);
+        let v = theta / consts::PI;
+
+        let x = ((u * self.width as Real) as usize).min(self.width - 1);
+        let y = (((1.0 - v) * self.height as Real) as usize).min(self.height - 1);
+        (x, y)
+    }
+
+    /// The color seen when looking in `direction`.
+    pub fn color(&self, direction: &Vec3) -> Color {
+        let (x, y) = self.pixel(direction);
+        self.pixels[y * self.width + x]
+    }
+
+    /// A random unit vector, more likely to point toward brighter parts of the map.
+    pub fn sample_direction<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3 {
+        let y = self.rows.sample(rng);
+        let x = self.columns[y].sample(rng);
+
+        // A uniformly random point in the pixel, inverting the mapping in `EnvironmentMap::pixel`.
+        let u = (x as Real + rng.gen::<Real>()) / self.width as Real;
+        let v = 1.0 - (y as Real + rng.gen::<Real>()) / self.height as Real;
+        let phi = 2.0 * consts::PI * u;
+        let theta = consts::PI * v;
+        Vec3 {
+            x: -phi.cos() * theta.sin(),
+            y: -theta.cos(),
+            z: phi.sin() * theta.sin(),
+        }
+    }
+
+    /// The probability density, with respect to solid angle, that
+    /// [`EnvironmentMap::sample_direction`] returns `direction`.
+    pub fn pdf(&self, direction: &Vec3) -> Real {
+        let sin_theta = match direction.try_unit() {
+            Some(direction) => (1.0 - direction.y * direction.y).max(0.0).sqrt(),
+            None => return 0.0,
+        };
+        if sin_theta == 0.0 {
+            return 0.0;
+        }
+        let (x, y) = self.pixel(direction);
+        let probability = self.rows.probability(y) * self.columns[y].probability(x);
+
+        // Each pixel covers `1 / (width * height)` of the `(u, v)` square, which maps onto the
+        // sphere with area `2π² sin(theta)` per unit.
+        let density = probability * (self.width * self.height) as Real;
+        density / (2.0 * consts::PI * consts::PI * sin_theta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn gradient_goes_from_bottom_to_top() {
+        let (top, bottom) = (
+            Color {
+                r: 0.2,
+                g: 0.4,
+                b: 1.0,
+            },
+            Color {
+                r: 1.0,
+                g: 0.9,
+                b: 0.8,
+            },
+        );
+        let background = Background::Gradient { top, bottom };
+        let vertical = |y: Real| Ray::new(Vec3::ZERO, Vec3 { x: 0.0, y, z: 0.0 });
+
+        assert!(background.color(&vertical(-3.0)).approx_eq(bottom, 1e-6));
+        assert!(background.color(&vertical(0.5)).approx_eq(top, 1e-6));
+
+        let horizontal = Ray::new(
+            Vec3::ZERO,
+            Vec3 {
+                x: 1.0,
+                y: 0.0,
+                z: -1.0,
+            },
+        );
+        assert!(background
+            .color(&horizontal)
+            .approx_eq(bottom.lerp(top, 0.5), 1e-6));
+    }
+
+    #[test]
+    fn environment_sampling_finds_the_bright_pixel() {
+        let (width, height) = (16, 8);
+        let bright = (5, 3);
+        let pixels = (0..width * height)
+            .map(|i| {
+                let value = if (i % width, i / width) == bright {
+                    1000.0
+                } else {
+                    0.01
+                };
+                Color {
+                    r: value,
+                    g: value,
+                    b: value,
+                }
+            })
+            .collect();
+        let environment = EnvironmentMap::new(width, height, pixels);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let samples = 10_000;
+        let mut toward_bright = 0;
+        for _ in 0..samples {
+            let direction = environment.sample_direction(&mut rng);
+            assert!((direction.norm() - 1.0).abs() < 1e-4, "{:?}", direction);
+            if environment.pixel(&direction) == bright {
+                toward_bright += 1;
+                assert!(environment.color(&direction).r == 1000.0);
+            }
+        }
+        assert!(
+            toward_bright as Real > 0.95 * samples as Real,
+            "{}",
+            toward_bright
+        );
+
+        // The density matches: much higher toward the bright pixel than anywhere else.
+        let toward = loop {
+            let direction = environment.sample_direction(&mut rng);
+            if environment.pixel(&direction) == bright {
+                break direction;
+            }
+        };
+        let away = -toward;
+        assert!(environment.pdf(&toward) > 1000.0 * environment.pdf(&away));
+    }
+}
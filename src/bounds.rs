@@ -1,4 +1,9 @@
-use crate::{axis::Axis3, interval::Interval, ray::Ray, vec3::Vec3};
+use crate::{axis::Axis3, interval::Interval, ray::Ray, real::Real, vec3::Vec3};
+
+#[cfg(all(feature = "simd", not(feature = "f32")))]
+type RealX4 = wide::f64x4;
+#[cfg(all(feature = "simd", feature = "f32"))]
+type RealX4 = wide::f32x4;
 
 #[derive(Clone, Copy)]
 pub struct Bounds3 {
@@ -7,19 +12,49 @@ pub struct Bounds3 {
 }
 
 impl Bounds3 {
+    /**
+    A box containing nothing.
+
+    Its min-corner is at positive infinity and its max-corner at negative infinity, so it's the
+    identity for [`Bounds3::union`], and no ray hits it.
+    */
+    pub const EMPTY: Bounds3 = Bounds3 {
+        min: Vec3 {
+            x: Real::INFINITY,
+            y: Real::INFINITY,
+            z: Real::INFINITY,
+        },
+        max: Vec3 {
+            x: Real::NEG_INFINITY,
+            y: Real::NEG_INFINITY,
+            z: Real::NEG_INFINITY,
+        },
+    };
+
+    /**
+    A box containing everything, for objects that go on forever.
+
+    A [`Bvh`](crate::bvh::Bvh) can't partition objects with boxes like this, so it tests them
+    against every ray instead.
+    */
+    pub const INFINITE: Bounds3 = Bounds3 {
+        min: Vec3 {
+            x: Real::NEG_INFINITY,
+            y: Real::NEG_INFINITY,
+            z: Real::NEG_INFINITY,
+        },
+        max: Vec3 {
+            x: Real::INFINITY,
+            y: Real::INFINITY,
+            z: Real::INFINITY,
+        },
+    };
+
     /// Construct a bounding box from two corner points.
     pub fn new(a: Vec3, b: Vec3) -> Self {
         Bounds3 {
-            min: Vec3 {
-                x: a.x.min(b.x),
-                y: a.y.min(b.y),
-                z: a.z.min(b.z),
-            },
-            max: Vec3 {
-                x: a.x.max(b.x),
-                y: a.y.max(b.y),
-                z: a.z.max(b.z),
-            },
+            min: a.min(b),
+            max: a.max(b),
         }
     }
 
@@ -38,19 +73,67 @@ impl Bounds3 {
         Bounds3 { min: v, max: v }
     }
 
+    /// Whether the box contains no points, like [`Bounds3::EMPTY`].
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    /// Whether both corners are finite, which isn't the case for [`Bounds3::EMPTY`] and
+    /// [`Bounds3::INFINITE`].
+    pub fn is_finite(&self) -> bool {
+        [self.min, self.max].iter().all(|corner| {
+            Axis3::all()
+                .into_iter()
+                .all(|axis| corner[axis].is_finite())
+        })
+    }
+
+    /// The distance from `point` to the nearest point in the box. `0` inside it, and infinite for an
+    /// empty box.
+    pub fn distance(&self, point: Vec3) -> Real {
+        if self.is_empty() {
+            return Real::INFINITY;
+        }
+        (self.min - point)
+            .max(point - self.max)
+            .max(Vec3::ZERO)
+            .norm()
+    }
+
     /// Compute the union of two bounding boxes.
     #[must_use]
     pub fn union(&self, other: &Bounds3) -> Self {
+        Bounds3 {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// Grow the box so that it's at least `delta` wide in every dimension.
+    #[must_use]
+    pub fn padded(&self, delta: Real) -> Self {
+        fn pad(min: Real, max: Real, delta: Real) -> (Real, Real) {
+            if max - min < delta {
+                let middle = 0.5 * (min + max);
+                (middle - delta / 2.0, middle + delta / 2.0)
+            } else {
+                (min, max)
+            }
+        }
+
+        let (min_x, max_x) = pad(self.min.x, self.max.x, delta);
+        let (min_y, max_y) = pad(self.min.y, self.max.y, delta);
+        let (min_z, max_z) = pad(self.min.z, self.max.z, delta);
         Bounds3 {
             min: Vec3 {
-                x: self.min.x.min(other.min.x),
-                y: self.min.y.min(other.min.y),
-                z: self.min.z.min(other.min.z),
+                x: min_x,
+                y: min_y,
+                z: min_z,
             },
             max: Vec3 {
-                x: self.max.x.max(other.max.x),
-                y: self.max.y.max(other.max.y),
-                z: self.max.z.max(other.max.z),
+                x: max_x,
+                y: max_y,
+                z: max_z,
             },
         }
     }
@@ -60,6 +143,34 @@ impl Bounds3 {
         self.max - self.min
     }
 
+    /**
+    The total area of the box's six faces, for estimating how likely a ray is to hit it.
+
+    `0` for an empty box, and infinite for a box with an infinite side.
+    */
+    pub fn surface_area(&self) -> Real {
+        if self.is_empty() {
+            0.0
+        } else if !self.is_finite() {
+            Real::INFINITY
+        } else {
+            let d = self.diagonal();
+            2.0 * (d.x * d.y + d.y * d.z + d.z * d.x)
+        }
+    }
+
+    /// The space inside the box. `0` for an empty box, and infinite for a box with an infinite side.
+    pub fn volume(&self) -> Real {
+        if self.is_empty() {
+            0.0
+        } else if !self.is_finite() {
+            Real::INFINITY
+        } else {
+            let d = self.diagonal();
+            d.x * d.y * d.z
+        }
+    }
+
     /// The dimension in which the bounding box is longest.
     pub fn maximum_extent(&self) -> Axis3 {
         let diagonal = self.diagonal();
@@ -73,24 +184,54 @@ impl Bounds3 {
         }
     }
 
+    /// The eight corners of the box.
+    pub fn corners(&self) -> [Vec3; 8] {
+        let (min, max) = (self.min, self.max);
+        [
+            min,
+            Vec3 { z: max.z, ..min },
+            Vec3 { y: max.y, ..min },
+            Vec3 { x: min.x, ..max },
+            Vec3 { x: max.x, ..min },
+            Vec3 { y: min.y, ..max },
+            Vec3 { z: min.z, ..max },
+            max,
+        ]
+    }
+
     /// The center of the box.
     pub fn centroid(&self) -> Vec3 {
-        0.5 * self.min + 0.5 * self.max
+        self.min.lerp(self.max, 0.5)
     }
 
-    pub fn hit_by(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+    /// Whether `ray` passes through the box for some `t` in `t_range`.
+    pub fn hit_by(&self, ray: &Ray, t_range: Interval) -> bool {
+        #[cfg(feature = "simd")]
+        {
+            self.hit_by_simd(ray, t_range)
+        }
+        #[cfg(not(feature = "simd"))]
+        {
+            self.hit_by_scalar(ray, t_range)
+        }
+    }
+
+    /// [`Bounds3::hit_by`], one axis at a time.
+    pub fn hit_by_scalar(&self, ray: &Ray, t_range: Interval) -> bool {
+        // The slabs of an empty box are inside out, which the slab test doesn't notice.
+        if self.is_empty() {
+            return false;
+        }
+
         /*
         The values of `t` for which the ray is inside the bounding box.
 
         By the end of this function, the interval will be non-empty iff the ray
         intersects the box.
         */
-        let mut t_interval = Interval {
-            start: t_min,
-            end: t_max,
-        };
+        let mut t_interval = t_range;
 
-        for axis in [Axis3::X, Axis3::Y, Axis3::Z] {
+        for axis in Axis3::all() {
             let parallel_to_slab = ray.direction[axis] == 0.0;
 
             if parallel_to_slab {
@@ -109,7 +250,7 @@ impl Bounds3 {
                 The two intersection points are used to refine `t_interval`.
                 */
 
-                let inverse_ray_direction = 1.0 / ray.direction[axis];
+                let inverse_ray_direction = ray.inverse_direction()[axis];
 
                 // origin_axis + t*direction_axis = min_axis
                 let t_for_axis_min = (self.min[axis] - ray.origin[axis]) * inverse_ray_direction;
@@ -133,4 +274,330 @@ impl Bounds3 {
 
         !t_interval.is_empty()
     }
+
+    /**
+    The first `t` in `t_range` at which `ray` crosses the surface of the box within `thickness` of
+    one of its edges. Used to draw boxes as wireframes.
+
+    The slabs give the points where the ray's line enters and leaves the box. Each is on a face,
+    and is near an edge when it's also within `thickness` of the plane of another face.
+    */
+    pub fn edge_hit(&self, ray: &Ray, t_range: Interval, thickness: Real) -> Option<Real> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let mut t_interval = Interval::ALL;
+        for axis in Axis3::all() {
+            if ray.direction[axis] == 0.0 {
+                if ray.origin[axis] < self.min[axis] || ray.origin[axis] > self.max[axis] {
+                    return None;
+                }
+            } else {
+                let inverse_ray_direction = ray.inverse_direction()[axis];
+                let t_for_axis_min = (self.min[axis] - ray.origin[axis]) * inverse_ray_direction;
+                let t_for_axis_max = (self.max[axis] - ray.origin[axis]) * inverse_ray_direction;
+                t_interval.intersect_mut(&Interval {
+                    start: t_for_axis_min.min(t_for_axis_max),
+                    end: t_for_axis_min.max(t_for_axis_max),
+                });
+            }
+        }
+        if t_interval.is_empty() {
+            return None;
+        }
+
+        let near_edge = |point: Vec3| {
+            let planes_nearby = Axis3::all()
+                .into_iter()
+                .filter(|&axis| {
+                    (point[axis] - self.min[axis]).abs() <= thickness
+                        || (self.max[axis] - point[axis]).abs() <= thickness
+                })
+                .count();
+            planes_nearby >= 2
+        };
+        [t_interval.start, t_interval.end]
+            .into_iter()
+            .find(|&t| t_range.contains(t) && near_edge(ray.at(t)))
+    }
+
+    /**
+    [`Bounds3::hit_by`], computing the slab intersections for all three axes at once.
+
+    Gives exactly the same results as [`Bounds3::hit_by_scalar`]: the arithmetic is the same, lane
+    by lane, and `wide`'s `min` and `max` ignore NaNs the same way `Real`'s do.
+    */
+    #[cfg(feature = "simd")]
+    pub fn hit_by_simd(&self, ray: &Ray, t_range: Interval) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+
+        // The fourth lane is unused. It's an infinitely wide slab, so it never narrows the interval.
+        let origin = [ray.origin.x, ray.origin.y, ray.origin.z, 0.0];
+        let direction = [ray.direction.x, ray.direction.y, ray.direction.z, 1.0];
+        let min = [self.min.x, self.min.y, self.min.z, Real::NEG_INFINITY];
+        let max = [self.max.x, self.max.y, self.max.z, Real::INFINITY];
+
+        let origin_x4 = RealX4::new(origin);
+        let inverse_direction = RealX4::new([
+            ray.inverse_direction().x,
+            ray.inverse_direction().y,
+            ray.inverse_direction().z,
+            1.0,
+        ]);
+        let t_for_axis_min = (RealX4::new(min) - origin_x4) * inverse_direction;
+        let t_for_axis_max = (RealX4::new(max) - origin_x4) * inverse_direction;
+        let starts = t_for_axis_min.min(t_for_axis_max).to_array();
+        let ends = t_for_axis_min.max(t_for_axis_max).to_array();
+
+        let mut t_interval = t_range;
+        for axis in 0..3 {
+            // Lanes for slabs parallel to the ray hold meaningless values, so they're handled as in
+            // the scalar version.
+            if direction[axis] == 0.0 {
+                let inside_slab = origin[axis] >= min[axis] && origin[axis] <= max[axis];
+                if !inside_slab {
+                    return false;
+                }
+            } else {
+                t_interval.intersect_mut(&Interval {
+                    start: starts[axis],
+                    end: ends[axis],
+                });
+            }
+        }
+
+        !t_interval.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_cube() -> Bounds3 {
+        Bounds3::new(
+            Vec3 {
+                x: -1.0,
+                y: -1.0,
+                z: -1.0,
+            },
+            Vec3 {
+                x: 1.0,
+                y: 1.0,
+                z: 1.0,
+            },
+        )
+    }
+
+    #[test]
+    fn empty_is_empty_and_the_identity_for_union() {
+        assert!(Bounds3::EMPTY.is_empty());
+        assert!(!unit_cube().is_empty());
+        assert!(!Bounds3::point(Vec3::ZERO).is_empty());
+
+        let union = Bounds3::EMPTY.union(&unit_cube());
+        assert_eq!(union.min, unit_cube().min);
+        assert_eq!(union.max, unit_cube().max);
+    }
+
+    #[test]
+    fn surface_area_and_volume() {
+        let one = Vec3 {
+            x: 1.0,
+            y: 1.0,
+            z: 1.0,
+        };
+        let cube = Bounds3::new(Vec3::ZERO, one);
+        assert_eq!(cube.surface_area(), 6.0);
+        assert_eq!(cube.volume(), 1.0);
+        assert_eq!(unit_cube().surface_area(), 24.0);
+        assert_eq!(unit_cube().volume(), 8.0);
+
+        // A flat box has no volume, but it isn't empty, and both its sides count.
+        let flat = Bounds3::new(
+            Vec3::ZERO,
+            Vec3 {
+                x: 2.0,
+                y: 3.0,
+                z: 0.0,
+            },
+        );
+        assert!(!flat.is_empty());
+        assert_eq!(flat.surface_area(), 12.0);
+        assert_eq!(flat.volume(), 0.0);
+
+        let point = Bounds3::point(one);
+        assert_eq!(point.surface_area(), 0.0);
+        assert_eq!(point.volume(), 0.0);
+
+        assert_eq!(Bounds3::EMPTY.surface_area(), 0.0);
+        assert_eq!(Bounds3::EMPTY.volume(), 0.0);
+        assert_eq!(Bounds3::INFINITE.surface_area(), Real::INFINITY);
+        assert_eq!(Bounds3::INFINITE.volume(), Real::INFINITY);
+    }
+
+    #[test]
+    fn empty_is_never_hit() {
+        let directions = [
+            Vec3 {
+                x: 0.0,
+                y: 0.0,
+                z: -1.0,
+            },
+            Vec3 {
+                x: 1.0,
+                y: -2.0,
+                z: 3.0,
+            },
+        ];
+        for direction in directions {
+            for origin in [Vec3::ZERO, -10.0 * direction] {
+                let ray = Ray::new(origin, direction);
+                assert!(unit_cube().hit_by(&ray, Interval::ALL));
+                assert!(!Bounds3::EMPTY.hit_by(&ray, Interval::ALL));
+                assert!(!Bounds3::EMPTY.hit_by_scalar(&ray, Interval::ALL));
+            }
+        }
+    }
+
+    #[test]
+    fn flat_box_is_hit_head_on() {
+        let flat = Bounds3::new(
+            Vec3 {
+                x: -1.0,
+                y: -1.0,
+                z: 0.0,
+            },
+            Vec3 {
+                x: 1.0,
+                y: 1.0,
+                z: 0.0,
+            },
+        );
+        // The ray is inside the box for exactly one `t`.
+        let ray = Ray::new(
+            Vec3 {
+                x: 0.5,
+                y: 0.5,
+                z: 5.0,
+            },
+            Vec3 {
+                x: 0.0,
+                y: 0.0,
+                z: -1.0,
+            },
+        );
+        assert!(flat.hit_by(&ray, Interval::ALL));
+        assert!(flat.hit_by_scalar(&ray, Interval::ALL));
+
+        let point = Bounds3::point(Vec3 {
+            x: 0.5,
+            y: 0.5,
+            z: 0.0,
+        });
+        assert!(point.hit_by(&ray, Interval::ALL));
+        assert!(point.hit_by_scalar(&ray, Interval::ALL));
+    }
+
+    #[test]
+    fn edge_hit_only_hits_edges() {
+        let cube = unit_cube();
+        let t_range = Interval::new(0.0, Real::INFINITY);
+
+        let through_face = Ray::new(
+            Vec3 {
+                x: 0.2,
+                y: 0.3,
+                z: 5.0,
+            },
+            Vec3 {
+                x: 0.0,
+                y: 0.0,
+                z: -1.0,
+            },
+        );
+        assert!(cube.hit_by(&through_face, t_range));
+        assert_eq!(cube.edge_hit(&through_face, t_range, 0.05), None);
+
+        // Crosses the edge where the `+x` and `+z` faces meet.
+        let through_edge = Ray::new(
+            Vec3 {
+                x: 2.0,
+                y: 0.0,
+                z: 2.0,
+            },
+            Vec3 {
+                x: -1.0,
+                y: 0.0,
+                z: -1.0,
+            },
+        );
+        let t = cube.edge_hit(&through_edge, t_range, 0.05).unwrap();
+        assert!((t - 1.0).abs() < 1e-6, "{}", t);
+    }
+}
+
+#[cfg(all(test, feature = "simd"))]
+mod simd_tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn simd_hit_matches_scalar_hit() {
+        let vec3 = |[x, y, z]: [Real; 3]| Vec3 { x, y, z };
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..100_000 {
+            let bounds = Bounds3::new(
+                Vec3::gen_range(&mut rng, -2.0..2.0),
+                Vec3::gen_range(&mut rng, -2.0..2.0),
+            );
+            let mut origin = [0.0; 3];
+            let mut direction = [0.0; 3];
+            for (axis, (origin, direction)) in Axis3::all()
+                .into_iter()
+                .zip(origin.iter_mut().zip(&mut direction))
+            {
+                // Rays parallel to a slab are the special case, especially when they start exactly
+                // on one of its planes.
+                match rng.gen_range(0..4) {
+                    0 => {
+                        *direction = if rng.gen() { 0.0 } else { -0.0 };
+                        *origin = if rng.gen() {
+                            bounds.min[axis]
+                        } else {
+                            bounds.max[axis]
+                        };
+                    }
+                    1 => {
+                        *direction = 0.0;
+                        *origin = rng.gen_range(-3.0..3.0);
+                    }
+                    _ => {
+                        *direction = rng.gen_range(-1.0..1.0);
+                        *origin = rng.gen_range(-3.0..3.0);
+                    }
+                }
+            }
+            let ray = Ray::new(vec3(origin), vec3(direction));
+            let start = rng.gen_range(-1.0..1.0);
+            let t_range = if rng.gen() {
+                Interval::new(start, Real::INFINITY)
+            } else {
+                Interval::new(start, start + rng.gen_range(0.0..5.0))
+            };
+
+            assert_eq!(
+                bounds.hit_by_simd(&ray, t_range),
+                bounds.hit_by_scalar(&ray, t_range),
+                "{:?} {:?} {:?} {:?}",
+                bounds.min,
+                bounds.max,
+                ray,
+                t_range
+            );
+        }
+    }
 }
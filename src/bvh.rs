@@ -1,23 +1,27 @@
 use crate::{
     bounds::Bounds3,
     hit::Hit,
+    interval::Interval,
     object::{IsObject, Object},
     ray::Ray,
+    real::Real,
     vec3::Vec3,
 };
 use std::sync::Arc;
 
-pub enum Bvh {
-    Empty,
-    Node(BvhNode),
+pub struct Bvh {
+    /// `None` when there are no bounded objects.
+    root: Option<BvhNode>,
+    /**
+    Objects whose bounds aren't finite, like planes. There's no sensible place for them in the
+    hierarchy (and their boxes would make every node above them infinite), so they're tested
+    against every ray.
+    */
+    unbounded: Vec<Object>,
 }
 
 impl From<&[Object]> for Bvh {
     fn from(items: &[Object]) -> Self {
-        if items.is_empty() {
-            return Bvh::Empty;
-        }
-
         #[derive(Clone, Copy)]
         struct ItemWithInfo {
             bounds: Bounds3,
@@ -25,19 +29,38 @@ impl From<&[Object]> for Bvh {
             item: usize,
         }
 
+        /*
+        Items with empty bounds can't be hit, and have no meaningful centroid to partition by.
+        Neither do items with infinite bounds, but they're kept aside.
+        */
+        let mut unbounded = Vec::new();
         let items_with_info: Vec<ItemWithInfo> = items
             .iter()
             .enumerate()
-            .map(|(ix, item)| {
+            .filter_map(|(ix, item)| {
                 let bounds = item.bounds();
-                ItemWithInfo {
-                    bounds,
-                    centroid: bounds.centroid(),
-                    item: ix,
+                if bounds.is_empty() {
+                    None
+                } else if !bounds.is_finite() {
+                    unbounded.push(item.clone());
+                    None
+                } else {
+                    Some(ItemWithInfo {
+                        bounds,
+                        centroid: bounds.centroid(),
+                        item: ix,
+                    })
                 }
             })
             .collect();
 
+        if items_with_info.is_empty() {
+            return Bvh {
+                root: None,
+                unbounded,
+            };
+        }
+
         fn build(items: &[Object], items_with_info: &[ItemWithInfo]) -> BvhNode {
             assert!(!items.is_empty());
 
@@ -97,7 +120,7 @@ impl From<&[Object]> for Bvh {
                             item_with_info.centroid[partition_axis] < midpoint[partition_axis]
                         });
 
-                    assert!(items_with_info_left.len() < items.len());
+                    assert!(items_with_info_left.len() < items_with_info.len());
                     let left = build(items, &items_with_info_left);
                     let right = build(items, &items_with_info_right);
 
@@ -106,23 +129,142 @@ impl From<&[Object]> for Bvh {
             }
         }
 
-        Bvh::Node(build(items, &items_with_info))
+        Bvh {
+            root: Some(build(items, &items_with_info)),
+            unbounded,
+        }
     }
 }
 
-impl IsObject for Bvh {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit> {
-        match self {
-            Bvh::Empty => None,
-            Bvh::Node(node) => node.hit(ray, t_min, t_max),
+/// The work done finding what rays hit, counted by [`Bvh::hit_counted`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TraversalStats {
+    /// Rays tested against the hierarchy.
+    pub rays: u64,
+    /// Nodes whose bounding boxes were tested.
+    pub node_visits: u64,
+    /// Objects tested directly, in leaves or because they're unbounded.
+    pub primitive_tests: u64,
+}
+
+impl TraversalStats {
+    pub fn combine(&mut self, other: &TraversalStats) {
+        self.rays += other.rays;
+        self.node_visits += other.node_visits;
+        self.primitive_tests += other.primitive_tests;
+    }
+
+    /// The average number of objects each ray was tested against. `0` when there were no rays.
+    pub fn tests_per_ray(&self) -> f64 {
+        if self.rays == 0 {
+            0.0
+        } else {
+            self.primitive_tests as f64 / self.rays as f64
         }
     }
+}
 
-    fn bounds(&self) -> Bounds3 {
-        match self {
-            Bvh::Empty => Bounds3::point(Vec3::ZERO),
-            Bvh::Node(node) => node.bounds(),
+/**
+Where [`Bvh::hit_counted`] records its work.
+
+`()` ignores everything, so traversals that aren't counted compile to the same code as
+[`IsObject::hit`].
+*/
+pub trait TraversalCounter {
+    fn ray(&mut self);
+    fn node(&mut self);
+    fn primitives(&mut self, count: usize);
+}
+
+impl TraversalCounter for () {
+    fn ray(&mut self) {}
+    fn node(&mut self) {}
+    fn primitives(&mut self, _: usize) {}
+}
+
+impl TraversalCounter for TraversalStats {
+    fn ray(&mut self) {
+        self.rays += 1;
+    }
+
+    fn node(&mut self) {
+        self.node_visits += 1;
+    }
+
+    fn primitives(&mut self, count: usize) {
+        self.primitive_tests += count as u64;
+    }
+}
+
+impl Bvh {
+    /**
+    Like [`IsObject::hit`], but records the nodes and objects tested in `counter`. Objects inside
+    other objects, such as a nested hierarchy, count as one test.
+    */
+    pub fn hit_counted<C: TraversalCounter>(
+        &self,
+        ray: &Ray,
+        t_range: Interval,
+        counter: &mut C,
+    ) -> Option<Hit> {
+        counter.ray();
+        let hit = self
+            .root
+            .as_ref()
+            .and_then(|root| root.hit_counted(ray, t_range, counter));
+        let t_range = match &hit {
+            Some(hit) => Interval {
+                end: hit.t,
+                ..t_range
+            },
+            None => t_range,
+        };
+        counter.primitives(self.unbounded.len());
+        self.unbounded.hit(ray, t_range).or(hit)
+    }
+
+    /**
+    The object closest to `point`, and how far away it is (`0` if `point` is inside it), using
+    [`IsObject::distance`]. `None` when there are no objects.
+
+    Subtrees whose bounding boxes are further away than the closest object so far are skipped.
+    */
+    pub fn nearest(&self, point: Vec3) -> Option<(Real, &Object)> {
+        let mut nearest = None;
+        for object in &self.unbounded {
+            closer(&mut nearest, point, object);
         }
+        if let Some(root) = &self.root {
+            root.nearest(point, &mut nearest);
+        }
+        nearest
+    }
+}
+
+/// Replace `nearest` with `object` if it's closer to `point`.
+fn closer<'a>(nearest: &mut Option<(Real, &'a Object)>, point: Vec3, object: &'a Object) {
+    let distance = object.distance(point);
+    if nearest.is_none_or(|(nearest_distance, _)| distance < nearest_distance) {
+        *nearest = Some((distance, object));
+    }
+}
+
+impl IsObject for Bvh {
+    fn hit(&self, ray: &Ray, t_range: Interval) -> Option<Hit> {
+        self.hit_counted(ray, t_range, &mut ())
+    }
+
+    fn bounds(&self) -> Bounds3 {
+        let bounds = match &self.root {
+            Some(root) => root.bounds(),
+            None => Bounds3::EMPTY,
+        };
+        bounds.union(&self.unbounded.bounds())
+    }
+
+    fn distance(&self, point: Vec3) -> Real {
+        self.nearest(point)
+            .map_or(Real::INFINITY, |(distance, _)| distance)
     }
 }
 
@@ -146,36 +288,68 @@ impl BvhNode {
         }
     }
 
-    fn branch(left: BvhNode, right: BvhNode) -> Self {
-        let bounds = left.bounds().union(&right.bounds());
-        BvhNode::Branch {
-            bounds,
-            left: Arc::new(left),
-            right: Arc::new(right),
+    /// Search the node for an object closer to `point` than `nearest`.
+    fn nearest<'a>(&'a self, point: Vec3, nearest: &mut Option<(Real, &'a Object)>) {
+        if let Some((distance, _)) = nearest {
+            if self.bounds().distance(point) >= *distance {
+                return;
+            }
+        }
+        match self {
+            BvhNode::Branch { left, right, .. } => {
+                // The nearer child first, so that the further one is more likely to be skipped.
+                let (first, second) =
+                    if left.bounds().distance(point) <= right.bounds().distance(point) {
+                        (left, right)
+                    } else {
+                        (right, left)
+                    };
+                first.nearest(point, nearest);
+                second.nearest(point, nearest);
+            }
+            BvhNode::Leaf { items, .. } => {
+                for item in items {
+                    closer(nearest, point, item);
+                }
+            }
         }
     }
-}
 
-impl IsObject for BvhNode {
-    fn hit(&self, ray: &crate::ray::Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+    fn hit_counted<C: TraversalCounter>(
+        &self,
+        ray: &Ray,
+        t_range: Interval,
+        counter: &mut C,
+    ) -> Option<Hit> {
+        counter.node();
         match self {
             BvhNode::Branch {
                 bounds,
                 left,
                 right,
             } => {
-                if bounds.hit_by(ray, t_min, t_max) {
-                    match left.hit(ray, t_min, t_max) {
-                        Some(left_hit) => right.hit(ray, t_min, left_hit.t).or(Some(left_hit)),
-                        None => right.hit(ray, t_min, t_max),
+                if bounds.hit_by(ray, t_range) {
+                    match left.hit_counted(ray, t_range, counter) {
+                        Some(left_hit) => right
+                            .hit_counted(
+                                ray,
+                                Interval {
+                                    end: left_hit.t,
+                                    ..t_range
+                                },
+                                counter,
+                            )
+                            .or(Some(left_hit)),
+                        None => right.hit_counted(ray, t_range, counter),
                     }
                 } else {
                     None
                 }
             }
             BvhNode::Leaf { bounds, items } => {
-                if bounds.hit_by(ray, t_min, t_max) {
-                    items.hit(ray, t_min, t_max)
+                if bounds.hit_by(ray, t_range) {
+                    counter.primitives(items.len());
+                    items.hit(ray, t_range)
                 } else {
                     None
                 }
@@ -183,6 +357,21 @@ impl IsObject for BvhNode {
         }
     }
 
+    fn branch(left: BvhNode, right: BvhNode) -> Self {
+        let bounds = left.bounds().union(&right.bounds());
+        BvhNode::Branch {
+            bounds,
+            left: Arc::new(left),
+            right: Arc::new(right),
+        }
+    }
+}
+
+impl IsObject for BvhNode {
+    fn hit(&self, ray: &crate::ray::Ray, t_range: Interval) -> Option<Hit> {
+        self.hit_counted(ray, t_range, &mut ())
+    }
+
     fn bounds(&self) -> Bounds3 {
         match self {
             BvhNode::Branch { bounds, .. } => *bounds,
@@ -190,3 +379,53 @@ impl IsObject for BvhNode {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        material::{Material, Normals},
+        sphere::Sphere,
+        texture::UvTransform,
+    };
+
+    #[test]
+    fn nearest_finds_the_closest_sphere() {
+        let objects: Vec<Object> = (0..10)
+            .map(|i| {
+                Object::new(Sphere {
+                    center: Vec3 {
+                        x: 3.0 * i as Real,
+                        y: 0.0,
+                        z: 0.0,
+                    },
+                    radius: 0.5,
+                    material: Material::new(Normals()),
+                    uv_transform: UvTransform::default(),
+                })
+            })
+            .collect();
+        let bvh = Bvh::from(objects.as_slice());
+
+        let point = Vec3 {
+            x: 6.2,
+            y: 2.0,
+            z: 0.0,
+        };
+        let (distance, object) = bvh.nearest(point).unwrap();
+        let expected = (0.2 as Real).hypot(2.0) - 0.5;
+        assert!((distance - expected).abs() < 1e-6, "{}", distance);
+        assert!((object.bounds().min().x - 5.5).abs() < 1e-6);
+
+        let inside = Vec3 {
+            x: 27.1,
+            y: 0.0,
+            z: 0.0,
+        };
+        let (distance, object) = bvh.nearest(inside).unwrap();
+        assert_eq!(distance, 0.0);
+        assert!((object.bounds().min().x - 26.5).abs() < 1e-6);
+
+        assert!(Bvh::from(&[][..]).nearest(point).is_none());
+    }
+}
@@ -1,5 +1,160 @@
-use crate::{ray::Ray, vec3::Vec3};
+use crate::{
+    onb::Onb,
+    ray::Ray,
+    real::{consts, Real},
+    sampling::{random_in_regular_polygon, random_in_unit_disc},
+    vec3::Vec3,
+};
+use image::{GenericImageView, ImageError};
 use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// How the camera maps points on the image to rays.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Projection {
+    /// Rays spread out from a single point, so distant objects look smaller.
+    #[default]
+    Perspective,
+    /**
+    Rays are parallel, so objects look the same size at any distance.
+
+    `scale` is the height of the view, in world units. The field of view and depth of field
+    settings are ignored.
+    */
+    Orthographic { scale: Real },
+    /**
+    A full 360 by 180 degree panorama around the camera.
+
+    The horizontal axis of the image is longitude and the vertical axis is latitude, so the image
+    should be twice as wide as it is tall. The centre of the image looks toward `look_at`. The
+    field of view and depth of field settings are ignored.
+    */
+    Equirectangular,
+}
+
+/**
+The shape of the lens opening, which out-of-focus highlights (bokeh) take on.
+
+Every shape fits inside the circle given by the aperture diameter.
+*/
+#[derive(Clone, Default)]
+pub enum Aperture {
+    #[default]
+    Circle,
+    /// A regular polygon with a corner at the top, like the opening made by a lens' blades.
+    /// Needs at least 3 blades.
+    Polygon {
+        blades: u32,
+    },
+    Custom(Arc<ApertureMask>),
+}
+
+impl Aperture {
+    /// A random point on the lens, uniformly distributed by area, within the unit disc in the XY
+    /// plane.
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3 {
+        match self {
+            Aperture::Circle => random_in_unit_disc(rng),
+            Aperture::Polygon { blades } => random_in_regular_polygon(rng, *blades),
+            Aperture::Custom(mask) => mask.sample(rng),
+        }
+    }
+}
+
+/// An image whose alpha channel gives the shape of the lens opening. The image is fit to the
+/// square around the lens, so its inscribed circle is the lens' edge, and anything outside the
+/// circle is ignored.
+pub struct ApertureMask {
+    width: usize,
+    height: usize,
+    /// Opacity, row-major from the top of the image.
+    alpha: Vec<Real>,
+}
+
+#[derive(Debug)]
+pub enum ApertureMaskError {
+    Image(ImageError),
+    /// The mask is transparent everywhere inside the lens, so light can't get through.
+    Empty,
+}
+
+impl std::fmt::Display for ApertureMaskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApertureMaskError::Image(err) => err.fmt(f),
+            ApertureMaskError::Empty => {
+                f.write_str("aperture mask is transparent everywhere inside the lens")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ApertureMaskError {}
+
+impl From<ImageError> for ApertureMaskError {
+    fn from(err: ImageError) -> Self {
+        ApertureMaskError::Image(err)
+    }
+}
+
+impl ApertureMask {
+    /// Load a mask from an image file. Images without an alpha channel are opaque everywhere.
+    pub fn open(path: &str) -> Result<Self, ApertureMaskError> {
+        let image = image::open(path)?;
+        let (width, height) = (image.width(), image.height());
+        let alpha: Vec<Real> = image
+            .to_rgba8()
+            .enumerate_pixels()
+            .map(|(column, row, pixel)| {
+                // Pixels centred outside the lens are never sampled.
+                let x = 2.0 * (column as Real + 0.5) / width as Real - 1.0;
+                let y = 2.0 * (row as Real + 0.5) / height as Real - 1.0;
+                if x * x + y * y > 1.0 {
+                    0.0
+                } else {
+                    pixel[3] as Real / 255.0
+                }
+            })
+            .collect();
+        if alpha.iter().all(|alpha| *alpha == 0.0) {
+            return Err(ApertureMaskError::Empty);
+        }
+        Ok(ApertureMask {
+            width: width as usize,
+            height: height as usize,
+            alpha,
+        })
+    }
+
+    /**
+    A random point in the unit disc in the XY plane, with density proportional to the mask's
+    opacity.
+
+    Points are chosen uniformly from the square around the disc and kept if they're inside it,
+    with probability equal to the opacity beneath them, so masks that are mostly transparent take
+    longer to sample.
+    */
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3 {
+        loop {
+            let (s, t) = (rng.gen::<Real>(), rng.gen::<Real>());
+            let point = Vec3 {
+                x: 2.0 * s - 1.0,
+                y: 2.0 * t - 1.0,
+                z: 0.0,
+            };
+            if point.norm_squared() > 1.0 {
+                continue;
+            }
+            let column = ((s * self.width as Real) as usize).min(self.width - 1);
+            let row = (((1.0 - t) * self.height as Real) as usize).min(self.height - 1);
+            if rng.gen::<Real>() < self.alpha[row * self.width + column] {
+                return point;
+            }
+        }
+    }
+}
 
 pub struct Camera {
     origin: Vec3,
@@ -9,46 +164,331 @@ pub struct Camera {
     horizontal: Vec3,
     vertical: Vec3,
     lower_left_corner: Vec3,
-    lens_radius: f64,
+    lens_radius: Real,
+    aperture_shape: Aperture,
+    projection: Projection,
+}
+
+#[derive(Debug)]
+pub enum CameraError {
+    LookFromEqualsLookAt,
+    UpParallelToView,
+    /// A polygonal aperture has fewer than 3 blades.
+    TooFewBlades,
+    /// One of the vector parameters has a NaN component.
+    ContainsNan,
+    /// The pixel aspect ratio isn't a positive number.
+    InvalidPixelAspect,
 }
 
+impl std::fmt::Display for CameraError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CameraError::LookFromEqualsLookAt => {
+                f.write_str("the camera is looking at its own position")
+            }
+            CameraError::UpParallelToView => {
+                f.write_str("up vector and look direction are parallel")
+            }
+            CameraError::TooFewBlades => {
+                f.write_str("a polygonal aperture needs at least 3 blades")
+            }
+            CameraError::ContainsNan => f.write_str("camera parameters contain NaN"),
+            CameraError::InvalidPixelAspect => {
+                f.write_str("pixel aspect ratio must be a positive number")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CameraError {}
+
 impl Camera {
+    /// Like [`Camera::try_new`], but panics when the parameters are invalid.
     pub fn new(
-        aspect_ratio: f64,
-        v_fov: f64,
+        aspect_ratio: Real,
+        v_fov: Real,
         up: &Vec3,
         look_from: &Vec3,
         look_at: &Vec3,
-        aperture: f64,
-        focal_distance: f64,
+        aperture: Real,
+        focal_distance: Real,
     ) -> Self {
-        debug_assert!(!up.contains_nan(), "up: {:?}", up);
-        debug_assert!(!look_from.contains_nan(), "look_from: {:?}", look_from);
-        debug_assert!(!look_at.contains_nan(), "look_at: {:?}", look_at);
+        Camera::try_new(
+            aspect_ratio,
+            v_fov,
+            up,
+            look_from,
+            look_at,
+            aperture,
+            focal_distance,
+        )
+        .expect("invalid camera")
+    }
+
+    pub fn try_new(
+        aspect_ratio: Real,
+        v_fov: Real,
+        up: &Vec3,
+        look_from: &Vec3,
+        look_at: &Vec3,
+        aperture: Real,
+        focal_distance: Real,
+    ) -> Result<Self, CameraError> {
+        Camera::builder()
+            .aspect_ratio(aspect_ratio)
+            .v_fov(v_fov)
+            .up(*up)
+            .look_from(*look_from)
+            .look_at(*look_at)
+            .aperture(aperture)
+            .focal_distance(focal_distance)
+            .build()
+    }
+
+    pub fn builder() -> CameraBuilder {
+        CameraBuilder::default()
+    }
+
+    pub fn get_ray<R: Rng + ?Sized>(&self, rng: &mut R, s: Real, t: Real) -> Ray {
+        match self.projection {
+            Projection::Perspective => {}
+            Projection::Orthographic { .. } => {
+                return Ray::new(
+                    self.lower_left_corner + s * self.horizontal + t * self.vertical,
+                    -self.w,
+                );
+            }
+            Projection::Equirectangular => {
+                // Angle from straight down.
+                let theta = consts::PI * t;
+                // Angle to the right of straight ahead.
+                let phi = 2.0 * consts::PI * (s - 0.5);
+                let direction =
+                    theta.sin() * (phi.sin() * self.u - phi.cos() * self.w) - theta.cos() * self.v;
+                return Ray::new(self.origin, direction);
+            }
+        }
+
+        let point_on_lens = self.lens_radius * self.aperture_shape.sample(rng);
+        debug_assert!(
+            !point_on_lens.contains_nan(),
+            "point_on_lens: {:?}",
+            point_on_lens
+        );
+
+        debug_assert!(!self.u.contains_nan(), "self.u: {:?}", self.u);
+        debug_assert!(!self.v.contains_nan(), "self.v: {:?}", self.v);
+        let offset = point_on_lens.x * self.u + point_on_lens.y * self.v;
+        debug_assert!(!offset.contains_nan(), "offset: {:?}", offset);
+
+        let origin = self.origin + offset;
+        debug_assert!(!origin.contains_nan(), "origin: {:?}", origin);
+
+        let direction =
+            self.lower_left_corner + s * self.horizontal + t * self.vertical - self.origin - offset;
+        debug_assert!(!direction.contains_nan(), "direction: {:?}", direction);
+
+        Ray::new(origin, direction)
+    }
+}
+
+/**
+Named, defaulted camera parameters.
+
+Defaults to a camera at the origin looking down the negative Z axis, with a 40 degree vertical
+field of view, a square image, +Y as up, and no depth of field.
+*/
+pub struct CameraBuilder {
+    aspect_ratio: Real,
+    pixel_aspect: Real,
+    v_fov: Real,
+    up: Vec3,
+    look_from: Vec3,
+    look_at: Vec3,
+    aperture: Real,
+    aperture_shape: Aperture,
+    focal_distance: Option<Real>,
+    projection: Projection,
+}
+
+impl Default for CameraBuilder {
+    fn default() -> Self {
+        CameraBuilder {
+            aspect_ratio: 1.0,
+            pixel_aspect: 1.0,
+            v_fov: 40.0,
+            up: Vec3 {
+                x: 0.0,
+                y: 1.0,
+                z: 0.0,
+            },
+            look_from: Vec3::ZERO,
+            look_at: Vec3 {
+                x: 0.0,
+                y: 0.0,
+                z: -1.0,
+            },
+            aperture: 0.0,
+            aperture_shape: Aperture::Circle,
+            focal_distance: None,
+            projection: Projection::Perspective,
+        }
+    }
+}
+
+impl CameraBuilder {
+    /// Image width divided by image height.
+    #[must_use]
+    pub fn aspect_ratio(mut self, aspect_ratio: Real) -> Self {
+        self.aspect_ratio = aspect_ratio;
+        self
+    }
+
+    /**
+    The width of each pixel divided by its height, for displays whose pixels aren't square. The
+    viewport is widened by this much, so that things look right once the image is stretched to
+    match. Defaults to 1.
+
+    ```
+    use rand::{rngs::StdRng, SeedableRng};
+    use rt_weekend::camera::Camera;
+
+    // How far to the side the ray through the middle of the right edge of the image goes, for
+    // each unit forward.
+    let right_edge = |pixel_aspect| {
+        let camera = Camera::builder().pixel_aspect(pixel_aspect).build().unwrap();
+        let ray = camera.get_ray(&mut StdRng::seed_from_u64(0), 1.0, 0.5);
+        ray.direction.x / -ray.direction.z
+    };
+    assert!((right_edge(2.0) - 2.0 * right_edge(1.0)).abs() < 1e-9);
+    ```
+    */
+    #[must_use]
+    pub fn pixel_aspect(mut self, pixel_aspect: Real) -> Self {
+        self.pixel_aspect = pixel_aspect;
+        self
+    }
+
+    /// Vertical field of view, in degrees.
+    #[must_use]
+    pub fn v_fov(mut self, v_fov: Real) -> Self {
+        self.v_fov = v_fov;
+        self
+    }
 
-        let viewport_height = 2.0 * (v_fov.to_radians() / 2.0).tan();
-        let viewport_width = viewport_height * aspect_ratio;
+    #[must_use]
+    pub fn up(mut self, up: Vec3) -> Self {
+        self.up = up;
+        self
+    }
+
+    #[must_use]
+    pub fn look_from(mut self, look_from: Vec3) -> Self {
+        self.look_from = look_from;
+        self
+    }
+
+    #[must_use]
+    pub fn look_at(mut self, look_at: Vec3) -> Self {
+        self.look_at = look_at;
+        self
+    }
 
-        let origin = *look_from;
+    /// Lens diameter. `0.0` puts everything in focus.
+    #[must_use]
+    pub fn aperture(mut self, aperture: Real) -> Self {
+        self.aperture = aperture;
+        self
+    }
+
+    #[must_use]
+    pub fn aperture_shape(mut self, aperture_shape: Aperture) -> Self {
+        self.aperture_shape = aperture_shape;
+        self
+    }
+
+    /// Distance to the plane that's in focus. Defaults to the distance to `look_at`.
+    #[must_use]
+    pub fn focal_distance(mut self, focal_distance: Real) -> Self {
+        self.focal_distance = Some(focal_distance);
+        self
+    }
 
-        let w = (*look_from - *look_at).unit();
-        debug_assert!(!w.contains_nan(), "w: {:?}", w);
+    #[must_use]
+    pub fn projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    pub fn build(self) -> Result<Camera, CameraError> {
+        let CameraBuilder {
+            aspect_ratio,
+            pixel_aspect,
+            v_fov,
+            up,
+            look_from,
+            look_at,
+            aperture,
+            aperture_shape,
+            focal_distance,
+            projection,
+        } = self;
+
+        if let Aperture::Polygon { blades } = aperture_shape {
+            if blades < 3 {
+                return Err(CameraError::TooFewBlades);
+            }
+        }
+
+        if !(pixel_aspect > 0.0 && pixel_aspect.is_finite()) {
+            return Err(CameraError::InvalidPixelAspect);
+        }
+
+        if up.contains_nan() || look_from.contains_nan() || look_at.contains_nan() {
+            return Err(CameraError::ContainsNan);
+        }
 
-        if up.unit() == w {
-            panic!("up vector and look direction are the same");
+        if look_from == look_at {
+            return Err(CameraError::LookFromEqualsLookAt);
         }
+        let focal_distance = focal_distance.unwrap_or_else(|| (look_from - look_at).norm());
 
-        let u = up.cross(w).unit();
-        debug_assert!(!u.contains_nan(), "u: {:?}", u);
+        let origin = look_from;
 
-        let v = w.cross(u).unit();
-        debug_assert!(!v.contains_nan(), "v: {:?}", v);
+        let Onb { u, v, w } =
+            Onb::from_w_and_up(look_from - look_at, up).ok_or(CameraError::UpParallelToView)?;
+        debug_assert!(
+            !(u.contains_nan() || v.contains_nan() || w.contains_nan()),
+            "u: {:?}, v: {:?}, w: {:?}",
+            u,
+            v,
+            w
+        );
+
+        let (horizontal, vertical, lower_left_corner) = match projection {
+            Projection::Perspective => {
+                let viewport_height = 2.0 * (v_fov.to_radians() / 2.0).tan();
+                let viewport_width = viewport_height * aspect_ratio * pixel_aspect;
 
-        let horizontal = focal_distance * viewport_width * u;
-        let vertical = focal_distance * viewport_height * v;
-        let lower_left_corner = origin - horizontal / 2.0 - vertical / 2.0 - focal_distance * w;
+                let horizontal = focal_distance * viewport_width * u;
+                let vertical = focal_distance * viewport_height * v;
+                let lower_left_corner =
+                    origin - horizontal / 2.0 - vertical / 2.0 - focal_distance * w;
+                (horizontal, vertical, lower_left_corner)
+            }
+            // The viewport is in the plane of the camera, and rays start on it.
+            Projection::Orthographic { scale } => {
+                let horizontal = scale * aspect_ratio * pixel_aspect * u;
+                let vertical = scale * v;
+                let lower_left_corner = origin - horizontal / 2.0 - vertical / 2.0;
+                (horizontal, vertical, lower_left_corner)
+            }
+            // Rays are computed directly from the image coordinates.
+            Projection::Equirectangular => (Vec3::ZERO, Vec3::ZERO, origin),
+        };
 
-        Camera {
+        Ok(Camera {
             origin,
             horizontal,
             vertical,
@@ -57,46 +497,168 @@ impl Camera {
             v,
             w,
             lens_radius: aperture / 2.0,
+            aperture_shape,
+            projection,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec3::assert_vec3_eq;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn up_parallel_to_view_is_an_error() {
+        let look_from = Vec3 {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+        };
+        let up = Vec3 {
+            x: 0.0,
+            y: 2.0,
+            z: 0.0,
+        };
+        for look_at in [look_from + up, look_from - 3.0 * up] {
+            let result = Camera::builder()
+                .look_from(look_from)
+                .look_at(look_at)
+                .up(up)
+                .build();
+            assert!(
+                matches!(result, Err(CameraError::UpParallelToView)),
+                "looking at {:?}",
+                look_at
+            );
         }
     }
 
-    pub fn get_ray(&self, s: f64, t: f64) -> Ray {
-        // Compute a random point inside the unit disc
-        fn random_in_unit_disc() -> Vec3 {
-            let mut rng = rand::thread_rng();
-            loop {
-                let p = Vec3 {
-                    x: rng.gen_range(-1.0..1.0),
-                    y: rng.gen_range(-1.0..1.0),
-                    z: 0.0,
-                };
-                if p.norm_squared() >= 1.0 {
-                    continue;
-                } else {
-                    return p;
-                }
+    #[test]
+    fn focus_defaults_to_the_distance_to_look_at() {
+        let look_from = Vec3 {
+            x: 3.0,
+            y: 2.0,
+            z: 5.0,
+        };
+        let look_at = Vec3 {
+            x: -1.0,
+            y: 0.5,
+            z: 0.0,
+        };
+        let camera = Camera::builder()
+            .look_from(look_from)
+            .look_at(look_at)
+            .build()
+            .unwrap();
+        // Without depth of field, the center ray reaches the focal plane at `t = 1`.
+        let ray = camera.get_ray(&mut StdRng::seed_from_u64(0), 0.5, 0.5);
+        let expected = (look_from - look_at).norm();
+        assert!(
+            (ray.direction.norm() - expected).abs() < 1e-4,
+            "{} != {}",
+            ray.direction.norm(),
+            expected
+        );
+        assert_vec3_eq!(ray.origin + ray.direction, look_at, 1e-4);
+    }
+
+    #[test]
+    fn orthographic_rays_are_parallel() {
+        let look_from = Vec3 {
+            x: 1.0,
+            y: 4.0,
+            z: 2.0,
+        };
+        let look_at = Vec3::ZERO;
+        let camera = Camera::builder()
+            .look_from(look_from)
+            .look_at(look_at)
+            .projection(Projection::Orthographic { scale: 3.0 })
+            .build()
+            .unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
+        let view = (look_at - look_from).unit();
+
+        let corners = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0), (0.5, 0.5)];
+        let rays: Vec<Ray> = corners
+            .iter()
+            .map(|&(s, t)| camera.get_ray(&mut rng, s, t))
+            .collect();
+        for ray in &rays {
+            assert_vec3_eq!(ray.direction.unit(), view, 1e-5);
+        }
+        for (i, a) in rays.iter().enumerate() {
+            for b in &rays[i + 1..] {
+                assert!(!a.origin.approx_eq(b.origin, 1e-3), "{:?}", a.origin);
             }
         }
+        // The middle ray starts at the camera.
+        assert_vec3_eq!(rays[4].origin, look_from, 1e-5);
+    }
 
-        let point_on_lens = self.lens_radius * random_in_unit_disc();
-        debug_assert!(
-            !point_on_lens.contains_nan(),
-            "point_on_lens: {:?}",
-            point_on_lens
-        );
+    #[test]
+    fn equirectangular_center_looks_at_look_at() {
+        let look_from = Vec3 {
+            x: -2.0,
+            y: 1.0,
+            z: 3.0,
+        };
+        let look_at = Vec3 {
+            x: 1.0,
+            y: 0.0,
+            z: -1.0,
+        };
+        let camera = Camera::builder()
+            .look_from(look_from)
+            .look_at(look_at)
+            .projection(Projection::Equirectangular)
+            .build()
+            .unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
 
-        debug_assert!(!self.u.contains_nan(), "self.u: {:?}", self.u);
-        debug_assert!(!self.v.contains_nan(), "self.v: {:?}", self.v);
-        let offset = point_on_lens.x * self.u + point_on_lens.y * self.v;
-        debug_assert!(!offset.contains_nan(), "offset: {:?}", offset);
+        let center = camera.get_ray(&mut rng, 0.5, 0.5);
+        assert_vec3_eq!(center.origin, look_from);
+        assert_vec3_eq!(center.direction.unit(), (look_at - look_from).unit(), 1e-5);
 
-        let origin = self.origin + offset;
-        debug_assert!(!origin.contains_nan(), "origin: {:?}", origin);
+        // The top and bottom rows look in opposite directions, perpendicular to the view.
+        let top = camera.get_ray(&mut rng, 0.3, 1.0).direction.unit();
+        let bottom = camera.get_ray(&mut rng, 0.8, 0.0).direction.unit();
+        assert_vec3_eq!(top, -bottom, 1e-5);
+        assert!(top.dot(center.direction).abs() < 1e-5);
+    }
 
-        let direction =
-            self.lower_left_corner + s * self.horizontal + t * self.vertical - self.origin - offset;
-        debug_assert!(!direction.contains_nan(), "direction: {:?}", direction);
+    /// The mean and mean squared distance from the center of `samples` points on `aperture`.
+    fn aperture_moments(aperture: &Aperture, samples: usize) -> (Vec3, Real) {
+        let mut rng = StdRng::seed_from_u64(0);
+        let (mut sum, mut sum_squared) = (Vec3::ZERO, 0.0);
+        for _ in 0..samples {
+            let point = aperture.sample(&mut rng);
+            assert!(point.norm_squared() <= 1.0, "{:?}", point);
+            sum += point;
+            sum_squared += point.norm_squared();
+        }
+        (sum / samples as Real, sum_squared / samples as Real)
+    }
+
+    #[test]
+    fn circle_is_uniform_over_the_unit_disc() {
+        let (mean, mean_squared) = aperture_moments(&Aperture::Circle, 100_000);
+        assert!(mean.norm() < 0.01, "{:?}", mean);
+        // For a uniform disc, r^2 is uniform on [0, 1].
+        assert!((mean_squared - 0.5).abs() < 0.01, "{}", mean_squared);
+    }
 
-        Ray { origin, direction }
+    #[test]
+    fn opaque_mask_samples_stay_inside_the_lens() {
+        let mask = ApertureMask {
+            width: 4,
+            height: 4,
+            alpha: vec![1.0; 16],
+        };
+        let (mean, mean_squared) = aperture_moments(&Aperture::Custom(Arc::new(mask)), 100_000);
+        assert!(mean.norm() < 0.01, "{:?}", mean);
+        assert!((mean_squared - 0.5).abs() < 0.01, "{}", mean_squared);
     }
 }
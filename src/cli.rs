@@ -1,6 +1,12 @@
-use std::fmt::Write;
+use std::{fmt::Write, str::FromStr};
 
-use clap::Parser;
+use clap::{ArgEnum, Parser};
+use rt_weekend::{
+    color::{Color, ParseColorError},
+    real::Real,
+    render::Crop,
+    vec3::Vec3,
+};
 
 pub struct Dimensions {
     pub width: usize,
@@ -19,6 +25,8 @@ impl std::fmt::Display for Dimensions {
 pub enum ParseDimensionsError {
     ParseIntError(std::num::ParseIntError),
     MissingComma,
+    /// The width or height is 0.
+    Empty,
 }
 
 impl From<std::num::ParseIntError> for ParseDimensionsError {
@@ -27,7 +35,7 @@ impl From<std::num::ParseIntError> for ParseDimensionsError {
     }
 }
 
-impl std::str::FromStr for Dimensions {
+impl FromStr for Dimensions {
     type Err = ParseDimensionsError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -36,6 +44,9 @@ impl std::str::FromStr for Dimensions {
             .ok_or(ParseDimensionsError::MissingComma)?;
         let width = usize::from_str(prefix)?;
         let height = usize::from_str(suffix)?;
+        if width == 0 || height == 0 {
+            return Err(ParseDimensionsError::Empty);
+        }
         Ok(Dimensions { width, height })
     }
 }
@@ -47,12 +58,202 @@ impl std::fmt::Display for ParseDimensionsError {
             ParseDimensionsError::MissingComma => {
                 f.write_str("dimensions value is missing a comma")
             }
+            ParseDimensionsError::Empty => f.write_str("width and height must be at least 1"),
         }
     }
 }
 
 impl std::error::Error for ParseDimensionsError {}
 
+#[derive(Debug)]
+pub enum ParseComponentsError<E> {
+    Component(E),
+    WrongComponentCount { expected: usize, found: usize },
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for ParseComponentsError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseComponentsError::Component(err) => err.fmt(f),
+            ParseComponentsError::WrongComponentCount { expected, found } => write!(
+                f,
+                "expected {} comma-separated components, got {}",
+                expected, found
+            ),
+        }
+    }
+}
+
+impl<E: std::fmt::Debug + std::fmt::Display> std::error::Error for ParseComponentsError<E> {}
+
+/// Parse exactly `N` comma-separated values.
+fn parse_components<T: FromStr, const N: usize>(
+    s: &str,
+) -> Result<[T; N], ParseComponentsError<T::Err>> {
+    let components = s
+        .split(',')
+        .map(|component| T::from_str(component.trim()))
+        .collect::<Result<Vec<T>, _>>()
+        .map_err(ParseComponentsError::Component)?;
+    components.try_into().map_err(
+        |components: Vec<T>| ParseComponentsError::WrongComponentCount {
+            expected: N,
+            found: components.len(),
+        },
+    )
+}
+
+#[derive(Debug)]
+pub enum ParseColorArgError {
+    Components(ParseComponentsError<std::num::ParseFloatError>),
+    Hex(ParseColorError),
+}
+
+impl std::fmt::Display for ParseColorArgError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseColorArgError::Components(err) => err.fmt(f),
+            ParseColorArgError::Hex(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ParseColorArgError {}
+
+/// A color written as linear `r,g,b`, or as sRGB hex `#rrggbb`.
+pub struct ColorArg(pub Color);
+
+impl FromStr for ColorArg {
+    type Err = ParseColorArgError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains(',') {
+            let [r, g, b] = parse_components(s).map_err(ParseColorArgError::Components)?;
+            Ok(ColorArg(Color { r, g, b }))
+        } else {
+            Color::from_hex(s)
+                .map(ColorArg)
+                .map_err(ParseColorArgError::Hex)
+        }
+    }
+}
+
+/// A vector written as `x,y,z`.
+pub struct Vec3Arg(pub Vec3);
+
+impl std::fmt::Display for Vec3Arg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{},{}", self.0.x, self.0.y, self.0.z)
+    }
+}
+
+impl FromStr for Vec3Arg {
+    type Err = ParseComponentsError<std::num::ParseFloatError>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [x, y, z] = parse_components(s)?;
+        Ok(Vec3Arg(Vec3 { x, y, z }))
+    }
+}
+
+/// A rectangle of pixels written as `x0,y0,x1,y1`.
+pub struct CropArg(pub Crop);
+
+impl FromStr for CropArg {
+    type Err = ParseComponentsError<std::num::ParseIntError>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [x0, y0, x1, y1] = parse_components(s)?;
+        Ok(CropArg(Crop { x0, y0, x1, y1 }))
+    }
+}
+
+/// A range of values written as `lo,hi`.
+pub struct RangeArg(pub Real, pub Real);
+
+impl FromStr for RangeArg {
+    type Err = ParseComponentsError<std::num::ParseFloatError>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [lo, hi] = parse_components(s)?;
+        Ok(RangeArg(lo, hi))
+    }
+}
+
+/// A pixel written as `x,y`, counted from the top left of the image.
+pub struct PixelArg(pub usize, pub usize);
+
+impl FromStr for PixelArg {
+    type Err = ParseComponentsError<std::num::ParseIntError>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [x, y] = parse_components(s)?;
+        Ok(PixelArg(x, y))
+    }
+}
+
+#[derive(Clone, ArgEnum)]
+pub enum BackgroundKind {
+    Gradient,
+    Solid,
+    Black,
+}
+
+#[derive(Clone, ArgEnum)]
+pub enum ProjectionKind {
+    Perspective,
+    Ortho,
+    /// A 360 degree panorama. Forces a 2:1 image.
+    Equirectangular,
+}
+
+#[derive(Clone, ArgEnum)]
+pub enum SamplerKind {
+    /// Independently random positions, which can clump together.
+    Random,
+    /// One random position in each cell of a grid.
+    Stratified,
+    /// Evenly spread positions from the Halton sequence, shifted differently in each pixel.
+    Halton,
+}
+
+#[derive(Clone, ArgEnum)]
+pub enum FilterKind {
+    /// Every ray in a pixel counts equally.
+    Box,
+    /// Rays count less the further they are from the pixel's center, out to one pixel away.
+    Tent,
+    /// A Gaussian falloff out to 1.5 pixels away.
+    Gaussian,
+}
+
+#[derive(Clone, ArgEnum)]
+pub enum ExhaustedKind {
+    /// No light, or `--depth-exhausted-color` if it's given.
+    Black,
+    /// The background, as if the path had escaped.
+    Background,
+}
+
+#[derive(Clone, ArgEnum)]
+pub enum NonFiniteKind {
+    /// Bright enough to spot.
+    Magenta,
+    Black,
+}
+
+#[derive(Clone, ArgEnum)]
+pub enum DebugMode {
+    /// Draw every object's surface normals instead of its material.
+    Normals,
+    /// Show paths that reach `--recursion-depth` in magenta, to find where it should be higher.
+    DepthExhaustion,
+    /// Draw the edges of every object's bounding box instead of the objects.
+    Aabb,
+    /// Draw each object in its own flat color.
+    Objects,
+}
+
 #[derive(Parser)]
 pub struct Cli {
     /// Image dimensions.
@@ -67,7 +268,234 @@ pub struct Cli {
     #[clap(long, default_value_t = 10)]
     pub rays_per_pixel: usize,
 
+    /// Grayscale image, the same size as the output, scaling each pixel's `--rays-per-pixel` by its
+    /// brightness times `--importance-max`. Every pixel gets at least one ray.
+    #[clap(long)]
+    pub importance_map: Option<String>,
+
+    /// How many times `--rays-per-pixel` the white pixels of `--importance-map` get.
+    #[clap(long, default_value_t = 4.0)]
+    pub importance_max: Real,
+
+    /// How each pixel's rays are spread over it.
+    #[clap(long, arg_enum, default_value_t = SamplerKind::Random)]
+    pub sampler: SamplerKind,
+
+    /// How much each of a pixel's rays counts toward its color. The wider filters soften
+    /// high-contrast edges.
+    #[clap(long, arg_enum, default_value_t = FilterKind::Box)]
+    pub filter: FilterKind,
+
     /// Max recursion depth per ray.
     #[clap(long, default_value_t = 50)]
     pub recursion_depth: usize,
+
+    /// Number of bounces after which paths are randomly ended, with a probability that rises as
+    /// less light gets through them. Faster, but noisier [default: off].
+    #[clap(long)]
+    pub rr_depth: Option<usize>,
+
+    /// What paths that reach `--recursion-depth` see.
+    #[clap(long, arg_enum, default_value_t = ExhaustedKind::Black)]
+    pub depth_exhausted: ExhaustedKind,
+
+    /// Light seen by paths that reach `--recursion-depth`, as linear `r,g,b` or sRGB `#rrggbb`,
+    /// used by `--depth-exhausted black` [default: black]
+    #[clap(long)]
+    pub depth_exhausted_color: Option<ColorArg>,
+
+    /// Clamp each channel of the light paths bring back after scattering more than once to at most
+    /// this, trading a little accuracy for fewer fireflies [default: no clamping].
+    #[clap(long)]
+    pub clamp_indirect: Option<Real>,
+
+    /// Seed for every random choice, including the built-in scene. The same seed and settings
+    /// always produce the same image, however many threads render it.
+    #[clap(long, default_value_t = 0)]
+    pub seed: u64,
+
+    /// What rays that don't hit anything see.
+    #[clap(long, arg_enum, default_value_t = BackgroundKind::Gradient)]
+    pub background: BackgroundKind,
+
+    /// Background color as linear `r,g,b` or sRGB `#rrggbb`, used by `--background solid`.
+    #[clap(long, required_if_eq("background", "solid"))]
+    pub background_color: Option<ColorArg>,
+
+    /// Equirectangular environment map to use as the background. Overrides `--background`.
+    #[clap(long)]
+    pub environment: Option<String>,
+
+    /// Camera position. Ignored when rendering a `--scene`.
+    #[clap(long, default_value_t = Vec3Arg(Vec3 { x: -13.0, y: 4.0, z: -4.0 }))]
+    pub look_from: Vec3Arg,
+
+    /// The point the camera looks at. Ignored when rendering a `--scene`.
+    #[clap(long, default_value_t = Vec3Arg(Vec3::ZERO))]
+    pub look_at: Vec3Arg,
+
+    /// The width of each pixel divided by its height, for displays with non-square pixels. Widens
+    /// the view by this factor.
+    #[clap(long, default_value_t = 1.0)]
+    pub pixel_aspect: Real,
+
+    /// Vertical field of view, in degrees. Ignored when rendering a `--scene`.
+    #[clap(long, default_value_t = 30.0)]
+    pub fov: Real,
+
+    /// Lens diameter. Ignored when rendering a `--scene`.
+    #[clap(long, default_value_t = 0.1)]
+    pub aperture: Real,
+
+    /// Give the lens a regular polygonal opening with this many blades, instead of a circular one,
+    /// which shapes out-of-focus highlights to match. Ignored when rendering a `--scene`.
+    #[clap(long)]
+    pub aperture_blades: Option<u32>,
+
+    /// Image whose alpha channel gives the shape of the lens opening. Ignored when rendering a
+    /// `--scene`.
+    #[clap(long, conflicts_with = "aperture-blades")]
+    pub aperture_mask: Option<String>,
+
+    /// Distance to the plane in focus [default: the distance from `--look-from` to `--look-at`].
+    /// Ignored when rendering a `--scene`.
+    #[clap(long)]
+    pub focal_distance: Option<Real>,
+
+    /// Focus on the first object in the middle of the view, instead of at `--focal-distance`.
+    /// Ignored when rendering a `--scene`.
+    #[clap(long, conflicts_with = "focal-distance")]
+    pub autofocus: bool,
+
+    /// Camera projection. Ignored when rendering a `--scene`.
+    #[clap(long, arg_enum, default_value_t = ProjectionKind::Perspective)]
+    pub projection: ProjectionKind,
+
+    /// Height of the view in world units, used by `--projection ortho`.
+    #[clap(long, default_value_t = 10.0)]
+    pub ortho_scale: Real,
+
+    /// JSON scene description to render instead of the built-in random scene, or `cornell` for the
+    /// built-in Cornell box, which is always rendered against a black background.
+    #[clap(long)]
+    pub scene: Option<String>,
+
+    /// File to write the image to: a Radiance HDR file with linear colors if the name ends in
+    /// `.hdr`, and a PPM otherwise [default: standard output].
+    #[clap(short, long)]
+    pub output: Option<String>,
+
+    /// Render in passes of this many rays per pixel, rewriting `--output` after each one, so that
+    /// a partial render can be inspected (or kept) early.
+    #[clap(long, requires = "output")]
+    pub progressive: Option<usize>,
+
+    /// Only render the pixels from `x0,y0` (inclusive) to `x1,y1` (exclusive), counted from the top
+    /// left of the image, leaving the rest black.
+    #[clap(long)]
+    pub crop: Option<CropArg>,
+
+    /// Clamp each channel of the gamma-corrected image to `lo,hi` before it's written with 8 bits
+    /// per channel. HDR output isn't clamped [default: 0,1].
+    #[clap(long)]
+    pub clamp: Option<RangeArg>,
+
+    /// Smooth out noise with an edge-preserving filter.
+    #[clap(long)]
+    pub denoise: bool,
+
+    /// How strongly `--denoise` smooths the image.
+    #[clap(long, default_value_t = 1.0)]
+    pub denoise_strength: Real,
+
+    /// File to write the surface normals seen through each pixel to, as a PNG image.
+    #[clap(long)]
+    pub aov_normals: Option<String>,
+
+    /// File to write the albedo seen through each pixel to, as a PNG image.
+    #[clap(long)]
+    pub aov_albedo: Option<String>,
+
+    /// Only find lights by scattering, instead of also sampling them directly. Much noisier; useful
+    /// for comparison.
+    #[clap(long)]
+    pub naive: bool,
+
+    /// Also count the BVH nodes and objects tested while rendering, and report them with the
+    /// other statistics. Slightly slower.
+    #[clap(long)]
+    pub stats: bool,
+
+    /// What to draw pixels whose color came out NaN or infinite as. How many there were is reported
+    /// on stderr.
+    #[clap(long, arg_enum, default_value_t = NonFiniteKind::Magenta)]
+    pub non_finite: NonFiniteKind,
+
+    /// Instead of rendering, trace `--probe-rays` rays through pixel `x,y` (from the top left) and
+    /// describe every bounce on stderr.
+    #[clap(long)]
+    pub probe: Option<PixelArg>,
+
+    /// Rays to trace with `--probe`.
+    #[clap(long, default_value_t = 1)]
+    pub probe_rays: usize,
+
+    /// Render a diagnostic view instead of the scene's materials.
+    #[clap(long, arg_enum)]
+    pub debug: Option<DebugMode>,
+
+    /// Width of the edges drawn by `--debug aabb`, in scene units.
+    #[clap(long, default_value_t = 0.02)]
+    pub aabb_thickness: Real,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_dimensions_are_rejected() {
+        for s in ["1,0", "0,1", "0,0"] {
+            assert!(
+                matches!(Dimensions::from_str(s), Err(ParseDimensionsError::Empty)),
+                "{}",
+                s
+            );
+        }
+        assert!(matches!(
+            Dimensions::from_str("4,3"),
+            Ok(Dimensions {
+                width: 4,
+                height: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn vec3_arg_needs_three_numbers() {
+        let Vec3Arg(v) = Vec3Arg::from_str("1.0,2.0,3.0").unwrap();
+        assert_eq!(
+            v,
+            Vec3 {
+                x: 1.0,
+                y: 2.0,
+                z: 3.0
+            }
+        );
+        assert!(matches!(
+            Vec3Arg::from_str("1,2"),
+            Err(ParseComponentsError::WrongComponentCount {
+                expected: 3,
+                found: 2
+            })
+        ));
+        assert!(matches!(
+            Vec3Arg::from_str("1,2,z"),
+            Err(ParseComponentsError::Component(_))
+        ));
+
+        // The default values are written back in a form that parses.
+        let Vec3Arg(v) = Vec3Arg::from_str(&Vec3Arg(v).to_string()).unwrap();
+        assert_eq!(v.z, 3.0);
+    }
 }
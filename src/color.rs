@@ -1,14 +1,78 @@
-use std::io::Write;
+use crate::real::Real;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
 
-#[derive(Debug, Clone, Copy)]
+/**
+A linear RGB color.
+
+Scene files can write colors as `{"r": ..., "g": ..., "b": ...}`, in linear RGB, or as sRGB hex
+strings like `"#ff8800"` (see [`Color::from_hex`]).
+*/
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(try_from = "ColorRepr")]
 pub struct Color {
-    pub r: f64,
-    pub g: f64,
-    pub b: f64,
+    pub r: Real,
+    pub g: Real,
+    pub b: Real,
+}
+
+/// The ways a color can be written in a scene file.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColorRepr {
+    Rgb { r: Real, g: Real, b: Real },
+    Hex(String),
+}
+
+impl TryFrom<ColorRepr> for Color {
+    type Error = ParseColorError;
+
+    fn try_from(repr: ColorRepr) -> Result<Self, Self::Error> {
+        match repr {
+            ColorRepr::Rgb { r, g, b } => Ok(Color { r, g, b }),
+            ColorRepr::Hex(hex) => Color::from_hex(&hex),
+        }
+    }
+}
+
+/// Why a string isn't a hex color.
+#[derive(Debug)]
+pub enum ParseColorError {
+    /// There weren't 3 or 6 digits.
+    WrongLength(usize),
+    InvalidDigit(char),
+}
+
+impl std::fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseColorError::WrongLength(length) => {
+                write!(f, "expected 3 or 6 hex digits, got {}", length)
+            }
+            ParseColorError::InvalidDigit(digit) => write!(f, "invalid hex digit {:?}", digit),
+        }
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+/// One of a [`Color`]'s channels, for indexing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    R,
+    G,
+    B,
+}
+
+impl Channel {
+    /// Every channel, in order.
+    pub fn all() -> [Channel; 3] {
+        [Channel::R, Channel::G, Channel::B]
+    }
 }
 
 impl Color {
-    pub fn render_ppm<W: Write>(&self, buffer: &mut W) {
+    pub fn render_ppm<W: Write>(&self, buffer: &mut W) -> io::Result<()> {
         writeln!(
             buffer,
             "{} {} {}",
@@ -16,16 +80,165 @@ impl Color {
             (self.g * 255.0).clamp(0.0, 255.0).round() as u8,
             (self.b * 255.0).clamp(0.0, 255.0).round() as u8
         )
-        .expect("writeln failed")
     }
 
+    /**
+    Parse an sRGB hex color, `#rrggbb` or the shorthand `#rgb`, and convert it to linear RGB. The
+    `#` is optional.
+
+    ```
+    use rt_weekend::color::Color;
+
+    let orange = Color::from_hex("#ff8800").unwrap();
+    assert_eq!(orange.to_string(), "#ff8800");
+    assert_eq!(Color::from_hex("f80").unwrap().to_string(), "#ff8800");
+    assert!(Color::from_hex("#ff880").is_err());
+    assert!(Color::from_hex("#gg8800").is_err());
+    ```
+    */
+    pub fn from_hex(hex: &str) -> Result<Self, ParseColorError> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        let values = digits
+            .chars()
+            .map(|digit| {
+                digit
+                    .to_digit(16)
+                    .ok_or(ParseColorError::InvalidDigit(digit))
+            })
+            .collect::<Result<Vec<u32>, _>>()?;
+        let bytes = match values[..] {
+            // Each digit is repeated: `#f80` is `#ff8800`.
+            [r, g, b] => [r * 17, g * 17, b * 17],
+            [r1, r0, g1, g0, b1, b0] => [r1 * 16 + r0, g1 * 16 + g0, b1 * 16 + b0],
+            _ => return Err(ParseColorError::WrongLength(values.len())),
+        };
+        Ok(Color {
+            r: bytes[0] as Real / 255.0,
+            g: bytes[1] as Real / 255.0,
+            b: bytes[2] as Real / 255.0,
+        }
+        .srgb_to_linear())
+    }
+
+    /// Apply `f` to each channel.
     #[must_use]
-    pub fn sqrt(&self) -> Self {
+    pub fn map<F: Fn(Real) -> Real>(&self, f: F) -> Self {
         Color {
-            r: self.r.sqrt(),
-            g: self.g.sqrt(),
-            b: self.b.sqrt(),
+            r: f(self.r),
+            g: f(self.g),
+            b: f(self.b),
+        }
+    }
+
+    #[must_use]
+    pub fn sqrt(&self) -> Self {
+        self.map(Real::sqrt)
+    }
+
+    /// Convert an sRGB-encoded color to linear RGB.
+    #[must_use]
+    pub fn srgb_to_linear(&self) -> Self {
+        fn channel(value: Real) -> Real {
+            if value <= 0.04045 {
+                value / 12.92
+            } else {
+                ((value + 0.055) / 1.055).powf(2.4)
+            }
         }
+
+        self.map(channel)
+    }
+
+    /// Convert a linear RGB color to sRGB encoding. The inverse of [`Color::srgb_to_linear`].
+    #[must_use]
+    pub fn linear_to_srgb(&self) -> Self {
+        fn channel(value: Real) -> Real {
+            if value <= 0.0031308 {
+                value * 12.92
+            } else {
+                1.055 * value.powf(1.0 / 2.4) - 0.055
+            }
+        }
+
+        self.map(channel)
+    }
+
+    /// The average of some colors. Black if there are none.
+    pub fn mean<I: IntoIterator<Item = Color>>(colors: I) -> Self {
+        let (sum, count) = colors
+            .into_iter()
+            .fold((Color::BLACK, 0usize), |(sum, count), color| {
+                (sum + color, count + 1)
+            });
+        if count == 0 {
+            Color::BLACK
+        } else {
+            sum / count as Real
+        }
+    }
+
+    /// Interpolate linearly from `self`, when `t` is `0`, to `other`, when `t` is `1`.
+    #[must_use]
+    pub fn lerp(&self, other: Color, t: Real) -> Self {
+        (1.0 - t) * *self + t * other
+    }
+
+    /// Whether each channel differs from `other`'s by at most `epsilon`.
+    pub fn approx_eq(&self, other: Color, epsilon: Real) -> bool {
+        Channel::all()
+            .into_iter()
+            .all(|channel| (self[channel] - other[channel]).abs() <= epsilon)
+    }
+
+    /// How bright the color looks, weighting each channel by the eye's sensitivity to it (Rec. 709).
+    pub fn luminance(&self) -> Real {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
+    /**
+    Whether every channel is a number, rather than NaN or infinite.
+
+    ```
+    use rt_weekend::{color::Color, real::Real};
+
+    assert!(Color::BLACK.is_finite());
+    assert!(!Color { r: Real::NAN, g: 0.0, b: 0.0 }.is_finite());
+    assert!(!Color { r: 0.0, g: 0.0, b: Real::INFINITY }.is_finite());
+    ```
+    */
+    pub fn is_finite(&self) -> bool {
+        self.r.is_finite() && self.g.is_finite() && self.b.is_finite()
+    }
+
+    /// The largest of the three channels.
+    pub fn max_channel(&self) -> Real {
+        self.r.max(self.g).max(self.b)
+    }
+
+    /// Each channel limited to at most `max`. NaN channels stay NaN.
+    #[must_use]
+    pub fn clamped(&self, max: Real) -> Self {
+        self.map(|value| if value > max { max } else { value })
+    }
+
+    /**
+    Each channel limited to `[lo, hi]`. Panics if `lo > hi`.
+
+    ```
+    use rt_weekend::color::Color;
+
+    let color = Color {
+        r: 0.0,
+        g: 0.5,
+        b: 1.0,
+    }
+    .clamp_range(0.1, 0.9);
+    assert_eq!((color.r, color.g, color.b), (0.1, 0.5, 0.9));
+    ```
+    */
+    #[must_use]
+    pub fn clamp_range(&self, lo: Real, hi: Real) -> Self {
+        self.map(|value| value.clamp(lo, hi))
     }
 
     pub const BLACK: Self = Color {
@@ -33,6 +246,34 @@ impl Color {
         g: 0.0,
         b: 0.0,
     };
+
+    pub const MAGENTA: Self = Color {
+        r: 1.0,
+        g: 0.0,
+        b: 1.0,
+    };
+}
+
+impl std::ops::Index<Channel> for Color {
+    type Output = Real;
+
+    fn index(&self, index: Channel) -> &Self::Output {
+        match index {
+            Channel::R => &self.r,
+            Channel::G => &self.g,
+            Channel::B => &self.b,
+        }
+    }
+}
+
+impl std::ops::IndexMut<Channel> for Color {
+    fn index_mut(&mut self, index: Channel) -> &mut Self::Output {
+        match index {
+            Channel::R => &mut self.r,
+            Channel::G => &mut self.g,
+            Channel::B => &mut self.b,
+        }
+    }
 }
 
 /// Pointwise addition.
@@ -57,8 +298,14 @@ impl std::ops::AddAssign<Color> for Color {
     }
 }
 
+impl std::iter::Sum for Color {
+    fn sum<I: Iterator<Item = Color>>(iter: I) -> Self {
+        iter.fold(Color::BLACK, |acc, color| acc + color)
+    }
+}
+
 /// Scalar multiplication.
-impl std::ops::Mul<Color> for f64 {
+impl std::ops::Mul<Color> for Real {
     type Output = Color;
 
     fn mul(self, rhs: Color) -> Self::Output {
@@ -70,6 +317,21 @@ impl std::ops::Mul<Color> for f64 {
     }
 }
 
+/// The color as sRGB hex, `#rrggbb`, with each channel clamped to `[0, 1]`.
+impl std::fmt::Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let srgb = self.linear_to_srgb();
+        let byte = |value: Real| (value * 255.0).clamp(0.0, 255.0).round() as u8;
+        write!(
+            f,
+            "#{:02x}{:02x}{:02x}",
+            byte(srgb.r),
+            byte(srgb.g),
+            byte(srgb.b)
+        )
+    }
+}
+
 /// Pointwise multiplication.
 impl std::ops::Mul<Color> for Color {
     type Output = Color;
@@ -84,10 +346,10 @@ impl std::ops::Mul<Color> for Color {
 }
 
 /// Scalar division.
-impl std::ops::Div<f64> for Color {
+impl std::ops::Div<Real> for Color {
     type Output = Color;
 
-    fn div(self, rhs: f64) -> Self::Output {
+    fn div(self, rhs: Real) -> Self::Output {
         1.0 / rhs * self
     }
 }
@@ -101,3 +363,90 @@ impl rand::distributions::Distribution<Color> for rand::distributions::Standard
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Color = Color {
+        r: 1.0,
+        g: 0.0,
+        b: 0.0,
+    };
+    const BLUE: Color = Color {
+        r: 0.0,
+        g: 0.0,
+        b: 1.0,
+    };
+
+    #[test]
+    fn lerp_goes_from_self_to_other() {
+        assert!(RED.lerp(BLUE, 0.0).approx_eq(RED, 1e-6));
+        assert!(RED.lerp(BLUE, 1.0).approx_eq(BLUE, 1e-6));
+        let halfway = Color {
+            r: 0.5,
+            g: 0.0,
+            b: 0.5,
+        };
+        assert!(RED.lerp(BLUE, 0.5).approx_eq(halfway, 1e-6));
+    }
+
+    #[test]
+    fn mean_of_colors() {
+        let colors = [
+            RED,
+            BLUE,
+            Color {
+                r: 0.5,
+                g: 0.9,
+                b: 0.5,
+            },
+        ];
+        let expected = Color {
+            r: 0.5,
+            g: 0.3,
+            b: 0.5,
+        };
+        assert!(Color::mean(colors).approx_eq(expected, 1e-6));
+        assert!(Color::mean([]).approx_eq(Color::BLACK, 0.0));
+    }
+
+    #[test]
+    fn sum_of_nothing_is_black() {
+        let sum: Color = std::iter::empty().sum();
+        assert!(sum.approx_eq(Color::BLACK, 0.0));
+        let sum: Color = [RED, BLUE].into_iter().sum();
+        assert!(sum.approx_eq(RED + BLUE, 0.0));
+    }
+
+    #[test]
+    fn map_applies_to_each_channel() {
+        let color = Color {
+            r: 1.0,
+            g: 2.0,
+            b: 3.0,
+        };
+        let mapped = color.map(|value| 10.0 * value + 1.0);
+        assert_eq!((mapped.r, mapped.g, mapped.b), (11.0, 21.0, 31.0));
+    }
+
+    #[test]
+    fn channels_index_their_fields() {
+        let mut color = Color {
+            r: 1.0,
+            g: 2.0,
+            b: 3.0,
+        };
+        assert_eq!(color[Channel::R], 1.0);
+        assert_eq!(color[Channel::G], 2.0);
+        assert_eq!(color[Channel::B], 3.0);
+
+        for channel in Channel::all() {
+            color[channel] *= 2.0;
+        }
+        assert_eq!((color.r, color.g, color.b), (2.0, 4.0, 6.0));
+
+        color[Channel::G] = 0.5;
+        assert_eq!((color.r, color.g, color.b), (2.0, 0.5, 6.0));
+    }
+}
@@ -0,0 +1,245 @@
+use crate::{
+    bounds::Bounds3,
+    hit::{Face, Hit, Tangents},
+    interval::Interval,
+    material::Material,
+    object::IsObject,
+    ray::Ray,
+    real::{consts, Real},
+    texture,
+    vec3::Vec3,
+};
+
+/**
+A finite cylinder whose axis is parallel to the Y axis. Use a
+[`Transform`](crate::instance::Transform) to orient it differently.
+
+The cylinder extends `height / 2` above and below `center`. When `capped` is false it's an open
+tube.
+*/
+pub struct Cylinder {
+    pub center: Vec3,
+    pub radius: Real,
+    pub height: Real,
+    pub capped: bool,
+    pub material: Material,
+}
+
+impl IsObject for Cylinder {
+    fn hit(&self, ray: &Ray, t_range: Interval) -> Option<Hit> {
+        let origin = ray.origin - self.center;
+        let half_height = self.height / 2.0;
+
+        // The closest intersection so far, and its outward normal.
+        let mut closest: Option<(Real, Vec3)> = None;
+        let mut consider = |t: Real, outward_normal: Vec3| {
+            if t_range.contains(t) && closest.is_none_or(|(closest_t, _)| t < closest_t) {
+                closest = Some((t, outward_normal));
+            }
+        };
+
+        // The curved side: an infinite cylinder, restricted to the band between the caps.
+        let a = ray.direction.x.powi(2) + ray.direction.z.powi(2);
+        if a > 0.0 {
+            let half_b = origin.x * ray.direction.x + origin.z * ray.direction.z;
+            let c = origin.x.powi(2) + origin.z.powi(2) - self.radius.powi(2);
+            let discriminant = half_b * half_b - a * c;
+            if discriminant >= 0.0 {
+                for t in [
+                    (-half_b - discriminant.sqrt()) / a,
+                    (-half_b + discriminant.sqrt()) / a,
+                ] {
+                    let point = origin + t * ray.direction;
+                    if point.y.abs() <= half_height {
+                        consider(
+                            t,
+                            Vec3 {
+                                x: point.x / self.radius,
+                                y: 0.0,
+                                z: point.z / self.radius,
+                            },
+                        );
+                    }
+                }
+            }
+        }
+
+        if self.capped && ray.direction.y != 0.0 {
+            for y in [-half_height, half_height] {
+                let t = (y - origin.y) / ray.direction.y;
+                let point = origin + t * ray.direction;
+                if point.x.powi(2) + point.z.powi(2) <= self.radius.powi(2) {
+                    consider(
+                        t,
+                        Vec3 {
+                            x: 0.0,
+                            y: y.signum(),
+                            z: 0.0,
+                        },
+                    );
+                }
+            }
+        }
+
+        let (t, outward_normal) = closest?;
+        let point = ray.at(t);
+        let local = point - self.center;
+
+        let (normal, face) = if ray.direction.dot(outward_normal) < 0.0 {
+            (outward_normal, Face::Front)
+        } else {
+            (-outward_normal, Face::Back)
+        };
+
+        let (texture_coord, tangents) = if outward_normal.y == 0.0 {
+            // The angle around the axis, using the same convention as `Sphere`, and the height.
+            let phi = (-local.z).atan2(local.x) + consts::PI;
+            let texture_coord = texture::Coord {
+                u: (phi / (2.0 * consts::PI)).min(1.0),
+                v: ((local.y + half_height) / self.height).clamp(0.0, 1.0),
+            };
+            let tangents = Tangents {
+                tangent: Vec3 {
+                    x: outward_normal.z,
+                    y: 0.0,
+                    z: -outward_normal.x,
+                },
+                bitangent: Vec3 {
+                    x: 0.0,
+                    y: 1.0,
+                    z: 0.0,
+                },
+            };
+            (texture_coord, tangents)
+        } else {
+            // Planar projection of the cap.
+            let texture_coord = texture::Coord {
+                u: (0.5 * (local.x / self.radius + 1.0)).clamp(0.0, 1.0),
+                v: (0.5 * (local.z / self.radius + 1.0)).clamp(0.0, 1.0),
+            };
+            let tangents = Tangents {
+                tangent: Vec3 {
+                    x: 1.0,
+                    y: 0.0,
+                    z: 0.0,
+                },
+                bitangent: Vec3 {
+                    x: 0.0,
+                    y: 0.0,
+                    z: 1.0,
+                },
+            };
+            (texture_coord, tangents)
+        };
+
+        Some(Hit {
+            point,
+            normal,
+            t,
+            face,
+            material: self.material.clone(),
+            texture_coord,
+            tangents: Some(tangents),
+            object_id: None,
+        })
+    }
+
+    fn bounds(&self) -> Bounds3 {
+        let corner = Vec3 {
+            x: self.radius,
+            y: self.height / 2.0,
+            z: self.radius,
+        };
+        Bounds3::new(self.center - corner, self.center + corner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{material::Normals, vec3::assert_vec3_eq};
+
+    fn cylinder(capped: bool) -> Cylinder {
+        Cylinder {
+            center: Vec3 {
+                x: 0.0,
+                y: 1.0,
+                z: 0.0,
+            },
+            radius: 0.5,
+            height: 2.0,
+            capped,
+            material: Material::new(Normals()),
+        }
+    }
+
+    #[test]
+    fn side_and_cap_hits() {
+        let t_range = Interval::new(0.0, Real::INFINITY);
+        let y = Vec3 {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+        };
+
+        // Horizontally into the side.
+        let sideways = Ray::new(
+            Vec3 {
+                x: 3.0,
+                y: 1.5,
+                z: 0.0,
+            },
+            Vec3 {
+                x: -1.0,
+                y: 0.0,
+                z: 0.0,
+            },
+        );
+        for capped in [false, true] {
+            let hit = cylinder(capped).hit(&sideways, t_range).unwrap();
+            assert!((hit.t - 2.5).abs() < 1e-5, "{}", hit.t);
+            assert_vec3_eq!(
+                hit.normal,
+                Vec3 {
+                    x: 1.0,
+                    y: 0.0,
+                    z: 0.0
+                },
+                1e-5
+            );
+        }
+
+        // Straight down onto the top.
+        let down = Ray::new(
+            Vec3 {
+                x: 0.1,
+                y: 5.0,
+                z: -0.2,
+            },
+            -y,
+        );
+        let hit = cylinder(true).hit(&down, t_range).unwrap();
+        assert!((hit.t - 3.0).abs() < 1e-5, "{}", hit.t);
+        assert_vec3_eq!(hit.normal, y, 1e-5);
+        assert!(matches!(hit.face, Face::Front));
+
+        // Without caps, it goes down the tube and only sees the inside of the side.
+        assert!(cylinder(false).hit(&down, t_range).is_none());
+        let slanted = Ray::new(
+            Vec3 {
+                x: 0.0,
+                y: 5.0,
+                z: 0.0,
+            },
+            Vec3 {
+                x: 0.1,
+                y: -1.0,
+                z: 0.0,
+            },
+        );
+        let hit = cylinder(false).hit(&slanted, t_range).unwrap();
+        assert!(hit.point.y < 2.0);
+        assert!((hit.point.x - 0.5).abs() < 1e-5);
+        assert!(matches!(hit.face, Face::Back));
+    }
+}
@@ -0,0 +1,173 @@
+/*!
+Noise reduction for rendered images.
+
+The filter works on linear colors, so it should run before gamma correction.
+*/
+
+use crate::{color::Color, image::Image, real::Real};
+
+/**
+An auxiliary image that tells the filter where edges are, such as the surface normals or albedo
+seen through each pixel.
+
+Pixels whose guide values differ by much more than `sigma` aren't blended, even when their colors
+are similar.
+*/
+pub struct Guide<'a> {
+    /// Row-major, the same size as the image being filtered.
+    pub buffer: &'a [Color],
+    pub sigma: Real,
+}
+
+fn distance_squared(a: Color, b: Color) -> Real {
+    (a.r - b.r).powi(2) + (a.g - b.g).powi(2) + (a.b - b.b).powi(2)
+}
+
+/**
+Smooth `image` with an edge-preserving bilateral filter.
+
+Each pixel becomes a weighted average of its neighbours, where a neighbour's weight falls off with
+its distance from the pixel, with the difference between their colors, and with the difference
+between their values in each of the `guides`. Flat regions are smoothed, but pixels on either side
+of an edge aren't mixed.
+
+`strength` scales both how far the filter reaches and how different colors can be before they're
+treated as an edge. `0` leaves the image unchanged.
+*/
+pub fn bilateral(image: &Image, strength: Real, guides: &[Guide]) -> Image {
+    let (width, height) = (image.width, image.height);
+    for guide in guides {
+        assert!(guide.buffer.len() == width * height);
+    }
+    if strength <= 0.0 {
+        return Image {
+            width,
+            height,
+            data: image.data.clone(),
+        };
+    }
+
+    let sigma_spatial = 2.0 * strength;
+    let sigma_range = 0.2 * strength;
+    let radius = (2.0 * sigma_spatial).ceil() as isize;
+
+    let data = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let center = y * width + x;
+            let center_color = image.data[center];
+
+            let mut sum = Color::BLACK;
+            let mut total_weight = 0.0;
+            for dy in -radius..=radius {
+                let ny = y as isize + dy;
+                if ny < 0 || ny >= height as isize {
+                    continue;
+                }
+                for dx in -radius..=radius {
+                    let nx = x as isize + dx;
+                    if nx < 0 || nx >= width as isize {
+                        continue;
+                    }
+                    let neighbour = ny as usize * width + nx as usize;
+                    let color = image.data[neighbour];
+
+                    let mut exponent = ((dx * dx + dy * dy) as Real)
+                        / (2.0 * sigma_spatial * sigma_spatial)
+                        + distance_squared(color, center_color) / (2.0 * sigma_range * sigma_range);
+                    for guide in guides {
+                        exponent += distance_squared(guide.buffer[neighbour], guide.buffer[center])
+                            / (2.0 * guide.sigma * guide.sigma);
+                    }
+                    let weight = (-exponent).exp();
+
+                    sum += weight * color;
+                    total_weight += weight;
+                }
+            }
+
+            // The center pixel always has weight 1, so `total_weight` is never zero.
+            sum / total_weight
+        })
+        .collect();
+
+    Image {
+        width,
+        height,
+        data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn gray(value: Real) -> Color {
+        Color {
+            r: value,
+            g: value,
+            b: value,
+        }
+    }
+
+    #[test]
+    fn noisy_gradient_is_smoothed_without_changing_its_mean() {
+        let (width, height) = (32, 32);
+        let clean = |x: usize| 0.2 + 0.5 * x as Real / (width - 1) as Real;
+        let mut rng = StdRng::seed_from_u64(0);
+        let noisy = Image {
+            width,
+            height,
+            data: (0..width * height)
+                .map(|i| gray(clean(i % width) + rng.gen_range(-0.05..0.05)))
+                .collect(),
+        };
+        let denoised = bilateral(&noisy, 1.0, &[]);
+
+        let mean = |image: &Image| {
+            image.data.iter().map(|color| color.g).sum::<Real>() / image.data.len() as Real
+        };
+        let noise_variance = |image: &Image| {
+            image
+                .data
+                .iter()
+                .enumerate()
+                .map(|(i, color)| (color.g - clean(i % width)).powi(2))
+                .sum::<Real>()
+                / image.data.len() as Real
+        };
+        assert!((mean(&denoised) - mean(&noisy)).abs() < 0.005);
+        assert!(
+            noise_variance(&denoised) < 0.25 * noise_variance(&noisy),
+            "{} vs {}",
+            noise_variance(&denoised),
+            noise_variance(&noisy)
+        );
+    }
+
+    #[test]
+    fn guide_keeps_edges_apart() {
+        // Two halves of similar color, which the guide says are different surfaces.
+        let (width, height) = (8, 4);
+        let side = |i: usize| if i % width < width / 2 { 0.0 } else { 1.0 };
+        let image = Image {
+            width,
+            height,
+            data: (0..width * height)
+                .map(|i| gray(0.5 + 0.05 * side(i)))
+                .collect(),
+        };
+        let normals: Vec<Color> = (0..width * height).map(|i| gray(side(i))).collect();
+        let guide = Guide {
+            buffer: &normals,
+            sigma: 0.1,
+        };
+
+        let unguided = bilateral(&image, 1.0, &[]);
+        let guided = bilateral(&image, 1.0, &[guide]);
+        let step = |image: &Image| image.data[width / 2].g - image.data[width / 2 - 1].g;
+        assert!((step(&guided) - 0.05).abs() < 1e-6, "{}", step(&guided));
+        assert!(step(&unguided) < 0.04, "{}", step(&unguided));
+    }
+}
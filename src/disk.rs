@@ -0,0 +1,177 @@
+use crate::{
+    bounds::Bounds3,
+    hit::{Face, Hit, Tangents},
+    interval::Interval,
+    material::Material,
+    object::IsObject,
+    ray::Ray,
+    real::{consts, Real},
+    texture,
+    vec3::Vec3,
+};
+use rand::Rng;
+
+/**
+A flat disk facing along `normal`.
+
+When `inner_radius` is greater than zero, the disk has a hole in the middle (an annulus).
+*/
+pub struct Disk {
+    pub center: Vec3,
+    pub normal: Vec3,
+    pub radius: Real,
+    pub inner_radius: Real,
+    pub material: Material,
+}
+
+impl Disk {
+    /// A point chosen uniformly at random from the disk's surface.
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec3 {
+        let (tangent, bitangent) = self.normal.unit().orthonormal_basis();
+        let inner_squared = self.inner_radius.powi(2);
+        let r = (inner_squared + rng.gen::<Real>() * (self.radius.powi(2) - inner_squared)).sqrt();
+        let angle = 2.0 * consts::PI * rng.gen::<Real>();
+        self.center + r * angle.cos() * tangent + r * angle.sin() * bitangent
+    }
+}
+
+impl IsObject for Disk {
+    fn hit(&self, ray: &Ray, t_range: Interval) -> Option<Hit> {
+        let outward_normal = self.normal.unit();
+
+        let denominator = ray.direction.dot(outward_normal);
+        if denominator.abs() < 1e-12 {
+            // The ray is parallel to the disk.
+            return None;
+        }
+
+        let t = (self.center - ray.origin).dot(outward_normal) / denominator;
+        if !t_range.contains(t) {
+            return None;
+        }
+
+        let point = ray.at(t);
+        let offset = point - self.center;
+        let distance_squared = offset.norm_squared();
+        if distance_squared > self.radius.powi(2) || distance_squared < self.inner_radius.powi(2) {
+            return None;
+        }
+
+        let (normal, face) = if denominator < 0.0 {
+            (outward_normal, Face::Front)
+        } else {
+            (-outward_normal, Face::Back)
+        };
+
+        // Polar coordinates: the angle around the center, and the distance out from the inner edge.
+        let (tangent, bitangent) = outward_normal.orthonormal_basis();
+        let angle = offset.dot(bitangent).atan2(offset.dot(tangent)) + consts::PI;
+        let texture_coord = texture::Coord {
+            u: (angle / (2.0 * consts::PI)).min(1.0),
+            v: ((distance_squared.sqrt() - self.inner_radius) / (self.radius - self.inner_radius))
+                .clamp(0.0, 1.0),
+        };
+
+        // Around the center, and outward from it. Neither is defined at the center.
+        let tangents = offset.try_unit().map(|outward| Tangents {
+            tangent: outward_normal.cross(outward),
+            bitangent: outward,
+        });
+
+        Some(Hit {
+            point,
+            normal,
+            t,
+            face,
+            material: self.material.clone(),
+            texture_coord,
+            tangents,
+            object_id: None,
+        })
+    }
+
+    fn bounds(&self) -> Bounds3 {
+        // How far the rim extends along each axis. Padded because the disk has no thickness.
+        let normal = self.normal.unit();
+        let extent = Vec3 {
+            x: self.radius * (1.0 - normal.x.powi(2)).max(0.0).sqrt(),
+            y: self.radius * (1.0 - normal.y.powi(2)).max(0.0).sqrt(),
+            z: self.radius * (1.0 - normal.z.powi(2)).max(0.0).sqrt(),
+        };
+        Bounds3::new(self.center - extent, self.center + extent).padded(0.0001)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Normals;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn annulus() -> Disk {
+        Disk {
+            center: Vec3 {
+                x: 1.0,
+                y: 2.0,
+                z: 0.0,
+            },
+            normal: Vec3 {
+                x: 0.0,
+                y: 0.0,
+                z: 2.0,
+            },
+            radius: 2.0,
+            inner_radius: 1.0,
+            material: Material::new(Normals()),
+        }
+    }
+
+    #[test]
+    fn hits_between_the_hole_and_the_rim() {
+        let disk = annulus();
+        let t_range = Interval::new(0.0, Real::INFINITY);
+        // Straight down onto the point `distance` along +X from the center.
+        let hit_at = |distance: Real| {
+            let ray = Ray::new(
+                disk.center
+                    + Vec3 {
+                        x: distance,
+                        y: 0.0,
+                        z: 3.0,
+                    },
+                Vec3 {
+                    x: 0.0,
+                    y: 0.0,
+                    z: -1.0,
+                },
+            );
+            disk.hit(&ray, t_range)
+        };
+
+        for distance in [1.01, 1.5, 1.99] {
+            let hit = hit_at(distance).unwrap();
+            assert!((hit.t - 3.0).abs() < 1e-5, "{}", hit.t);
+            assert!(matches!(hit.face, Face::Front));
+            assert!((hit.texture_coord.v - (distance - 1.0)).abs() < 1e-4);
+        }
+        for distance in [0.0, 0.5, 0.99, 2.01, 3.0] {
+            assert!(hit_at(distance).is_none(), "hit at {}", distance);
+        }
+    }
+
+    #[test]
+    fn samples_are_on_the_annulus() {
+        let disk = annulus();
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..1000 {
+            let offset = disk.sample(&mut rng) - disk.center;
+            assert!(offset.z.abs() < 1e-6);
+            let distance = offset.norm();
+            assert!(
+                (1.0 - 1e-5..=2.0 + 1e-5).contains(&distance),
+                "{}",
+                distance
+            );
+        }
+    }
+}
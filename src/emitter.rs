@@ -0,0 +1,51 @@
+/*!
+Light sources that can be sampled directly.
+
+Scattering rays at random rarely finds small lights, so the renderer also aims rays at them. An
+[`Emitter`] provides the geometry needed to do that: directions toward a light, and how likely each
+direction is.
+*/
+
+use crate::{real::Real, vec3::Vec3};
+use rand::RngCore;
+use std::sync::Arc;
+
+pub trait IsEmitter: Send + Sync {
+    /// A random direction from `origin` toward the emitter. Not necessarily a unit vector.
+    fn sample_direction(&self, rng: &mut dyn RngCore, origin: Vec3) -> Vec3;
+
+    /// The probability density, with respect to solid angle, that
+    /// [`sample_direction`](IsEmitter::sample_direction) returns `direction` from `origin`.
+    fn direction_pdf(&self, origin: Vec3, direction: Vec3) -> Real;
+
+    /**
+    Roughly how much light the emitter gives off, which decides how often it's sampled compared to
+    the others. Only the ratios between emitters matter; the default of 1 samples them equally.
+    */
+    fn power(&self) -> Real {
+        1.0
+    }
+}
+
+#[derive(Clone)]
+pub struct Emitter(Arc<dyn IsEmitter>);
+
+impl Emitter {
+    pub fn new<T: IsEmitter + 'static>(value: T) -> Self {
+        Emitter(Arc::new(value))
+    }
+}
+
+impl IsEmitter for Emitter {
+    fn sample_direction(&self, rng: &mut dyn RngCore, origin: Vec3) -> Vec3 {
+        self.0.sample_direction(rng, origin)
+    }
+
+    fn direction_pdf(&self, origin: Vec3, direction: Vec3) -> Real {
+        self.0.direction_pdf(origin, direction)
+    }
+
+    fn power(&self) -> Real {
+        self.0.power()
+    }
+}
@@ -0,0 +1,104 @@
+//! How much each of a pixel's rays counts toward its color.
+
+use crate::real::Real;
+
+/**
+A reconstruction filter: a weight for each point around a pixel's center.
+
+Rays are spread over the filter's support, which for the wider filters reaches into neighbouring
+pixels, and each pixel's color is the weighted average of what its rays see. Weighting rays near
+the center more than those near the edges softens high-contrast edges.
+*/
+#[derive(Debug, Clone, Copy, Default)]
+pub enum Filter {
+    /// Every ray in the pixel counts equally.
+    #[default]
+    Box,
+    /// Weights fall linearly from the center to zero one pixel away.
+    Tent,
+    /// A Gaussian with a standard deviation of half a pixel, cut off 1.5 pixels away.
+    Gaussian,
+}
+
+impl Filter {
+    /// How far the filter reaches from the pixel's center along each axis, in pixels.
+    pub fn radius(self) -> Real {
+        match self {
+            Filter::Box => 0.5,
+            Filter::Tent => 1.0,
+            Filter::Gaussian => 1.5,
+        }
+    }
+
+    /**
+    Where a ray goes along one axis, relative to the pixel's lower corner, given a sample `value`
+    in `[0, 1)`.
+
+    Box filter rays stay inside the pixel at `value` itself; the others spread `value` over the
+    filter's support.
+    */
+    pub fn position(self, value: Real) -> Real {
+        match self {
+            Filter::Box => value,
+            Filter::Tent | Filter::Gaussian => 0.5 + (2.0 * value - 1.0) * self.radius(),
+        }
+    }
+
+    /// The weight of a ray at `(dx, dy)` from the pixel's center, in pixels.
+    pub fn weight(self, dx: Real, dy: Real) -> Real {
+        self.weight_1d(dx) * self.weight_1d(dy)
+    }
+
+    fn weight_1d(self, d: Real) -> Real {
+        let radius = self.radius();
+        match self {
+            Filter::Box => 1.0,
+            Filter::Tent => (1.0 - d.abs() / radius).max(0.0),
+            Filter::Gaussian => {
+                // Shifted down so that the weight reaches zero at the edge of the support.
+                let sigma: Real = 0.5;
+                let gaussian = |d: Real| (-d * d / (2.0 * sigma * sigma)).exp();
+                (gaussian(d) - gaussian(radius)).max(0.0)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn box_filter_is_a_plain_average() {
+        for value in [0.0, 0.25, 0.5, 0.999] {
+            assert_eq!(Filter::Box.position(value), value);
+        }
+        for (dx, dy) in [(0.0, 0.0), (0.5, -0.5), (-0.3, 0.1)] {
+            assert_eq!(Filter::Box.weight(dx, dy), 1.0);
+        }
+    }
+
+    #[test]
+    fn tent_filter_weights_the_center_most() {
+        let tent = Filter::Tent;
+        let center = tent.weight(0.0, 0.0);
+        let edge = tent.weight(0.5, 0.0);
+        let corner = tent.weight(0.5, -0.5);
+        assert_eq!(center, 1.0);
+        assert!(
+            corner < edge && edge < center,
+            "{} {} {}",
+            corner,
+            edge,
+            center
+        );
+        assert!((corner - 0.25).abs() < 1e-6, "{}", corner);
+        assert_eq!(tent.weight(1.0, 0.0), 0.0);
+        assert_eq!(tent.weight(0.2, 1.5), 0.0);
+
+        // Rays are spread over the whole support, one pixel either side of the center.
+        assert_eq!(tent.position(0.0), -0.5);
+        assert_eq!(tent.position(0.5), 0.5);
+        assert_eq!(tent.position(1.0), 1.5);
+    }
+}
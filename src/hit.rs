@@ -1,15 +1,48 @@
-use crate::{material::Material, texture, vec3::Vec3};
+use crate::{material::Material, real::Real, texture, vec3::Vec3};
 
+#[derive(Clone, Copy)]
 pub enum Face {
     Front,
     Back,
 }
 
+/**
+The directions along the surface in which the texture coordinates increase. Used to orient
+tangent-space textures, such as normal maps.
+
+Neither is necessarily a unit vector or perpendicular to the normal. They're the same whichever
+side of the surface was hit.
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct Tangents {
+    /// The direction of increasing `u`.
+    pub tangent: Vec3,
+    /// The direction of increasing `v`.
+    pub bitangent: Vec3,
+}
+
+impl Tangents {
+    /// Transform both directions with `f`.
+    #[must_use]
+    pub fn map<F: Fn(Vec3) -> Vec3>(&self, f: F) -> Self {
+        Tangents {
+            tangent: f(self.tangent),
+            bitangent: f(self.bitangent),
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct Hit {
     pub point: Vec3,
     pub normal: Vec3,
-    pub t: f64,
+    pub t: Real,
     pub face: Face,
     pub material: Material,
     pub texture_coord: texture::Coord,
+    /// Only present for surfaces that know how their texture coordinates are laid out.
+    pub tangents: Option<Tangents>,
+    /// Which object was hit, for objects given an id with
+    /// [`Object::with_id`](crate::object::Object::with_id).
+    pub object_id: Option<usize>,
 }
@@ -1,5 +1,28 @@
-use crate::color::Color;
-use std::io::{self, Write};
+use crate::{color::Color, real::Real};
+use rayon::prelude::*;
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+};
+
+#[derive(Debug)]
+pub enum SaveError {
+    /// Creating, renaming or removing a file failed.
+    Io(io::Error),
+    /// Writing the image data failed.
+    Render(io::Error),
+}
+
+impl std::fmt::Display for SaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveError::Io(err) => err.fmt(f),
+            SaveError::Render(err) => write!(f, "failed to write image data: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
 
 pub struct Image {
     pub width: usize,
@@ -8,16 +31,257 @@ pub struct Image {
 }
 
 impl Image {
+    /**
+    Average accumulated samples.
+
+    `sums` holds the weighted total of each pixel's samples and `weights` the total of their
+    weights, row-major from the top of the image. Pixels without any weight are black.
+    */
+    pub fn from_accumulator(sums: &[Color], weights: &[Real], width: usize, height: usize) -> Self {
+        assert!(sums.len() == width * height && weights.len() == width * height);
+        Image {
+            width,
+            height,
+            data: sums
+                .iter()
+                .zip(weights)
+                .map(|(sum, weight)| {
+                    if *weight == 0.0 {
+                        Color::BLACK
+                    } else {
+                        *sum / *weight
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /**
+    Replace every pixel with a NaN or infinite channel with `replacement`, returning how many
+    there were.
+
+    Such pixels come from numerical bugs, and would otherwise be written as arbitrary bytes.
+    */
+    pub fn replace_non_finite(&mut self, replacement: Color) -> usize {
+        let mut count = 0;
+        for pixel in self.data.iter_mut().filter(|pixel| !pixel.is_finite()) {
+            *pixel = replacement;
+            count += 1;
+        }
+        count
+    }
+
+    /// Convert linear colors for display, using a gamma of 2.
+    #[must_use]
+    pub fn gamma_corrected(&self) -> Self {
+        Image {
+            width: self.width,
+            height: self.height,
+            data: self.data.iter().map(Color::sqrt).collect(),
+        }
+    }
+
+    /**
+    Write the image as a plain (P3) PPM file.
+
+    Formatting the numbers is the slow part for large images, so the rows are formatted in
+    parallel, and the whole file is written at once. The output is the same as writing each pixel
+    with [`Color::render_ppm`].
+    */
     pub fn render<W: Write>(&self, buffer: &mut W) -> io::Result<()> {
-        writeln!(buffer, "P3")?;
-        writeln!(buffer, "{} {}", self.width, self.height)?;
-        writeln!(buffer, "255")?;
-        (0..self.height).try_for_each(|y| {
-            let offset = self.width * y;
-            (0..self.width).try_for_each(|x| {
-                self.data[offset + x].render_ppm(buffer);
-                Ok(())
+        let rows: Vec<Vec<u8>> = self
+            .data
+            .par_chunks(self.width.max(1))
+            .map(|row| {
+                // At most 12 bytes per pixel: "255 255 255\n".
+                let mut bytes = Vec::with_capacity(12 * row.len());
+                for color in row {
+                    for (value, separator) in [(color.r, b' '), (color.g, b' '), (color.b, b'\n')] {
+                        push_decimal(&mut bytes, to_byte(value));
+                        bytes.push(separator);
+                    }
+                }
+                bytes
             })
-        })
+            .collect();
+
+        let header = format!("P3\n{} {}\n255\n", self.width, self.height);
+        let mut bytes = Vec::with_capacity(header.len() + rows.iter().map(Vec::len).sum::<usize>());
+        bytes.extend_from_slice(header.as_bytes());
+        for row in rows {
+            bytes.extend(row);
+        }
+        buffer.write_all(&bytes)
+    }
+
+    /**
+    Write the image to `path` as a PPM file.
+
+    The image is written to `<path>.tmp`, which is renamed to `path` once it's complete. If
+    writing fails, `path` is left untouched, so it's never a partially written image.
+    */
+    pub fn save(&self, path: &str) -> Result<(), SaveError> {
+        let temporary_path = format!("{}.tmp", path);
+
+        let file = File::create(&temporary_path).map_err(SaveError::Io)?;
+        let mut writer = BufWriter::new(file);
+        if let Err(err) = self.render(&mut writer).and_then(|()| writer.flush()) {
+            let _ = fs::remove_file(&temporary_path);
+            return Err(SaveError::Render(err));
+        }
+        drop(writer);
+
+        fs::rename(&temporary_path, path).map_err(SaveError::Io)
+    }
+
+    /**
+    Write the image to `path` as a Radiance HDR file, keeping the linear colors as they are, without
+    clamping or gamma correction, so that they can be tone mapped elsewhere.
+
+    Like [`Image::save`], the file is written to `<path>.tmp` first and then renamed.
+    */
+    pub fn save_hdr(&self, path: &str) -> ::image::ImageResult<()> {
+        let temporary_path = format!("{}.tmp", path);
+
+        // The casts are only unnecessary when `Real` is `f32`.
+        #[allow(clippy::unnecessary_cast)]
+        let pixels: Vec<::image::Rgb<f32>> = self
+            .data
+            .iter()
+            .map(|color| ::image::Rgb([color.r as f32, color.g as f32, color.b as f32]))
+            .collect();
+        let result = File::create(&temporary_path)
+            .map_err(::image::ImageError::from)
+            .and_then(|file| {
+                let mut writer = BufWriter::new(file);
+                ::image::codecs::hdr::HdrEncoder::new(&mut writer).encode(
+                    &pixels,
+                    self.width,
+                    self.height,
+                )?;
+                writer.flush()?;
+                Ok(())
+            });
+        if let Err(err) = result {
+            let _ = fs::remove_file(&temporary_path);
+            return Err(err);
+        }
+
+        fs::rename(&temporary_path, path)?;
+        Ok(())
+    }
+
+    /// Write the image to `path` as an 8-bit PNG file. Colors are clamped to `[0, 1]`.
+    pub fn save_png(&self, path: &str) -> ::image::ImageResult<()> {
+        let buffer = ::image::RgbImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            let color = self.data[self.width * y as usize + x as usize];
+            ::image::Rgb([to_byte(color.r), to_byte(color.g), to_byte(color.b)])
+        });
+        buffer.save_with_format(path, ::image::ImageFormat::Png)
+    }
+
+    /**
+    The pixels as 8-bit RGBA, four bytes per pixel with an opaque alpha, row by row from the top
+    of the image and left to right within each row. Colors are clamped to `[0, 1]`.
+    */
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(4 * self.data.len());
+        for color in &self.data {
+            bytes.extend([to_byte(color.r), to_byte(color.g), to_byte(color.b), 255]);
+        }
+        bytes
+    }
+}
+
+/// A channel value in `[0, 1]` as a byte, clamping anything outside.
+fn to_byte(value: Real) -> u8 {
+    (value * 255.0).clamp(0.0, 255.0).round() as u8
+}
+
+/// Append the decimal digits of `value`.
+fn push_decimal(bytes: &mut Vec<u8>, value: u8) {
+    if value >= 100 {
+        bytes.push(b'0' + value / 100);
+    }
+    if value >= 10 {
+        bytes.push(b'0' + value / 10 % 10);
+    }
+    bytes.push(b'0' + value % 10);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{io::BufReader, path::PathBuf};
+
+    /// A path for `name` in the system's temporary directory, unique to this process.
+    fn temporary_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn save_leaves_no_temporary_file() {
+        let image = Image {
+            width: 1,
+            height: 1,
+            data: vec![Color {
+                r: 1.0,
+                g: 0.5,
+                b: 0.0,
+            }],
+        };
+        let path = temporary_file("save_leaves_no_temporary_file.ppm");
+        let path = path.to_str().unwrap();
+        image.save(path).unwrap();
+
+        let saved = fs::read(path).unwrap();
+        fs::remove_file(path).unwrap();
+        let mut rendered = Vec::new();
+        image.render(&mut rendered).unwrap();
+        assert_eq!(saved, rendered);
+        assert!(!std::path::Path::new(&format!("{}.tmp", path)).exists());
+    }
+
+    #[test]
+    fn save_hdr_keeps_values_above_one() {
+        let image = Image {
+            width: 2,
+            height: 1,
+            data: vec![
+                Color {
+                    r: 4.0,
+                    g: 0.5,
+                    b: 0.0,
+                },
+                Color {
+                    r: 0.25,
+                    g: 1.0,
+                    b: 12.0,
+                },
+            ],
+        };
+        let path = temporary_file("save_hdr_keeps_values_above_one.hdr");
+        image.save_hdr(path.to_str().unwrap()).unwrap();
+
+        let file = BufReader::new(File::open(&path).unwrap());
+        let pixels = ::image::codecs::hdr::HdrDecoder::new(file)
+            .unwrap()
+            .read_image_hdr()
+            .unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(pixels.len(), 2);
+        for (pixel, color) in pixels.iter().zip(&image.data) {
+            for (read, written) in pixel.0.iter().zip([color.r, color.g, color.b]) {
+                // RGBE shares an exponent between the channels, so small ones lose precision.
+                let tolerance = 0.01 * color.max_channel();
+                assert!(
+                    (*read as Real - written).abs() <= tolerance,
+                    "{} != {}",
+                    read,
+                    written
+                );
+            }
+        }
     }
 }
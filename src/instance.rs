@@ -0,0 +1,263 @@
+use crate::{
+    bounds::Bounds3,
+    hit::Hit,
+    interval::Interval,
+    mat4::Mat4,
+    object::{IsObject, Object},
+    ray::Ray,
+    real::Real,
+    vec3::Vec3,
+};
+
+/// An object moved by `offset`.
+pub struct Translate {
+    pub object: Object,
+    pub offset: Vec3,
+}
+
+impl IsObject for Translate {
+    fn hit(&self, ray: &Ray, t_range: Interval) -> Option<Hit> {
+        let moved_ray = Ray::new(ray.origin - self.offset, ray.direction);
+
+        let mut hit = self.object.hit(&moved_ray, t_range)?;
+        hit.point += self.offset;
+        Some(hit)
+    }
+
+    fn bounds(&self) -> Bounds3 {
+        let bounds = self.object.bounds();
+        Bounds3::new(*bounds.min() + self.offset, *bounds.max() + self.offset)
+    }
+
+    fn distance(&self, point: Vec3) -> Real {
+        self.object.distance(point - self.offset)
+    }
+}
+
+/// An object rotated around the Y axis.
+pub struct RotateY {
+    pub object: Object,
+    pub sin_theta: Real,
+    pub cos_theta: Real,
+}
+
+impl RotateY {
+    /// Rotate `object` counter-clockwise (looking down the Y axis) by `angle` degrees.
+    pub fn new(object: Object, angle: Real) -> Self {
+        let theta = angle.to_radians();
+        RotateY {
+            object,
+            sin_theta: theta.sin(),
+            cos_theta: theta.cos(),
+        }
+    }
+
+    /// Rotate a vector from object space to world space.
+    fn to_world(&self, v: Vec3) -> Vec3 {
+        Vec3 {
+            x: self.cos_theta * v.x + self.sin_theta * v.z,
+            y: v.y,
+            z: -self.sin_theta * v.x + self.cos_theta * v.z,
+        }
+    }
+
+    /// Rotate a vector from world space to object space.
+    fn to_object(&self, v: Vec3) -> Vec3 {
+        Vec3 {
+            x: self.cos_theta * v.x - self.sin_theta * v.z,
+            y: v.y,
+            z: self.sin_theta * v.x + self.cos_theta * v.z,
+        }
+    }
+}
+
+impl IsObject for RotateY {
+    fn hit(&self, ray: &Ray, t_range: Interval) -> Option<Hit> {
+        let rotated_ray = Ray::new(self.to_object(ray.origin), self.to_object(ray.direction));
+
+        let mut hit = self.object.hit(&rotated_ray, t_range)?;
+        hit.point = self.to_world(hit.point);
+        hit.normal = self.to_world(hit.normal);
+        hit.tangents = hit
+            .tangents
+            .map(|tangents| tangents.map(|v| self.to_world(v)));
+        Some(hit)
+    }
+
+    fn bounds(&self) -> Bounds3 {
+        let corners = self.object.bounds().corners();
+        let init = Bounds3::point(self.to_world(corners[0]));
+        corners[1..].iter().fold(init, |acc, corner| {
+            acc.union(&Bounds3::point(self.to_world(*corner)))
+        })
+    }
+
+    fn distance(&self, point: Vec3) -> Real {
+        // Rotations don't change distances.
+        self.object.distance(self.to_object(point))
+    }
+}
+
+/**
+An object with an arbitrary affine transformation applied.
+
+Transformations are built up by chaining [`Transform::translate`], [`Transform::rotate_axis`] and
+[`Transform::scale`]; each one is applied after the ones before it.
+*/
+pub struct Transform {
+    object: Object,
+    /// Object space to world space.
+    matrix: Mat4,
+    /// World space to object space.
+    inverse: Mat4,
+}
+
+impl Transform {
+    pub fn new(object: Object) -> Self {
+        Transform {
+            object,
+            matrix: Mat4::IDENTITY,
+            inverse: Mat4::IDENTITY,
+        }
+    }
+
+    fn then(self, matrix: Mat4, inverse: Mat4) -> Self {
+        Transform {
+            object: self.object,
+            matrix: matrix * self.matrix,
+            inverse: self.inverse * inverse,
+        }
+    }
+
+    #[must_use]
+    pub fn translate(self, offset: Vec3) -> Self {
+        self.then(Mat4::translation(offset), Mat4::translation(-offset))
+    }
+
+    /// Rotate counter-clockwise by `angle` degrees around `axis`.
+    #[must_use]
+    pub fn rotate_axis(self, axis: Vec3, angle: Real) -> Self {
+        let angle = angle.to_radians();
+        self.then(Mat4::rotation(axis, angle), Mat4::rotation(axis, -angle))
+    }
+
+    /// Scale each dimension independently. The factors must be non-zero.
+    #[must_use]
+    pub fn scale(self, factors: Vec3) -> Self {
+        let inverse_factors = Vec3 {
+            x: 1.0 / factors.x,
+            y: 1.0 / factors.y,
+            z: 1.0 / factors.z,
+        };
+        self.then(Mat4::scaling(factors), Mat4::scaling(inverse_factors))
+    }
+}
+
+impl IsObject for Transform {
+    fn hit(&self, ray: &Ray, t_range: Interval) -> Option<Hit> {
+        /*
+        The direction isn't normalised after the transformation, so `t` means the same thing in
+        object space as it does in world space.
+        */
+        let local_ray = Ray::new(
+            self.inverse.transform_point(ray.origin),
+            self.inverse.transform_vector(ray.direction),
+        );
+
+        let mut hit = self.object.hit(&local_ray, t_range)?;
+        hit.point = self.matrix.transform_point(hit.point);
+        hit.normal = self.inverse.transform_normal(hit.normal).unit();
+        hit.tangents = hit
+            .tangents
+            .map(|tangents| tangents.map(|v| self.matrix.transform_vector(v)));
+        Some(hit)
+    }
+
+    fn bounds(&self) -> Bounds3 {
+        let corners = self.object.bounds().corners();
+        let init = Bounds3::point(self.matrix.transform_point(corners[0]));
+        corners[1..].iter().fold(init, |acc, corner| {
+            acc.union(&Bounds3::point(self.matrix.transform_point(*corner)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        material::{Material, Normals},
+        sphere::Sphere,
+        texture::UvTransform,
+        vec3::assert_vec3_eq,
+    };
+
+    fn unit_sphere() -> Object {
+        Object::new(Sphere {
+            center: Vec3::ZERO,
+            radius: 1.0,
+            material: Material::new(Normals()),
+            uv_transform: UvTransform::default(),
+        })
+    }
+
+    #[test]
+    fn translated_sphere_is_hit_where_it_was_moved() {
+        let offset = Vec3 {
+            x: 5.0,
+            y: 0.0,
+            z: 0.0,
+        };
+        let translated: [Object; 2] = [
+            Object::new(Translate {
+                object: unit_sphere(),
+                offset,
+            }),
+            Object::new(Transform::new(unit_sphere()).translate(offset)),
+        ];
+        let down_z = Vec3 {
+            x: 0.0,
+            y: 0.0,
+            z: -1.0,
+        };
+        let t_range = Interval::new(0.0, Real::INFINITY);
+        for object in translated {
+            let ray = Ray::new(
+                offset
+                    + Vec3 {
+                        x: 0.0,
+                        y: 0.0,
+                        z: 10.0,
+                    },
+                down_z,
+            );
+            let hit = object.hit(&ray, t_range).unwrap();
+            assert!((hit.t - 9.0).abs() < 1e-4, "{}", hit.t);
+            assert_vec3_eq!(
+                hit.point,
+                Vec3 {
+                    x: 5.0,
+                    y: 0.0,
+                    z: 1.0
+                },
+                1e-4
+            );
+            assert_vec3_eq!(hit.normal, -down_z, 1e-4);
+
+            // Where the sphere used to be, there's nothing.
+            let ray = Ray::new(-10.0 * down_z, down_z);
+            assert!(object.hit(&ray, t_range).is_none());
+
+            let bounds = object.bounds();
+            assert_vec3_eq!(
+                *bounds.min(),
+                Vec3 {
+                    x: 4.0,
+                    y: -1.0,
+                    z: -1.0
+                },
+                1e-4
+            );
+        }
+    }
+}
@@ -1,15 +1,93 @@
+use crate::real::Real;
+
+/// A range of numbers from `start` to `end`, such as the distances along a ray at which hits count.
+#[derive(Debug, Clone, Copy)]
 pub struct Interval {
-    pub start: f64,
-    pub end: f64,
+    pub start: Real,
+    pub end: Real,
 }
 
 impl Interval {
+    /// Every number.
+    pub const ALL: Self = Interval {
+        start: Real::NEG_INFINITY,
+        end: Real::INFINITY,
+    };
+
+    pub fn new(start: Real, end: Real) -> Self {
+        Interval { start, end }
+    }
+
+    /// Whether `start <= value <= end`.
+    pub fn contains(&self, value: Real) -> bool {
+        self.start <= value && value <= self.end
+    }
+
+    /// Whether `start < value < end`.
+    pub fn surrounds(&self, value: Real) -> bool {
+        self.start < value && value < self.end
+    }
+
+    /// The closest number in the interval to `value`.
+    pub fn clamp(&self, value: Real) -> Real {
+        value.max(self.start).min(self.end)
+    }
+
     pub fn intersect_mut(&mut self, other: &Interval) {
         self.start = self.start.max(other.start);
         self.end = self.end.min(other.end);
     }
 
+    /// Whether no number is in the interval. `[a, a]` isn't empty: it contains `a`.
     pub fn is_empty(&self) -> bool {
-        self.start >= self.end
+        self.start > self.end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_includes_the_ends() {
+        let interval = Interval::new(1.0, 2.0);
+        assert!(interval.contains(1.0));
+        assert!(interval.contains(1.5));
+        assert!(interval.contains(2.0));
+        assert!(!interval.contains(0.5));
+        assert!(!interval.contains(2.5));
+        assert!(Interval::ALL.contains(Real::INFINITY));
+    }
+
+    #[test]
+    fn intersect_mut_keeps_the_overlap() {
+        let mut interval = Interval::new(0.0, 2.0);
+        interval.intersect_mut(&Interval::new(1.0, 3.0));
+        assert_eq!((interval.start, interval.end), (1.0, 2.0));
+        assert!(!interval.is_empty());
+
+        interval.intersect_mut(&Interval::ALL);
+        assert_eq!((interval.start, interval.end), (1.0, 2.0));
+    }
+
+    #[test]
+    fn intersect_mut_of_disjoint_intervals_is_empty() {
+        let mut interval = Interval::new(0.0, 1.0);
+        interval.intersect_mut(&Interval::new(2.0, 3.0));
+        assert!(interval.is_empty());
+        assert!(!interval.contains(0.5));
+        assert!(!interval.contains(2.5));
+    }
+
+    #[test]
+    fn single_number_is_not_empty() {
+        let interval = Interval::new(1.0, 1.0);
+        assert!(!interval.is_empty());
+        assert!(interval.contains(1.0));
+
+        let mut touching = Interval::new(0.0, 1.0);
+        touching.intersect_mut(&Interval::new(1.0, 2.0));
+        assert_eq!((touching.start, touching.end), (1.0, 1.0));
+        assert!(!touching.is_empty());
     }
 }
@@ -1,14 +1,35 @@
 pub mod axis;
+pub mod background;
 pub mod bounds;
 pub mod bvh;
 pub mod camera;
 pub mod color;
+pub mod cylinder;
+pub mod denoise;
+pub mod disk;
+pub mod emitter;
+pub mod filter;
 pub mod hit;
 pub mod image;
+pub mod instance;
 pub mod interval;
+pub mod mat4;
 pub mod material;
+pub mod medium;
+pub mod mesh;
+#[cfg(feature = "obj")]
+pub mod obj;
 pub mod object;
+pub mod onb;
+pub mod plane;
+pub mod quad;
 pub mod ray;
+pub mod real;
+pub mod render;
+pub mod sampler;
+pub mod sampling;
+pub mod scene;
 pub mod sphere;
 pub mod texture;
 pub mod vec3;
+pub mod wireframe;
@@ -1,53 +1,67 @@
 mod cli;
 
 use clap::Parser;
-use cli::{Cli, Dimensions};
-use rand::{prelude::ThreadRng, Rng};
+use cli::{
+    BackgroundKind, Cli, ColorArg, CropArg, DebugMode, Dimensions, ExhaustedKind, FilterKind,
+    NonFiniteKind, PixelArg, ProjectionKind, RangeArg, SamplerKind,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use rt_weekend::{
-    bvh::Bvh,
-    camera::Camera,
+    background::{Background, EnvironmentMap},
+    bounds::Bounds3,
+    camera::{Aperture, ApertureMask, Camera, Projection},
     color::Color,
+    denoise,
+    filter::Filter,
     image::Image,
-    material::{Dielectric, IsMaterial, Lambertian, Material, Metal},
+    interval::Interval,
+    material::{self, Dielectric, Diffuse, IsMaterial, Lambertian, Material, Metal},
+    medium::ConstantMedium,
     object::{IsObject, Object},
+    plane::Plane,
     ray::Ray,
+    real::Real,
+    render::{Accumulator, Exhausted, PathLimits, Renderer, Stats},
+    sampler::SamplePattern,
+    scene::{self, Description, Scene},
     sphere::Sphere,
-    texture::{self, Texture},
+    texture::{self, ColorSpace, Texture, UvTransform},
     vec3::Vec3,
+    wireframe::Wireframe,
 };
-use std::{io, sync::Arc, thread};
+use std::{io, sync::Arc, time::Duration};
+
+/// Images with more pixels than this get a warning: the buffers for 100 megapixels take gigabytes.
+const MAX_PIXELS: usize = 100_000_000;
 
-fn random_scene() -> Vec<Object> {
+fn random_scene<R: Rng>(rng: &mut R) -> Vec<Object> {
     let mut world = Vec::new();
 
-    let ground_material = Material::new(Lambertian {
-        albedo: Texture::new(texture::Constant {
-            color: Color {
-                r: 0.5,
-                g: 0.5,
-                b: 0.5,
-            },
-        }),
+    let ground_material = Material::new(Diffuse {
+        albedo: Color {
+            r: 0.5,
+            g: 0.5,
+            b: 0.5,
+        },
     });
 
-    world.push(Object::new(Sphere {
-        center: Vec3 {
+    world.push(Object::new(Plane {
+        point: Vec3::ZERO,
+        normal: Vec3 {
             x: 0.0,
-            y: -1000.0,
+            y: 1.0,
             z: 0.0,
         },
-        radius: 1000.0,
         material: ground_material,
     }));
 
-    let mut rng = rand::thread_rng();
     for a in -11..11 {
         for b in -11..11 {
-            let choose_mat = rng.gen::<f64>();
+            let choose_mat = rng.gen::<Real>();
             let center = Vec3 {
-                x: a as f64 + 0.9 * rng.gen::<f64>(),
+                x: a as Real + 0.9 * rng.gen::<Real>(),
                 y: 0.2,
-                z: b as f64 + 0.9 * rng.gen::<f64>(),
+                z: b as Real + 0.9 * rng.gen::<Real>(),
             };
             if (center
                 - Vec3 {
@@ -61,17 +75,16 @@ fn random_scene() -> Vec<Object> {
                 let sphere_material: Material;
 
                 if choose_mat < 0.8 {
-                    let albedo = Texture::new(texture::Constant {
-                        color: rng.gen::<Color>() * rng.gen::<Color>(),
-                    });
-                    sphere_material = Material::new(Lambertian { albedo });
+                    let albedo = rng.gen::<Color>() * rng.gen::<Color>();
+                    sphere_material = Material::new(Diffuse { albedo });
                 } else if choose_mat < 0.95 {
                     let albedo = rng.gen::<Color>();
                     let fuzziness = rng.gen_range(0.0..0.5);
-                    sphere_material = Material::new(Metal { albedo, fuzziness });
+                    sphere_material = Material::new(Metal::new(albedo, fuzziness));
                 } else {
                     sphere_material = Material::new(Dielectric {
                         refractive_index: 1.5,
+                        dispersion: 0.0,
                     });
                 }
 
@@ -79,6 +92,7 @@ fn random_scene() -> Vec<Object> {
                     center,
                     radius: 0.2,
                     material: sphere_material,
+                    uv_transform: UvTransform::default(),
                 }))
             }
         }
@@ -93,7 +107,9 @@ fn random_scene() -> Vec<Object> {
         radius: 1.0,
         material: Material::new(Dielectric {
             refractive_index: 1.5,
+            dispersion: 0.0,
         }),
+        uv_transform: UvTransform::default(),
     }));
 
     world.push(Object::new(Sphere {
@@ -103,15 +119,14 @@ fn random_scene() -> Vec<Object> {
             z: 0.0,
         },
         radius: 1.0,
-        material: Material::new(Lambertian {
-            albedo: Texture::new(texture::Constant {
-                color: Color {
-                    r: 0.4,
-                    g: 0.2,
-                    b: 0.1,
-                },
-            }),
+        material: Material::new(Diffuse {
+            albedo: Color {
+                r: 0.4,
+                g: 0.2,
+                b: 0.1,
+            },
         }),
+        uv_transform: UvTransform::default(),
     }));
 
     world.push(Object::new(Sphere {
@@ -121,14 +136,15 @@ fn random_scene() -> Vec<Object> {
             z: 0.0,
         },
         radius: 1.0,
-        material: Material::new(Metal {
-            albedo: Color {
+        material: Material::new(Metal::new(
+            Color {
                 r: 0.7,
                 g: 0.6,
                 b: 0.5,
             },
-            fuzziness: 0.0,
-        }),
+            0.0,
+        )),
+        uv_transform: UvTransform::default(),
     }));
 
     world.push(Object::new(Sphere {
@@ -139,77 +155,145 @@ fn random_scene() -> Vec<Object> {
         },
         radius: 1.0,
         material: Material::new(Lambertian {
-            albedo: Texture::new(texture::Image::new("earth.png")),
+            albedo: Texture::new(texture::Image::new("earth.png", ColorSpace::Srgb)),
         }),
+        uv_transform: UvTransform::default(),
     }));
 
     world
 }
 
-fn ray_color(rng: &mut ThreadRng, ray: &Ray, world: &dyn IsObject, depth: usize) -> Color {
-    if depth == 0 {
-        return Color {
-            r: 0.0,
-            g: 0.0,
-            b: 0.0,
-        };
-    }
+/// The distance from `look_from` to the first object toward `look_at`, if there is one.
+fn autofocus(objects: &[Object], look_from: Vec3, look_at: Vec3) -> Option<Real> {
+    let ray = Ray::new(look_from, (look_at - look_from).unit());
+    objects
+        .hit(&ray, Interval::new(0.001, Real::INFINITY))
+        .map(|hit| hit.t)
+}
 
-    if let Some(hit) = world.hit(ray, 0.001, f64::INFINITY) {
-        let material = &hit.material;
-        let emittance = material.emit();
+/// Statistics about the render, on stderr. BVH traversals are only reported with `traversal`.
+fn print_stats(stats: &Stats, render_time: Duration, limits: PathLimits, traversal: bool) {
+    let rays = stats.path_rays + stats.shadow_rays;
+    eprintln!(
+        "Traced {} rays ({} primary, {} scattered, {} shadow) in {:.2?}: {:.0} rays/s.",
+        rays,
+        stats.primary_rays,
+        stats.path_rays - stats.primary_rays,
+        stats.shadow_rays,
+        render_time,
+        rays as f64 / render_time.as_secs_f64()
+    );
+    eprintln!(
+        "Paths: {} escaped, {} absorbed, {} ended by Russian roulette{}, {} cut off at \
+         --recursion-depth {}. Longest path: {} rays.",
+        stats.escaped,
+        stats.absorbed,
+        stats.roulette,
+        limits.rr_depth.map_or(String::new(), |rr_depth| format!(
+            " after {} bounces",
+            rr_depth
+        )),
+        stats.exhausted,
+        limits.recursion_depth,
+        stats.longest_path
+    );
+    if traversal {
+        eprintln!(
+            "BVH: {} rays, {} node visits, {} object tests ({:.2} per ray).",
+            stats.traversal.rays,
+            stats.traversal.node_visits,
+            stats.traversal.primitive_tests,
+            stats.traversal.tests_per_ray()
+        );
+    }
+}
 
-        match material.scatter(rng, ray, &hit) {
-            Some(scatter) => {
-                emittance
-                    + scatter.attenuation * ray_color(rng, &scatter.outgoing, world, depth - 1)
-            }
-            None => emittance,
+/// Write a linear `image`. Files ending in `.hdr` keep the linear colors; anything else is gamma
+/// corrected, clamped to `[lo, hi]`, and written as a PPM.
+fn write_image(image: &Image, output: Option<&str>, (lo, hi): (Real, Real)) {
+    eprintln!("Writing file...");
+    let display = || {
+        let mut display = image.gamma_corrected();
+        for pixel in &mut display.data {
+            *pixel = pixel.clamp_range(lo, hi);
         }
-    } else {
-        let unit_direction = ray.direction.unit();
-        let white = Color {
-            r: 1.0,
-            g: 1.0,
-            b: 1.0,
-        };
-        let blue = Color {
-            r: 0.5,
-            g: 0.7,
-            b: 1.0,
-        };
-        let t = 0.5 * (unit_direction.y + 1.0);
-        (1.0 - t) * white + t * blue
+        display
+    };
+    match output {
+        Some(path) if path.to_lowercase().ends_with(".hdr") => image
+            .save_hdr(path)
+            .unwrap_or_else(|err| panic!("failed to save {}: {}", path, err)),
+        Some(path) => display()
+            .save(path)
+            .unwrap_or_else(|err| panic!("failed to save {}: {}", path, err)),
+        None => display().render(&mut io::stdout()).expect("render failed"),
     }
 }
 
-fn get_pixel_color(
-    rng: &mut ThreadRng,
-    camera: &Camera,
-    world: &dyn IsObject,
-    recursion_depth: usize,
-    rays_per_pixel: usize,
-    rays_per_pixel_f64: f64,
-    x: f64,
-    y: f64,
-    x_total: f64,
-    y_total: f64,
-) -> Color {
-    let mut color = Color {
-        r: 0.0,
-        g: 0.0,
-        b: 0.0,
-    };
-    let x = x as f64;
+/// One value per pixel from a grayscale image, from 0 for black to `scale` for white.
+fn load_importance_map(path: &str, width: usize, height: usize, scale: Real) -> Vec<Real> {
+    let map = image::open(path)
+        .unwrap_or_else(|err| panic!("failed to load importance map: {}", err))
+        .to_luma8();
+    if (map.width() as usize, map.height() as usize) != (width, height) {
+        panic!(
+            "importance map is {}x{}, but the image is {}x{}",
+            map.width(),
+            map.height(),
+            width,
+            height
+        );
+    }
+    map.pixels()
+        .map(|pixel| pixel[0] as Real / 255.0 * scale)
+        .collect()
+}
 
-    for _ in 0..rays_per_pixel {
-        let u = (x + rng.gen::<f64>()) / x_total;
-        let v = (y + rng.gen::<f64>()) / y_total;
-        let ray = camera.get_ray(u, v);
-        color += ray_color(rng, &ray, world, recursion_depth);
+/// Describe every bounce of `rays` rays through pixel `(x, y)` on stderr.
+fn probe(renderer: &Renderer, x: usize, y: usize, rays: usize, description: Option<&Description>) {
+    let vec3 = |v: Vec3| format!("({:.4}, {:.4}, {:.4})", v.x, v.y, v.z);
+    let color = |c: Color| format!("({:.4}, {:.4}, {:.4})", c.r, c.g, c.b);
+    let colors = renderer.probe(x, y, rays, |index, bounce| {
+        if bounce.depth == 0 {
+            eprintln!("ray {}:", index);
+        }
+        eprint!("  {}: ", bounce.depth);
+        match bounce.hit {
+            Some(hit) => {
+                let name = hit
+                    .object_id
+                    .and_then(|id| description.and_then(|description| description.object_name(id)));
+                eprintln!(
+                    "hit {}{} at {}, normal {}, t {:.4}",
+                    hit.material.name(),
+                    name.map_or(String::new(), |name| format!(" on \"{}\"", name)),
+                    vec3(hit.point),
+                    vec3(hit.normal),
+                    hit.t
+                );
+            }
+            None => eprintln!("escaped along {}", vec3(bounce.ray.direction)),
+        }
+        match bounce.scattered {
+            Some(direction) => eprintln!("     scattered toward {}", vec3(direction)),
+            None => eprintln!("     path ended"),
+        }
+        eprintln!(
+            "     radiance {}, throughput {}",
+            color(bounce.radiance),
+            color(bounce.throughput)
+        );
+    });
+    for (index, ray_color) in colors.iter().enumerate() {
+        eprintln!("ray {} color: {}", index, color(*ray_color));
     }
+    eprintln!("mean color: {}", color(Color::mean(colors)));
+}
 
-    (color / rays_per_pixel_f64).sqrt()
+fn write_png(image: &Image, path: &str) {
+    image
+        .save_png(path)
+        .unwrap_or_else(|err| panic!("failed to save {}: {}", path, err))
 }
 
 fn main() {
@@ -217,114 +301,297 @@ fn main() {
 
     let num_threads = cli.num_threads.unwrap_or_else(num_cpus::get_physical);
     let rays_per_pixel = cli.rays_per_pixel;
-    let recursion_depth = cli.recursion_depth;
+    let limits = PathLimits {
+        recursion_depth: cli.recursion_depth,
+        rr_depth: cli.rr_depth,
+        exhausted: match cli.debug {
+            Some(DebugMode::DepthExhaustion) => Exhausted::Highlight(Color {
+                r: 1.0,
+                g: 0.0,
+                b: 1.0,
+            }),
+            _ => match cli.depth_exhausted {
+                ExhaustedKind::Black => Exhausted::Light(match &cli.depth_exhausted_color {
+                    Some(ColorArg(color)) => *color,
+                    None => Color::BLACK,
+                }),
+                ExhaustedKind::Background => Exhausted::Background,
+            },
+        },
+        clamp_indirect: cli.clamp_indirect.unwrap_or(Real::INFINITY),
+    };
     let Dimensions {
         width: image_width,
-        height: image_height,
+        height: mut image_height,
     } = cli.dimensions;
-    let aspect_ratio = image_width as f64 / image_height as f64;
-
-    let look_from = Vec3 {
-        x: -13.0,
-        y: 4.0,
-        z: -4.0,
-    };
-    let look_at = Vec3::ZERO;
-    let up = Vec3 {
-        x: 0.0,
-        y: 1.0,
-        z: 0.0,
+    if let ProjectionKind::Equirectangular = cli.projection {
+        if cli.scene.is_none() && image_width != 2 * image_height {
+            image_height = (image_width / 2).max(1);
+            eprintln!(
+                "Equirectangular images are 2:1; using dimensions {},{}.",
+                image_width, image_height
+            );
+        }
+    }
+    if image_width
+        .checked_mul(image_height)
+        .is_none_or(|pixels| pixels > MAX_PIXELS)
+    {
+        eprintln!(
+            "Warning: {}x{} is a very large image, and may not fit in memory.",
+            image_width, image_height
+        );
+    }
+    let aspect_ratio = image_width as Real / image_height as Real;
+    // The Cornell box is lit only by its ceiling light.
+    let cornell = cli.scene.as_deref() == Some("cornell");
+    let background = match cli.environment {
+        _ if cornell => Background::Black,
+        Some(path) => {
+            let environment = EnvironmentMap::open(&path)
+                .unwrap_or_else(|err| panic!("failed to load environment map: {}", err));
+            Background::Environment(environment)
+        }
+        None => match cli.background {
+            BackgroundKind::Gradient => Background::default(),
+            BackgroundKind::Solid => {
+                let ColorArg(color) = cli
+                    .background_color
+                    .expect("--background solid requires --background-color");
+                Background::Solid(color)
+            }
+            BackgroundKind::Black => Background::Black,
+        },
     };
-    let v_fov = 30.0;
-    let focal_distance = (look_from - look_at).norm();
-    let camera = Camera::new(
-        aspect_ratio,
-        v_fov,
-        &up,
-        &look_from,
-        &look_at,
-        0.1,
-        focal_distance,
-    );
-
-    let world = Bvh::from(random_scene().as_ref());
-
-    let rays_per_pixel_f64 = rays_per_pixel as f64;
-    let world_ref = Arc::new(world);
-    let camera_ref = Arc::new(camera);
-    let x_total = (image_width - 1) as f64;
-    let y_total = (image_height - 1) as f64;
-
-    let data = {
-        eprintln!("Using {} threads.", num_threads);
 
-        let outputs_reciever = {
-            let (inputs_sender, inputs_reciever) = crossbeam_channel::unbounded::<usize>();
-            let (outputs_sender, outputs_reciever) =
-                crossbeam_channel::unbounded::<(usize, Vec<Color>)>();
-
-            for _ in 0..num_threads {
-                let inputs_reciever = inputs_reciever.clone();
-                let outputs_sender = outputs_sender.clone();
-                let world_ref = world_ref.clone();
-                let camera_ref = camera_ref.clone();
-
-                let _ = thread::spawn(move || {
-                    let mut rng = rand::thread_rng();
-                    while let Ok(y) = inputs_reciever.recv() {
-                        let y_f64 = y as f64;
-                        let row = (0..image_width)
-                            .map(|x| {
-                                get_pixel_color(
-                                    &mut rng,
-                                    camera_ref.as_ref(),
-                                    world_ref.as_ref(),
-                                    recursion_depth,
-                                    rays_per_pixel,
-                                    rays_per_pixel_f64,
-                                    x as f64,
-                                    y_f64,
-                                    x_total,
-                                    y_total,
-                                )
-                            })
-                            .collect();
-                        outputs_sender.send((y, row)).expect("failed to send color");
-                    }
+    let description = cli.scene.as_ref().map(|path| {
+        if cornell {
+            scene::Description::cornell_box()
+        } else {
+            scene::Description::load(path)
+                .unwrap_or_else(|err| panic!("failed to load scene: {}", err))
+        }
+    });
+    let (camera, (objects, lights)) = match &description {
+        Some(description) => {
+            let camera = description
+                .camera(aspect_ratio, cli.pixel_aspect)
+                .unwrap_or_else(|err| panic!("invalid camera: {}", err));
+            (camera, description.objects_and_lights())
+        }
+        None => {
+            let builder = Camera::builder()
+                .aspect_ratio(aspect_ratio)
+                .pixel_aspect(cli.pixel_aspect)
+                .v_fov(cli.fov)
+                .look_from(cli.look_from.0)
+                .look_at(cli.look_at.0)
+                .aperture(cli.aperture)
+                .aperture_shape(match (cli.aperture_blades, &cli.aperture_mask) {
+                    (Some(blades), _) => Aperture::Polygon { blades },
+                    (None, Some(path)) => Aperture::Custom(Arc::new(
+                        ApertureMask::open(path)
+                            .unwrap_or_else(|err| panic!("failed to load aperture mask: {}", err)),
+                    )),
+                    (None, None) => Aperture::Circle,
+                })
+                .projection(match cli.projection {
+                    ProjectionKind::Perspective => Projection::Perspective,
+                    ProjectionKind::Ortho => Projection::Orthographic {
+                        scale: cli.ortho_scale,
+                    },
+                    ProjectionKind::Equirectangular => Projection::Equirectangular,
                 });
+            let objects = random_scene(&mut StdRng::seed_from_u64(cli.seed));
+            let focal_distance = if cli.autofocus {
+                let focal_distance = autofocus(&objects, cli.look_from.0, cli.look_at.0);
+                if focal_distance.is_none() {
+                    eprintln!("Nothing to --autofocus on; focusing on --look-at.");
+                }
+                focal_distance
+            } else {
+                cli.focal_distance
+            };
+            let camera = match focal_distance {
+                Some(focal_distance) => builder.focal_distance(focal_distance),
+                None => builder,
             }
+            .build()
+            .unwrap_or_else(|err| panic!("invalid camera: {}", err));
+            (camera, (objects, Vec::new()))
+        }
+    };
 
-            for y in 0..image_height {
-                inputs_sender.send(y).expect("failed to send input");
-            }
-
-            outputs_reciever
-        };
+    let media = description
+        .as_ref()
+        .map(Description::media)
+        .unwrap_or_default();
+    let objects = match cli.debug {
+        None | Some(DebugMode::DepthExhaustion) => objects,
+        Some(DebugMode::Normals) => {
+            let normals = Material::new(material::Normals());
+            objects
+                .iter()
+                .map(|object| object.with_material(normals.clone()))
+                .collect()
+        }
+        Some(DebugMode::Objects) => {
+            let ids = Material::new(material::ObjectIds());
+            // Objects from a scene file with a name already have their index as their id.
+            objects
+                .iter()
+                .enumerate()
+                .map(|(id, object)| object.with_id(id).with_material(ids.clone()))
+                .collect()
+        }
+        Some(DebugMode::Aabb) => {
+            let edges = Material::new(material::Light {
+                brightness: 1.0,
+                color: Color {
+                    r: 0.0,
+                    g: 1.0,
+                    b: 0.0,
+                },
+            });
+            // Unbounded objects, like planes, have no edges to draw.
+            objects
+                .iter()
+                .map(|object| object.bounds())
+                .chain(media.iter().map(ConstantMedium::bounds))
+                .filter(Bounds3::is_finite)
+                .map(|bounds| {
+                    Object::new(Wireframe {
+                        bounds,
+                        thickness: cli.aabb_thickness,
+                        material: edges.clone(),
+                    })
+                })
+                .collect()
+        }
+    };
 
-        let mut rows_remaining = image_height;
-        let data: Vec<Color> = {
-            let mut data: Vec<(usize, Vec<Color>)> = Vec::with_capacity(image_height);
-            while let Ok((y, row)) = outputs_reciever.recv() {
-                data.push((y, row));
-                rows_remaining -= 1;
-                eprint!("\r\x1B[0K");
-                eprint!("rows remaining: {:?}", rows_remaining);
-            }
-            assert!(rows_remaining == 0);
-            data.sort_by(|a, b| b.0.cmp(&a.0));
-            data.into_iter().flat_map(|x| x.1.into_iter()).collect()
-        };
-        eprintln!();
+    // The wireframes replace the lights, along with everything else.
+    let lights = if cli.naive || matches!(cli.debug, Some(DebugMode::Aabb)) {
+        Vec::new()
+    } else {
+        lights
+    };
+    // Media would hide the debugging output.
+    let media = match cli.debug {
+        None | Some(DebugMode::DepthExhaustion) => media,
+        Some(_) => Vec::new(),
+    };
+    let scene = Scene::new(&objects, lights).with_media(media);
+
+    let renderer = Renderer::builder(camera, scene)
+        .background(background)
+        .dimensions(image_width, image_height)
+        .rays_per_pixel(rays_per_pixel)
+        .sample_pattern(match cli.sampler {
+            SamplerKind::Random => SamplePattern::Random,
+            SamplerKind::Stratified => SamplePattern::Stratified,
+            SamplerKind::Halton => SamplePattern::Halton,
+        })
+        .filter(match cli.filter {
+            FilterKind::Box => Filter::Box,
+            FilterKind::Tent => Filter::Tent,
+            FilterKind::Gaussian => Filter::Gaussian,
+        })
+        .limits(limits)
+        .auxiliary(cli.denoise || cli.aov_normals.is_some() || cli.aov_albedo.is_some())
+        .traversal_stats(cli.stats)
+        .seed(cli.seed)
+        .progress(true)
+        .num_threads(num_threads);
+    let renderer = match cli.crop {
+        Some(CropArg(crop)) => renderer.crop(crop),
+        None => renderer,
+    };
+    let renderer = match &cli.importance_map {
+        Some(path) => renderer.importance_map(
+            load_importance_map(path, image_width, image_height, cli.importance_max),
+            (rays_per_pixel as Real * cli.importance_max).ceil() as usize,
+        ),
+        None => renderer,
+    }
+    .build()
+    .unwrap_or_else(|err| panic!("invalid render settings: {}", err));
+    if let Some(PixelArg(x, y)) = cli.probe {
+        if x >= image_width || y >= image_height {
+            panic!(
+                "--probe {},{} is outside the {}x{} image",
+                x, y, image_width, image_height
+            );
+        }
+        probe(&renderer, x, y, cli.probe_rays, description.as_ref());
+        return;
+    }
+    eprintln!("Using {} threads.", num_threads);
 
-        data
+    let clamp = match cli.clamp {
+        Some(RangeArg(lo, hi)) => {
+            assert!(lo <= hi, "clamp range {},{} is empty", lo, hi);
+            (lo, hi)
+        }
+        None => (0.0, 1.0),
     };
 
-    let image = Image {
-        width: image_width,
-        height: image_height,
-        data,
+    // Average the accumulated samples, then filter them.
+    let finish = |accumulator: &Accumulator| {
+        let mut image = accumulator.image();
+        // Replaced before denoising, which would spread them into their neighbours.
+        let non_finite = image.replace_non_finite(match cli.non_finite {
+            NonFiniteKind::Magenta => Color::MAGENTA,
+            NonFiniteKind::Black => Color::BLACK,
+        });
+        if non_finite > 0 {
+            eprintln!("{} pixels were NaN or infinite.", non_finite);
+        }
+        if cli.denoise {
+            let albedo = accumulator.albedo();
+            let normals = accumulator.normals();
+            let guides = [
+                denoise::Guide {
+                    buffer: &albedo.data,
+                    sigma: 0.1,
+                },
+                denoise::Guide {
+                    buffer: &normals.data,
+                    sigma: 0.3,
+                },
+            ];
+            denoise::bilateral(&image, cli.denoise_strength, &guides)
+        } else {
+            image
+        }
     };
 
-    eprintln!("Writing file...");
-    image.render(&mut io::stdout()).expect("render failed");
+    // Without `--progressive`, everything is rendered in a single pass.
+    let pass_size = cli.progressive.unwrap_or(rays_per_pixel);
+    let accumulator = renderer.render_progressive(pass_size, |accumulator| {
+        if cli.progressive.is_some() && accumulator.samples < rays_per_pixel {
+            eprintln!("{}/{} rays per pixel", accumulator.samples, rays_per_pixel);
+            write_image(&finish(accumulator), cli.output.as_deref(), clamp);
+        }
+    });
+    print_stats(
+        &accumulator.stats,
+        accumulator.render_time,
+        limits,
+        cli.stats,
+    );
+
+    write_image(&finish(&accumulator), cli.output.as_deref(), clamp);
+
+    // Albedo is gamma corrected like the image; normals are mapped from `[-1, 1]` to `[0, 1]`.
+    if let Some(path) = &cli.aov_albedo {
+        write_png(&accumulator.albedo().gamma_corrected(), path);
+    }
+    if let Some(path) = &cli.aov_normals {
+        let mut normals = accumulator.normals();
+        for color in normals.data.iter_mut() {
+            *color = color.map(|value| 0.5 * (value + 1.0));
+        }
+        write_png(&normals, path);
+    }
 }
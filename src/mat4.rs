@@ -0,0 +1,128 @@
+use crate::{real::Real, vec3::Vec3};
+
+/// A 4x4 matrix for affine transformations, stored in row-major order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat4 {
+    pub rows: [[Real; 4]; 4],
+}
+
+impl Mat4 {
+    pub const IDENTITY: Self = Mat4 {
+        rows: [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+    };
+
+    /// Move points by `offset`.
+    pub fn translation(offset: Vec3) -> Self {
+        Mat4 {
+            rows: [
+                [1.0, 0.0, 0.0, offset.x],
+                [0.0, 1.0, 0.0, offset.y],
+                [0.0, 0.0, 1.0, offset.z],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    /// Scale each dimension independently.
+    pub fn scaling(factors: Vec3) -> Self {
+        Mat4 {
+            rows: [
+                [factors.x, 0.0, 0.0, 0.0],
+                [0.0, factors.y, 0.0, 0.0],
+                [0.0, 0.0, factors.z, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    /// Rotate counter-clockwise by `angle` radians around `axis`.
+    pub fn rotation(axis: Vec3, angle: Real) -> Self {
+        let Vec3 { x, y, z } = axis.unit();
+        let (sin, cos) = angle.sin_cos();
+        let one_minus_cos = 1.0 - cos;
+        Mat4 {
+            rows: [
+                [
+                    cos + x * x * one_minus_cos,
+                    x * y * one_minus_cos - z * sin,
+                    x * z * one_minus_cos + y * sin,
+                    0.0,
+                ],
+                [
+                    y * x * one_minus_cos + z * sin,
+                    cos + y * y * one_minus_cos,
+                    y * z * one_minus_cos - x * sin,
+                    0.0,
+                ],
+                [
+                    z * x * one_minus_cos - y * sin,
+                    z * y * one_minus_cos + x * sin,
+                    cos + z * z * one_minus_cos,
+                    0.0,
+                ],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    #[must_use]
+    pub fn transpose(&self) -> Self {
+        let mut rows = [[0.0; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.rows[j][i];
+            }
+        }
+        Mat4 { rows }
+    }
+
+    /// Transform a position. Affected by translation.
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        let [x, y, z, _] = self.rows;
+        Vec3 {
+            x: x[0] * point.x + x[1] * point.y + x[2] * point.z + x[3],
+            y: y[0] * point.x + y[1] * point.y + y[2] * point.z + y[3],
+            z: z[0] * point.x + z[1] * point.y + z[2] * point.z + z[3],
+        }
+    }
+
+    /// Transform a direction. Unaffected by translation.
+    pub fn transform_vector(&self, vector: Vec3) -> Vec3 {
+        let [x, y, z, _] = self.rows;
+        Vec3 {
+            x: x[0] * vector.x + x[1] * vector.y + x[2] * vector.z,
+            y: y[0] * vector.x + y[1] * vector.y + y[2] * vector.z,
+            z: z[0] * vector.x + z[1] * vector.y + z[2] * vector.z,
+        }
+    }
+
+    /**
+    Transform a surface normal.
+
+    Normals are transformed by the inverse-transpose of a transformation, so `self` should be the
+    *inverse* of the transformation being applied. The result is not normalised.
+    */
+    pub fn transform_normal(&self, normal: Vec3) -> Vec3 {
+        self.transpose().transform_vector(normal)
+    }
+}
+
+/// Matrix multiplication.
+impl std::ops::Mul<Mat4> for Mat4 {
+    type Output = Mat4;
+
+    fn mul(self, rhs: Mat4) -> Self::Output {
+        let mut rows = [[0.0; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.rows[i][k] * rhs.rows[k][j]).sum();
+            }
+        }
+        Mat4 { rows }
+    }
+}
@@ -1,11 +1,14 @@
 use crate::{
     color::Color,
     hit::{Face, Hit},
+    onb::Onb,
     ray::Ray,
+    real::{consts, Real},
+    sampling::{random_cosine_direction, random_in_unit_sphere, random_unit_vector},
     texture::{IsTexture, Texture},
     vec3::Vec3,
 };
-use rand::{prelude::ThreadRng, Rng};
+use rand::{Rng, RngCore};
 use std::sync::Arc;
 
 pub struct Scatter {
@@ -13,19 +16,54 @@ pub struct Scatter {
     pub outgoing: Ray,
 }
 
+/// How much light a material reflects in a particular direction.
+pub struct Reflectance {
+    /// The scattering function, multiplied by the cosine of the angle between the direction and
+    /// the normal.
+    pub value: Color,
+    /// The probability density, with respect to solid angle, that the material's `scatter` picks
+    /// the direction.
+    pub pdf: Real,
+}
+
 pub trait IsMaterial: Send + Sync {
     /// Scatter a `ray` that has `hit` a material.
-    fn scatter(&self, _: &mut ThreadRng, _: &Ray, _: &Hit) -> Option<Scatter> {
+    fn scatter(&self, _: &mut dyn RngCore, _: &Ray, _: &Hit) -> Option<Scatter> {
         None
     }
 
-    fn emit(&self) -> Color {
+    /**
+    How much light arriving from `direction` is scattered back along `ray`.
+
+    Used to sample lights directly. Materials that only scatter in a few discrete directions
+    (mirrors, glass) return `None`, because a direction toward a light is almost never one of them.
+    */
+    fn reflectance(&self, _: &Ray, _: &Hit, _: Vec3) -> Option<Reflectance> {
+        None
+    }
+
+    /// The light emitted back along `ray` from the point where it `hit` the material.
+    fn emit(&self, _: &Ray, _: &Hit) -> Color {
         Color {
             r: 0.0,
             g: 0.0,
             b: 0.0,
         }
     }
+
+    /**
+    The material's base color where a ray `hit` it, regardless of lighting. Used for the auxiliary
+    albedo buffer that guides the denoiser. Materials that only emit light are black.
+    */
+    fn albedo_hint(&self, _: &Hit) -> Color {
+        Color::BLACK
+    }
+
+    /// The material's type, for debugging output.
+    fn name(&self) -> &'static str {
+        let name = std::any::type_name::<Self>();
+        name.rsplit("::").next().unwrap_or(name)
+    }
 }
 
 #[derive(Clone)]
@@ -38,23 +76,24 @@ impl Material {
 }
 
 impl IsMaterial for Material {
-    fn scatter(&self, rng: &mut ThreadRng, ray: &Ray, hit: &Hit) -> Option<Scatter> {
+    fn scatter(&self, rng: &mut dyn RngCore, ray: &Ray, hit: &Hit) -> Option<Scatter> {
         self.0.scatter(rng, ray, hit)
     }
 
-    fn emit(&self) -> Color {
-        self.0.emit()
+    fn reflectance(&self, ray: &Ray, hit: &Hit, direction: Vec3) -> Option<Reflectance> {
+        self.0.reflectance(ray, hit, direction)
     }
-}
 
-fn random_in_unit_sphere(rng: &mut ThreadRng) -> Vec3 {
-    loop {
-        let p = Vec3::gen_range(rng, -1.0..1.0);
-        if p.norm_squared() >= 1.0 {
-            continue;
-        } else {
-            return p;
-        }
+    fn emit(&self, ray: &Ray, hit: &Hit) -> Color {
+        self.0.emit(ray, hit)
+    }
+
+    fn albedo_hint(&self, hit: &Hit) -> Color {
+        self.0.albedo_hint(hit)
+    }
+
+    fn name(&self) -> &'static str {
+        self.0.name()
     }
 }
 
@@ -63,128 +102,1330 @@ pub struct DiffuseHack {
 }
 
 impl IsMaterial for DiffuseHack {
-    fn scatter(&self, rng: &mut ThreadRng, _: &Ray, hit: &Hit) -> Option<Scatter> {
+    fn scatter(&self, rng: &mut dyn RngCore, _: &Ray, hit: &Hit) -> Option<Scatter> {
         Some(Scatter {
             attenuation: self.albedo,
-            outgoing: Ray {
-                origin: hit.point,
-                direction: hit.normal + random_in_unit_sphere(rng),
-            },
+            outgoing: Ray::new(hit.point, hit.normal + random_in_unit_sphere(rng)),
         })
     }
+
+    fn albedo_hint(&self, _: &Hit) -> Color {
+        self.albedo
+    }
 }
 
 pub struct Lambertian {
     pub albedo: Texture,
 }
 
+impl Lambertian {
+    /// A random direction around the normal, distributed proportionally to the cosine of the angle
+    /// from the normal.
+    fn scatter_direction(rng: &mut dyn RngCore, hit: &Hit) -> Vec3 {
+        let direction = Onb::from_w(hit.normal).local(random_cosine_direction(rng));
+        if direction.near_zero() {
+            hit.normal
+        } else {
+            direction
+        }
+    }
+
+    /// The reflectance of a diffuse surface with the given `albedo`, sampled with
+    /// `scatter_direction`.
+    fn diffuse_reflectance(albedo: Color, hit: &Hit, direction: Vec3) -> Option<Reflectance> {
+        let cos = direction.try_unit()?.dot(hit.normal).max(0.0);
+        Some(Reflectance {
+            value: (cos / consts::PI) * albedo,
+            pdf: cos / consts::PI,
+        })
+    }
+}
+
 impl IsMaterial for Lambertian {
-    fn scatter(&self, rng: &mut ThreadRng, _: &Ray, hit: &Hit) -> Option<Scatter> {
-        fn random_in_unit_sphere(rng: &mut ThreadRng) -> Vec3 {
-            loop {
-                let p = Vec3::gen_range(rng, -1.0..1.0);
-                if p.norm_squared() >= 1.0 {
-                    continue;
-                } else {
-                    return p;
-                }
-            }
+    fn scatter(&self, rng: &mut dyn RngCore, _: &Ray, hit: &Hit) -> Option<Scatter> {
+        Some(Scatter {
+            attenuation: self.albedo.color(&hit.texture_coord),
+            outgoing: Ray::new(hit.point, Lambertian::scatter_direction(rng, hit)),
+        })
+    }
+
+    fn reflectance(&self, _: &Ray, hit: &Hit, direction: Vec3) -> Option<Reflectance> {
+        Lambertian::diffuse_reflectance(self.albedo.color(&hit.texture_coord), hit, direction)
+    }
+
+    fn albedo_hint(&self, hit: &Hit) -> Color {
+        self.albedo.color(&hit.texture_coord)
+    }
+}
+
+/**
+A rough diffuse surface, such as clay or the surface of the moon. Construct with
+[`OrenNayar::new`].
+
+Uses the qualitative Oren–Nayar model. Directions are sampled like [`Lambertian`] (proportional to
+the cosine of the angle from the normal), so the attenuation is the albedo scaled by the
+Oren–Nayar factor for the incoming and outgoing directions.
+*/
+pub struct OrenNayar {
+    pub albedo: Texture,
+    a: Real,
+    b: Real,
+}
+
+impl OrenNayar {
+    /// `roughness` is the standard deviation of the microfacet slope angle, in radians. `0` is
+    /// Lambertian.
+    pub fn new(albedo: Texture, roughness: Real) -> Self {
+        let sigma_squared = roughness * roughness;
+        OrenNayar {
+            albedo,
+            a: 1.0 - 0.5 * sigma_squared / (sigma_squared + 0.33),
+            b: 0.45 * sigma_squared / (sigma_squared + 0.09),
         }
+    }
+}
 
-        let direction = {
-            let mut direction = hit.normal + random_in_unit_sphere(rng).unit();
-            if direction.near_zero() {
-                direction = hit.normal;
-            }
-            direction
+impl OrenNayar {
+    /// How much brighter or darker than Lambertian the surface is, for light arriving from
+    /// `incoming` and leaving toward `outgoing`. Both are unit vectors pointing away from the
+    /// surface.
+    fn factor(&self, normal: Vec3, outgoing: Vec3, incoming: Vec3) -> Real {
+        let cos_outgoing = outgoing.dot(normal).clamp(0.0, 1.0);
+        let cos_incoming = incoming.dot(normal).clamp(0.0, 1.0);
+
+        // The cosine of the azimuthal angle between the two directions.
+        let cos_azimuth = match (
+            (outgoing - cos_outgoing * normal).try_unit(),
+            (incoming - cos_incoming * normal).try_unit(),
+        ) {
+            (Some(outgoing), Some(incoming)) => outgoing.dot(incoming).max(0.0),
+            _ => 0.0,
         };
 
+        // sin(alpha) * tan(beta), where alpha is the larger angle from the normal and beta the
+        // smaller.
+        let (cos_alpha, cos_beta) = if cos_incoming < cos_outgoing {
+            (cos_incoming, cos_outgoing)
+        } else {
+            (cos_outgoing, cos_incoming)
+        };
+        let sin_alpha = (1.0 - cos_alpha * cos_alpha).sqrt();
+        let tan_beta = if cos_beta > 0.0 {
+            (1.0 - cos_beta * cos_beta).sqrt() / cos_beta
+        } else {
+            0.0
+        };
+
+        self.a + self.b * cos_azimuth * sin_alpha * tan_beta
+    }
+}
+
+impl IsMaterial for OrenNayar {
+    fn scatter(&self, rng: &mut dyn RngCore, ray: &Ray, hit: &Hit) -> Option<Scatter> {
+        let direction = Lambertian::scatter_direction(rng, hit);
+        let factor = self.factor(
+            hit.normal,
+            ray.direction.try_unit()?.negate(),
+            direction.unit(),
+        );
+
+        Some(Scatter {
+            attenuation: factor * self.albedo.color(&hit.texture_coord),
+            outgoing: Ray::new(hit.point, direction),
+        })
+    }
+
+    fn reflectance(&self, ray: &Ray, hit: &Hit, direction: Vec3) -> Option<Reflectance> {
+        let factor = self.factor(
+            hit.normal,
+            ray.direction.try_unit()?.negate(),
+            direction.try_unit()?,
+        );
+        Lambertian::diffuse_reflectance(
+            factor * self.albedo.color(&hit.texture_coord),
+            hit,
+            direction,
+        )
+    }
+
+    fn albedo_hint(&self, hit: &Hit) -> Color {
+        self.albedo.color(&hit.texture_coord)
+    }
+}
+
+/**
+A [`Lambertian`] surface with the same color everywhere, stored directly instead of being looked
+up in a texture.
+*/
+pub struct Diffuse {
+    pub albedo: Color,
+}
+
+impl IsMaterial for Diffuse {
+    fn scatter(&self, rng: &mut dyn RngCore, _: &Ray, hit: &Hit) -> Option<Scatter> {
+        Some(Scatter {
+            attenuation: self.albedo,
+            outgoing: Ray::new(hit.point, Lambertian::scatter_direction(rng, hit)),
+        })
+    }
+
+    fn reflectance(&self, _: &Ray, hit: &Hit, direction: Vec3) -> Option<Reflectance> {
+        Lambertian::diffuse_reflectance(self.albedo, hit, direction)
+    }
+
+    fn albedo_hint(&self, _: &Hit) -> Color {
+        self.albedo
+    }
+}
+
+/// A diffuse surface that also glows, like a backlit plastic sign.
+pub struct LambertianEmissive {
+    pub albedo: Texture,
+    pub emission: Color,
+}
+
+impl IsMaterial for LambertianEmissive {
+    fn scatter(&self, rng: &mut dyn RngCore, _: &Ray, hit: &Hit) -> Option<Scatter> {
         Some(Scatter {
             attenuation: self.albedo.color(&hit.texture_coord),
-            outgoing: Ray {
-                origin: hit.point,
-                direction,
-            },
+            outgoing: Ray::new(hit.point, Lambertian::scatter_direction(rng, hit)),
         })
     }
+
+    fn reflectance(&self, _: &Ray, hit: &Hit, direction: Vec3) -> Option<Reflectance> {
+        Lambertian::diffuse_reflectance(self.albedo.color(&hit.texture_coord), hit, direction)
+    }
+
+    fn emit(&self, _: &Ray, _: &Hit) -> Color {
+        self.emission
+    }
+
+    fn albedo_hint(&self, hit: &Hit) -> Color {
+        self.albedo.color(&hit.texture_coord)
+    }
 }
 
+/// A reflective material. Construct with [`Metal::new`].
 pub struct Metal {
     pub albedo: Color,
-    pub fuzziness: f64,
+    fuzziness: Real,
+}
+
+impl Metal {
+    /**
+    `fuzziness` is clamped to `[0, 1]`. `0` is a perfect mirror; beyond `1`, reflected rays would
+    regularly point into the surface.
+    */
+    pub fn new(albedo: Color, fuzziness: Real) -> Self {
+        Metal {
+            albedo,
+            fuzziness: fuzziness.clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn fuzziness(&self) -> Real {
+        self.fuzziness
+    }
 }
 
 impl IsMaterial for Metal {
-    fn scatter(&self, rng: &mut ThreadRng, ray: &Ray, hit: &Hit) -> Option<Scatter> {
+    fn scatter(&self, rng: &mut dyn RngCore, ray: &Ray, hit: &Hit) -> Option<Scatter> {
         let direction =
             ray.direction.reflect(&hit.normal) + self.fuzziness * random_in_unit_sphere(rng);
 
         if direction.dot(hit.normal) > 0.0 {
             Some(Scatter {
                 attenuation: self.albedo,
-                outgoing: Ray {
-                    origin: hit.point,
-                    direction,
-                },
+                outgoing: Ray::new(hit.point, direction),
             })
         } else {
             None
         }
     }
+
+    fn albedo_hint(&self, _: &Hit) -> Color {
+        self.albedo
+    }
+}
+
+/**
+A rough metal, modelled as a surface of tiny mirrors whose orientations follow the GGX
+distribution. Construct with [`Microfacet::new`], or [`Microfacet::anisotropic`] for brushed
+metals.
+
+Each scattered ray reflects off a single microfacet normal, sampled from the distribution of
+normals visible from the incoming direction (Heitz, "Sampling the GGX Distribution of Visible
+Normals", 2018). The attenuation is the Fresnel reflectance (Schlick's approximation, with `albedo`
+as the reflectance at normal incidence) times the fraction of reflected light that isn't shadowed
+by other microfacets, so it never exceeds `albedo`.
+
+Anisotropic roughness is measured along the surface's tangents (see [`Tangents`](crate::hit::Tangents)). Surfaces without
+tangents orient it arbitrarily.
+*/
+pub struct Microfacet {
+    pub albedo: Color,
+    roughness_u: Real,
+    roughness_v: Real,
+}
+
+impl Microfacet {
+    /// `roughness` is clamped to `[0, 1]`. `0` is a perfect mirror.
+    pub fn new(albedo: Color, roughness: Real) -> Self {
+        Microfacet::anisotropic(albedo, roughness, roughness)
+    }
+
+    /**
+    A metal that's rougher in one direction than the other. `roughness_u` is along the direction
+    of increasing `u` texture coordinate, and `roughness_v` along that of increasing `v`. Both are
+    clamped to `[0, 1]`.
+    */
+    pub fn anisotropic(albedo: Color, roughness_u: Real, roughness_v: Real) -> Self {
+        Microfacet {
+            albedo,
+            roughness_u: roughness_u.clamp(0.0, 1.0),
+            roughness_v: roughness_v.clamp(0.0, 1.0),
+        }
+    }
+
+    /// The roughness along `u` and along `v`.
+    pub fn roughness(&self) -> (Real, Real) {
+        (self.roughness_u, self.roughness_v)
+    }
+
+    fn fresnel(&self, cos_theta: Real) -> Color {
+        let weight = (1.0 - cos_theta.clamp(0.0, 1.0)).powi(5);
+        (1.0 - weight) * self.albedo
+            + weight
+                * Color {
+                    r: 1.0,
+                    g: 1.0,
+                    b: 1.0,
+                }
+    }
+
+    /// Smith's auxiliary function for GGX, for a unit `direction` in the surface's local basis
+    /// (where the macrosurface normal is +Z), with GGX widths `alpha_u` and `alpha_v`.
+    fn lambda(alpha_u: Real, alpha_v: Real, direction: Vec3) -> Real {
+        let stretched_squared = (alpha_u * direction.x).powi(2) + (alpha_v * direction.y).powi(2);
+        let tan_squared = stretched_squared / (direction.z * direction.z);
+        0.5 * (-1.0 + (1.0 + tan_squared).sqrt())
+    }
+
+    /// A basis around the normal where `hit`, with `u` along the surface's tangent when it has one.
+    fn basis(hit: &Hit) -> Onb {
+        let tangent = hit.tangents.and_then(|tangents| {
+            (tangents.tangent - tangents.tangent.dot(hit.normal) * hit.normal).try_unit()
+        });
+        match tangent {
+            Some(u) => Onb {
+                u,
+                v: hit.normal.cross(u),
+                w: hit.normal,
+            },
+            None => Onb::from_w(hit.normal),
+        }
+    }
+}
+
+impl IsMaterial for Microfacet {
+    fn scatter(&self, rng: &mut dyn RngCore, ray: &Ray, hit: &Hit) -> Option<Scatter> {
+        let outgoing = ray.direction.try_unit()?.negate();
+        let cos_outgoing = outgoing.dot(hit.normal);
+        if cos_outgoing <= 0.0 {
+            return None;
+        }
+
+        // The GGX width parameters. Squaring the roughness makes it perceptually linear.
+        let alpha_u = self.roughness_u * self.roughness_u;
+        let alpha_v = self.roughness_v * self.roughness_v;
+
+        if alpha_u.max(alpha_v) < 1e-4 {
+            return Some(Scatter {
+                attenuation: self.fresnel(cos_outgoing),
+                outgoing: Ray::new(hit.point, ray.direction.reflect(&hit.normal)),
+            });
+        }
+
+        // Work in a basis where the macrosurface normal is +Z.
+        let basis = Microfacet::basis(hit);
+        let local = Vec3 {
+            x: outgoing.dot(basis.u),
+            y: outgoing.dot(basis.v),
+            z: cos_outgoing,
+        };
+
+        // Stretch the view direction so that the distribution becomes a hemisphere.
+        let view = Vec3 {
+            x: alpha_u * local.x,
+            y: alpha_v * local.y,
+            z: local.z,
+        }
+        .unit();
+        let length_squared = view.x * view.x + view.y * view.y;
+        let t_1 = if length_squared > 0.0 {
+            Vec3 {
+                x: -view.y,
+                y: view.x,
+                z: 0.0,
+            } / length_squared.sqrt()
+        } else {
+            Vec3 {
+                x: 1.0,
+                y: 0.0,
+                z: 0.0,
+            }
+        };
+        let t_2 = view.cross(t_1);
+
+        // Sample the projected area of the visible hemisphere.
+        let r = rng.gen::<Real>().sqrt();
+        let phi = 2.0 * consts::PI * rng.gen::<Real>();
+        let p_1 = r * phi.cos();
+        let s = 0.5 * (1.0 + view.z);
+        let p_2 = (1.0 - s) * (1.0 - p_1 * p_1).sqrt() + s * r * phi.sin();
+        let hemisphere_normal =
+            p_1 * t_1 + p_2 * t_2 + (1.0 - p_1 * p_1 - p_2 * p_2).max(0.0).sqrt() * view;
+
+        // Unstretch back to the microfacet normal.
+        let microfacet_normal = Vec3 {
+            x: alpha_u * hemisphere_normal.x,
+            y: alpha_v * hemisphere_normal.y,
+            z: hemisphere_normal.z.max(0.0),
+        }
+        .try_unit()?;
+
+        let cos_microfacet = local.dot(microfacet_normal);
+        let incoming = 2.0 * cos_microfacet * microfacet_normal - local;
+        if incoming.z <= 0.0 {
+            // Reflected into the surface, so it's absorbed by a neighbouring microfacet.
+            return None;
+        }
+
+        // The ratio of the shadowing-masking term to the masking term, G2 / G1.
+        let lambda_outgoing = Self::lambda(alpha_u, alpha_v, local);
+        let geometry = (1.0 + lambda_outgoing)
+            / (1.0 + lambda_outgoing + Self::lambda(alpha_u, alpha_v, incoming));
+
+        Some(Scatter {
+            attenuation: geometry * self.fresnel(cos_microfacet),
+            outgoing: Ray::new(hit.point, basis.local(incoming)),
+        })
+    }
+
+    fn albedo_hint(&self, _: &Hit) -> Color {
+        self.albedo
+    }
 }
 
+/**
+A clear substance that light passes into, like glass or water.
+
+With a nonzero `dispersion`, red light refracts with index `refractive_index - dispersion`, green
+with `refractive_index`, and blue with `refractive_index + dispersion`, which splits white light
+into colored fringes. Each scatter follows one of the three channels, chosen at random, so
+dispersive materials are noisier.
+*/
 pub struct Dielectric {
-    pub refractive_index: f64,
+    pub refractive_index: Real,
+    pub dispersion: Real,
+}
+
+/**
+Schlick's approximation of the Fresnel reflectance, for a ray at angle `theta` to the normal passing
+from a substance with refractive index `eta_from` into one with `eta_to`.
+
+When the ray is leaving the denser substance, the approximation uses the angle of the refracted ray,
+so that the reflectance reaches 1 at the critical angle.
+*/
+fn schlick(cos_theta: Real, eta_from: Real, eta_to: Real) -> Real {
+    let r_0 = ((eta_from - eta_to) / (eta_from + eta_to)).powi(2);
+    let cos = if eta_from > eta_to {
+        let sin_squared_refracted = (eta_from / eta_to).powi(2) * (1.0 - cos_theta.powi(2));
+        if sin_squared_refracted >= 1.0 {
+            return 1.0;
+        }
+        (1.0 - sin_squared_refracted).sqrt()
+    } else {
+        cos_theta
+    };
+    r_0 + (1.0 - r_0) * (1.0 - cos).powi(5)
 }
 
 impl IsMaterial for Dielectric {
-    fn scatter(&self, rng: &mut ThreadRng, ray: &Ray, hit: &Hit) -> Option<Scatter> {
-        let attenuation = Color {
-            r: 1.0,
-            g: 1.0,
-            b: 1.0,
+    fn scatter(&self, rng: &mut dyn RngCore, ray: &Ray, hit: &Hit) -> Option<Scatter> {
+        let (attenuation, refractive_index) = if self.dispersion == 0.0 {
+            let attenuation = Color {
+                r: 1.0,
+                g: 1.0,
+                b: 1.0,
+            };
+            (attenuation, self.refractive_index)
+        } else {
+            // Only the chosen channel carries light, weighted up for the two that weren't chosen.
+            let black = Color::BLACK;
+            match rng.gen_range(0..3) {
+                0 => (
+                    Color { r: 3.0, ..black },
+                    self.refractive_index - self.dispersion,
+                ),
+                1 => (Color { g: 3.0, ..black }, self.refractive_index),
+                _ => (
+                    Color { b: 3.0, ..black },
+                    self.refractive_index + self.dispersion,
+                ),
+            }
         };
-        let (outside_refractive_index, inside_refractive_index) = match hit.face {
-            Face::Front => (1.0, self.refractive_index),
-            Face::Back => (self.refractive_index, 1.0),
+        // Refractive indices of the substance the ray is leaving and the one it's entering.
+        let (eta_from, eta_to) = match hit.face {
+            Face::Front => (1.0, refractive_index),
+            Face::Back => (refractive_index, 1.0),
         };
 
-        fn reflectance(cos_theta: f64, refractive_index: f64) -> f64 {
-            let r_0 = (1.0 - refractive_index) / (1.0 + refractive_index);
-            r_0 + (1.0 - r_0) * (1.0 - cos_theta).powi(5)
-        }
+        // A zero-length ray has no direction to refract or reflect, so it's absorbed.
+        let unit_direction = ray.direction.try_unit()?;
 
-        let cos_theta = ray.direction.unit().negate().dot(hit.normal);
-        let direction = if reflectance(cos_theta, self.refractive_index) > rng.gen::<f64>() {
+        let cos_theta = unit_direction.negate().dot(hit.normal);
+        let direction = if schlick(cos_theta, eta_from, eta_to) > rng.gen::<Real>() {
             ray.direction.reflect(&hit.normal)
         } else {
-            match ray.direction.unit().refract(
-                &hit.normal,
-                outside_refractive_index,
-                inside_refractive_index,
-            ) {
+            match unit_direction.refract(&hit.normal, eta_from, eta_to) {
                 Some(direction) => direction,
                 None => ray.direction.reflect(&hit.normal),
             }
         };
-        let outgoing = Ray {
-            origin: hit.point,
-            direction,
-        };
+        let outgoing = Ray::new(hit.point, direction);
         Some(Scatter {
             attenuation,
             outgoing,
         })
     }
+
+    fn albedo_hint(&self, _: &Hit) -> Color {
+        Color {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+        }
+    }
+}
+
+/**
+Adds surface detail to another material by tilting its normals, without changing the geometry.
+
+`normals` is a tangent-space normal map. Each channel is mapped from `[0, 1]` to `[-1, 1]`: red is
+along the surface's tangent, green along its bitangent, and blue along the normal, so a flat map is
+`(0.5, 0.5, 1)`. Load normal map images with
+[`ColorSpace::Linear`](crate::texture::ColorSpace::Linear). Surfaces that don't provide tangents
+are left unchanged.
+*/
+pub struct NormalMap {
+    pub material: Material,
+    pub normals: Texture,
+}
+
+impl NormalMap {
+    /// The normal from the map where a ray `hit` the surface, facing the same side as `hit.normal`.
+    pub fn normal(&self, hit: &Hit) -> Option<Vec3> {
+        let tangents = hit.tangents?;
+
+        // Build the tangent space around the outward normal, so the map looks the same from both
+        // sides of the surface.
+        let outward_normal = match hit.face {
+            Face::Front => hit.normal,
+            Face::Back => -hit.normal,
+        };
+        let tangent = (tangents.tangent - tangents.tangent.dot(outward_normal) * outward_normal)
+            .try_unit()?;
+        let bitangent = if outward_normal.cross(tangent).dot(tangents.bitangent) < 0.0 {
+            tangent.cross(outward_normal)
+        } else {
+            outward_normal.cross(tangent)
+        };
+
+        let color = self.normals.color(&hit.texture_coord);
+        let normal = ((2.0 * color.r - 1.0) * tangent
+            + (2.0 * color.g - 1.0) * bitangent
+            + (2.0 * color.b - 1.0) * outward_normal)
+            .try_unit()?;
+        Some(match hit.face {
+            Face::Front => normal,
+            Face::Back => -normal,
+        })
+    }
+
+    fn perturbed(&self, hit: &Hit) -> Hit {
+        let mut hit = hit.clone();
+        if let Some(normal) = self.normal(&hit) {
+            hit.normal = normal;
+        }
+        hit
+    }
+}
+
+impl IsMaterial for NormalMap {
+    fn scatter(&self, rng: &mut dyn RngCore, ray: &Ray, hit: &Hit) -> Option<Scatter> {
+        self.material.scatter(rng, ray, &self.perturbed(hit))
+    }
+
+    fn reflectance(&self, ray: &Ray, hit: &Hit, direction: Vec3) -> Option<Reflectance> {
+        self.material
+            .reflectance(ray, &self.perturbed(hit), direction)
+    }
+
+    fn emit(&self, ray: &Ray, hit: &Hit) -> Color {
+        self.material.emit(ray, hit)
+    }
+
+    fn albedo_hint(&self, hit: &Hit) -> Color {
+        self.material.albedo_hint(hit)
+    }
+}
+
+/**
+Cuts holes in another material, for things like leaves drawn on flat quads.
+
+Where `opacity` is below `threshold`, rays carry on through the surface as if it weren't there.
+Opacity is the mean of the texture's channels, so a grayscale mask works, with white opaque and
+black cut out. Light sampling doesn't see through the holes, so lights behind them are only found
+by rays that pass through.
+*/
+pub struct Masked {
+    pub material: Material,
+    pub opacity: Texture,
+    pub threshold: Real,
+}
+
+impl Masked {
+    /// Whether rays pass through where a ray `hit` the surface.
+    pub fn is_cut_out(&self, hit: &Hit) -> bool {
+        let color = self.opacity.color(&hit.texture_coord);
+        (color.r + color.g + color.b) / 3.0 < self.threshold
+    }
+}
+
+impl IsMaterial for Masked {
+    fn scatter(&self, rng: &mut dyn RngCore, ray: &Ray, hit: &Hit) -> Option<Scatter> {
+        if self.is_cut_out(hit) {
+            Some(Scatter {
+                attenuation: Color {
+                    r: 1.0,
+                    g: 1.0,
+                    b: 1.0,
+                },
+                outgoing: Ray::new(hit.point, ray.direction),
+            })
+        } else {
+            self.material.scatter(rng, ray, hit)
+        }
+    }
+
+    fn reflectance(&self, ray: &Ray, hit: &Hit, direction: Vec3) -> Option<Reflectance> {
+        if self.is_cut_out(hit) {
+            None
+        } else {
+            self.material.reflectance(ray, hit, direction)
+        }
+    }
+
+    fn emit(&self, ray: &Ray, hit: &Hit) -> Color {
+        if self.is_cut_out(hit) {
+            Color::BLACK
+        } else {
+            self.material.emit(ray, hit)
+        }
+    }
+
+    fn albedo_hint(&self, hit: &Hit) -> Color {
+        if self.is_cut_out(hit) {
+            Color::BLACK
+        } else {
+            self.material.albedo_hint(hit)
+        }
+    }
+}
+
+/**
+Two materials layered over each other, such as a clear coat over paint: each ray scatters off
+`first` with probability `weight`, and off `second` otherwise.
+
+The weights of `0` and `1` don't draw a random number, so they scatter exactly like `second` and
+`first`. Lights are only sampled directly when both materials can be, so a [`Metal`] layer means
+lights are found by scattering alone.
+
+The emitted light is always the sum of both materials', whatever the weight: `weight` only chooses
+which layer scatters.
+*/
+pub struct Mix {
+    pub first: Material,
+    pub second: Material,
+    pub weight: Real,
+}
+
+impl Mix {
+    /// Pick a layer for the next ray.
+    fn choose(&self, rng: &mut dyn RngCore) -> &Material {
+        let first = if self.weight >= 1.0 {
+            true
+        } else if self.weight <= 0.0 {
+            false
+        } else {
+            rng.gen::<Real>() < self.weight
+        };
+        if first {
+            &self.first
+        } else {
+            &self.second
+        }
+    }
+}
+
+impl IsMaterial for Mix {
+    fn scatter(&self, rng: &mut dyn RngCore, ray: &Ray, hit: &Hit) -> Option<Scatter> {
+        self.choose(rng).scatter(rng, ray, hit)
+    }
+
+    fn reflectance(&self, ray: &Ray, hit: &Hit, direction: Vec3) -> Option<Reflectance> {
+        let weight = self.weight.clamp(0.0, 1.0);
+        if weight == 1.0 {
+            return self.first.reflectance(ray, hit, direction);
+        }
+        if weight == 0.0 {
+            return self.second.reflectance(ray, hit, direction);
+        }
+        let first = self.first.reflectance(ray, hit, direction)?;
+        let second = self.second.reflectance(ray, hit, direction)?;
+        Some(Reflectance {
+            value: second.value.lerp(first.value, weight),
+            pdf: weight * first.pdf + (1.0 - weight) * second.pdf,
+        })
+    }
+
+    fn emit(&self, ray: &Ray, hit: &Hit) -> Color {
+        self.first.emit(ray, hit) + self.second.emit(ray, hit)
+    }
+
+    fn albedo_hint(&self, hit: &Hit) -> Color {
+        let weight = self.weight.clamp(0.0, 1.0);
+        self.second
+            .albedo_hint(hit)
+            .lerp(self.first.albedo_hint(hit), weight)
+    }
+}
+
+/// Scatters rays uniformly in all directions. Used for volumes.
+pub struct Isotropic {
+    pub albedo: Texture,
+}
+
+impl IsMaterial for Isotropic {
+    fn scatter(&self, rng: &mut dyn RngCore, _: &Ray, hit: &Hit) -> Option<Scatter> {
+        Some(Scatter {
+            attenuation: self.albedo.color(&hit.texture_coord),
+            outgoing: Ray::new(hit.point, random_unit_vector(rng)),
+        })
+    }
+
+    fn reflectance(&self, _: &Ray, hit: &Hit, _: Vec3) -> Option<Reflectance> {
+        let pdf = 1.0 / (4.0 * consts::PI);
+        Some(Reflectance {
+            value: pdf * self.albedo.color(&hit.texture_coord),
+            pdf,
+        })
+    }
+
+    fn albedo_hint(&self, hit: &Hit) -> Color {
+        self.albedo.color(&hit.texture_coord)
+    }
 }
 
 pub struct Light {
-    pub brightness: f64,
+    pub brightness: Real,
     pub color: Color,
 }
 
 impl IsMaterial for Light {
-    fn emit(&self) -> Color {
+    fn emit(&self, _: &Ray, _: &Hit) -> Color {
         self.brightness * self.color
     }
 }
+
+/**
+A light that shines along `direction`, like a stage light.
+
+It's fully bright within `inner_angle` degrees of `direction`, dark beyond `outer_angle` degrees,
+and fades smoothly in between.
+*/
+pub struct SpotLight {
+    pub brightness: Real,
+    pub color: Color,
+    pub direction: Vec3,
+    pub inner_angle: Real,
+    pub outer_angle: Real,
+}
+
+impl SpotLight {
+    /// The fraction of the light's brightness that leaves it in `direction`.
+    pub fn falloff(&self, direction: Vec3) -> Real {
+        let cos_theta = match direction.try_unit() {
+            Some(direction) => direction.dot(self.direction.unit()),
+            None => return 0.0,
+        };
+        let cos_inner = self.inner_angle.to_radians().cos();
+        let cos_outer = self.outer_angle.to_radians().cos();
+        if cos_theta >= cos_inner {
+            1.0
+        } else if cos_theta <= cos_outer {
+            0.0
+        } else {
+            // Smoothstep from the outer edge to the inner one.
+            let t = (cos_theta - cos_outer) / (cos_inner - cos_outer);
+            t * t * (3.0 - 2.0 * t)
+        }
+    }
+}
+
+impl IsMaterial for SpotLight {
+    fn emit(&self, ray: &Ray, _: &Hit) -> Color {
+        (self.brightness * self.falloff(-ray.direction)) * self.color
+    }
+}
+
+/// A light whose color varies over its surface.
+pub struct DiffuseLight {
+    pub emit: Texture,
+}
+
+impl IsMaterial for DiffuseLight {
+    fn emit(&self, _: &Ray, hit: &Hit) -> Color {
+        self.emit.color(&hit.texture_coord)
+    }
+}
+
+/**
+Gives each object a different color, chosen from its id (see
+[`Object::with_id`](crate::object::Object::with_id)), for debugging. Objects without an id are
+dark gray.
+*/
+pub struct ObjectIds();
+
+impl IsMaterial for ObjectIds {
+    fn emit(&self, _: &Ray, hit: &Hit) -> Color {
+        match hit.object_id {
+            Some(id) => {
+                // Neighbouring ids get unrelated colors.
+                let hash = (id as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+                let channel = |shift: u32| 0.2 + 0.8 * ((hash >> shift) & 0xFF) as Real / 255.0;
+                Color {
+                    r: channel(40),
+                    g: channel(48),
+                    b: channel(56),
+                }
+            }
+            None => Color {
+                r: 0.1,
+                g: 0.1,
+                b: 0.1,
+            },
+        }
+    }
+}
+
+/// Shows surface normals as colors, for debugging. Each component is mapped from `[-1, 1]` to `[0, 1]`.
+pub struct Normals();
+
+impl IsMaterial for Normals {
+    fn emit(&self, _: &Ray, hit: &Hit) -> Color {
+        Color {
+            r: 0.5 * (hit.normal.x + 1.0),
+            g: 0.5 * (hit.normal.y + 1.0),
+            b: 0.5 * (hit.normal.z + 1.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::Channel, hit::Tangents, texture, vec3::assert_vec3_eq};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn gray() -> Texture {
+        Texture::new(texture::Constant {
+            color: Color {
+                r: 0.5,
+                g: 0.5,
+                b: 0.5,
+            },
+        })
+    }
+
+    /// A hit at the origin, on the front of a surface facing +Z.
+    fn hit_at_origin(material: Material) -> Hit {
+        Hit {
+            point: Vec3::ZERO,
+            normal: Vec3 {
+                x: 0.0,
+                y: 0.0,
+                z: 1.0,
+            },
+            t: 1.0,
+            face: Face::Front,
+            material,
+            texture_coord: texture::Coord { u: 0.5, v: 0.5 },
+            tangents: None,
+            object_id: None,
+        }
+    }
+
+    /// A ray arriving at the origin at an angle, from above the surface.
+    fn incoming() -> Ray {
+        Ray::new(
+            Vec3 {
+                x: 0.3,
+                y: 0.2,
+                z: 1.0,
+            },
+            Vec3 {
+                x: -0.3,
+                y: -0.2,
+                z: -1.0,
+            },
+        )
+    }
+
+    #[test]
+    fn mix_of_weight_one_is_its_first_material() {
+        let first = Material::new(Lambertian { albedo: gray() });
+        let second = Material::new(LambertianEmissive {
+            albedo: gray(),
+            emission: Color {
+                r: 2.0,
+                g: 1.0,
+                b: 0.0,
+            },
+        });
+        let mix = Mix {
+            first: first.clone(),
+            second: second.clone(),
+            weight: 1.0,
+        };
+        let (ray, hit) = (incoming(), hit_at_origin(Material::new(Normals())));
+
+        let (mut mix_rng, mut first_rng) = (StdRng::seed_from_u64(1), StdRng::seed_from_u64(1));
+        for _ in 0..10 {
+            let from_mix = mix.scatter(&mut mix_rng, &ray, &hit).unwrap();
+            let from_first = first.scatter(&mut first_rng, &ray, &hit).unwrap();
+            assert!(from_mix.attenuation.approx_eq(from_first.attenuation, 0.0));
+            assert_eq!(from_mix.outgoing.origin, from_first.outgoing.origin);
+            assert_eq!(from_mix.outgoing.direction, from_first.outgoing.direction);
+        }
+        // Choosing the layer didn't draw a random number.
+        assert_eq!(mix_rng.next_u64(), first_rng.next_u64());
+
+        let direction = Vec3 {
+            x: 0.1,
+            y: -0.4,
+            z: 0.8,
+        };
+        let from_mix = mix.reflectance(&ray, &hit, direction).unwrap();
+        let from_first = first.reflectance(&ray, &hit, direction).unwrap();
+        assert!(from_mix.value.approx_eq(from_first.value, 0.0));
+        assert_eq!(from_mix.pdf, from_first.pdf);
+        assert!(mix
+            .albedo_hint(&hit)
+            .approx_eq(first.albedo_hint(&hit), 0.0));
+
+        // Emission isn't weighted.
+        assert!(mix
+            .emit(&ray, &hit)
+            .approx_eq(first.emit(&ray, &hit) + second.emit(&ray, &hit), 0.0));
+    }
+
+    #[test]
+    fn schlick_rises_to_one_at_the_critical_angle() {
+        let (eta_from, eta_to) = (1.5, 1.0);
+        let critical_cos = (1.0 - (eta_to / eta_from as Real).powi(2)).sqrt();
+
+        // Head on, it's the reflectance at normal incidence.
+        assert!((schlick(1.0, eta_from, eta_to) - 0.04).abs() < 1e-6);
+
+        let steps = 100;
+        let mut previous = 0.0;
+        for step in 0..steps {
+            let cos_theta = 1.0 - (1.0 - critical_cos) * step as Real / steps as Real;
+            let reflectance = schlick(cos_theta, eta_from, eta_to);
+            assert!(reflectance >= previous, "{} at {}", reflectance, cos_theta);
+            assert!(reflectance < 1.0, "{} at {}", reflectance, cos_theta);
+            previous = reflectance;
+        }
+        assert!(previous > 0.5, "{}", previous);
+
+        for cos_theta in [critical_cos - 1e-3, 0.5, 0.1, 0.0] {
+            assert_eq!(schlick(cos_theta, eta_from, eta_to), 1.0);
+        }
+    }
+
+    #[test]
+    fn metal_fuzziness_is_clamped() {
+        let albedo = Color {
+            r: 0.8,
+            g: 0.8,
+            b: 0.8,
+        };
+        assert_eq!(Metal::new(albedo, 5.0).fuzziness(), 1.0);
+        assert_eq!(Metal::new(albedo, -0.5).fuzziness(), 0.0);
+        assert_eq!(Metal::new(albedo, 0.3).fuzziness(), 0.3);
+    }
+
+    #[test]
+    fn microfacet_never_adds_energy() {
+        let white = Color {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+        };
+        let copper = Color {
+            r: 0.95,
+            g: 0.64,
+            b: 0.54,
+        };
+        let mut rng = StdRng::seed_from_u64(0);
+        for albedo in [white, copper] {
+            for (roughness_u, roughness_v) in [(0.0, 0.0), (0.3, 0.3), (1.0, 1.0), (0.1, 0.8)] {
+                let material = Microfacet::anisotropic(albedo, roughness_u, roughness_v);
+                for z in [-1.0, -0.5, -0.05] {
+                    let ray = Ray::new(Vec3::ZERO, Vec3 { x: 0.4, y: -0.2, z });
+                    let hit = hit_at_origin(Material::new(Normals()));
+                    for _ in 0..2000 {
+                        if let Some(scatter) = material.scatter(&mut rng, &ray, &hit) {
+                            for channel in Channel::all() {
+                                let attenuation = scatter.attenuation[channel];
+                                assert!(
+                                    (0.0..=1.0).contains(&attenuation),
+                                    "{} with roughness ({}, {})",
+                                    attenuation,
+                                    roughness_u,
+                                    roughness_v
+                                );
+                            }
+                            assert!(scatter.outgoing.direction.dot(hit.normal) > 0.0);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn anisotropic_microfacet_spreads_along_the_rougher_tangent() {
+        let material = Microfacet::anisotropic(
+            Color {
+                r: 0.9,
+                g: 0.9,
+                b: 0.9,
+            },
+            0.8,
+            0.05,
+        );
+        let mut hit = hit_at_origin(Material::new(Normals()));
+        hit.tangents = Some(Tangents {
+            tangent: Vec3 {
+                x: 1.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            bitangent: Vec3 {
+                x: 0.0,
+                y: 1.0,
+                z: 0.0,
+            },
+        });
+        let ray = Ray::new(
+            hit.normal,
+            Vec3 {
+                x: 0.0,
+                y: 0.0,
+                z: -1.0,
+            },
+        );
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let (mut spread_u, mut spread_v) = (0.0, 0.0);
+        for _ in 0..2000 {
+            if let Some(scatter) = material.scatter(&mut rng, &ray, &hit) {
+                let direction = scatter.outgoing.direction.unit();
+                spread_u += direction.x.abs();
+                spread_v += direction.y.abs();
+            }
+        }
+        assert!(spread_u > 5.0 * spread_v, "{} vs {}", spread_u, spread_v);
+    }
+
+    #[test]
+    fn smooth_oren_nayar_is_lambertian() {
+        let oren_nayar = OrenNayar::new(gray(), 0.0);
+        let lambertian = Lambertian { albedo: gray() };
+        let (ray, hit) = (incoming(), hit_at_origin(Material::new(Normals())));
+
+        let directions = [
+            Vec3 {
+                x: 0.0,
+                y: 0.0,
+                z: 1.0,
+            },
+            Vec3 {
+                x: 0.5,
+                y: -0.3,
+                z: 0.4,
+            },
+            Vec3 {
+                x: -2.0,
+                y: 1.0,
+                z: 0.1,
+            },
+        ];
+        for direction in directions {
+            let rough = oren_nayar.reflectance(&ray, &hit, direction).unwrap();
+            let smooth = lambertian.reflectance(&ray, &hit, direction).unwrap();
+            assert!(rough.value.approx_eq(smooth.value, 1e-6));
+            assert!((rough.pdf - smooth.pdf).abs() < 1e-6);
+        }
+
+        let (mut rough_rng, mut smooth_rng) = (StdRng::seed_from_u64(2), StdRng::seed_from_u64(2));
+        for _ in 0..100 {
+            let rough = oren_nayar.scatter(&mut rough_rng, &ray, &hit).unwrap();
+            let smooth = lambertian.scatter(&mut smooth_rng, &ray, &hit).unwrap();
+            assert!(rough.attenuation.approx_eq(smooth.attenuation, 1e-6));
+            assert_eq!(rough.outgoing.direction, smooth.outgoing.direction);
+        }
+    }
+
+    #[test]
+    fn dielectric_without_dispersion_is_achromatic() {
+        let glass = Dielectric {
+            refractive_index: 1.5,
+            dispersion: 0.0,
+        };
+        let (ray, hit) = (incoming(), hit_at_origin(Material::new(Normals())));
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..1000 {
+            let scatter = glass.scatter(&mut rng, &ray, &hit).unwrap();
+            let Color { r, g, b } = scatter.attenuation;
+            assert!(r == g && g == b, "{:?}", scatter.attenuation);
+        }
+
+        // With dispersion, each scatter carries a single channel.
+        let prism = Dielectric {
+            refractive_index: 1.5,
+            dispersion: 0.02,
+        };
+        let scatter = prism.scatter(&mut rng, &ray, &hit).unwrap();
+        let nonzero = Channel::all()
+            .into_iter()
+            .filter(|&channel| scatter.attenuation[channel] != 0.0)
+            .count();
+        assert_eq!(nonzero, 1);
+    }
+
+    #[test]
+    fn diffuse_light_emits_its_texture() {
+        let light = DiffuseLight {
+            emit: Texture::new(texture::UV()),
+        };
+        let ray = incoming();
+        let mut hit = hit_at_origin(Material::new(Normals()));
+        for (u, v) in [(0.0, 0.0), (0.25, 0.75), (1.0, 0.5)] {
+            hit.texture_coord = texture::Coord { u, v };
+            let expected = Color { r: u, g: 0.0, b: v };
+            assert!(light.emit(&ray, &hit).approx_eq(expected, 0.0));
+        }
+        assert!(light
+            .scatter(&mut StdRng::seed_from_u64(0), &ray, &hit)
+            .is_none());
+    }
+
+    #[test]
+    fn lambertian_emissive_both_scatters_and_emits() {
+        let emission = Color {
+            r: 4.0,
+            g: 2.0,
+            b: 1.0,
+        };
+        let material = LambertianEmissive {
+            albedo: gray(),
+            emission,
+        };
+        let (ray, hit) = (incoming(), hit_at_origin(Material::new(Normals())));
+
+        let scatter = material
+            .scatter(&mut StdRng::seed_from_u64(0), &ray, &hit)
+            .unwrap();
+        assert!(scatter
+            .attenuation
+            .approx_eq(gray().color(&hit.texture_coord), 0.0));
+        assert!(scatter.outgoing.direction.dot(hit.normal) > 0.0);
+        assert!(material.emit(&ray, &hit).approx_eq(emission, 0.0));
+    }
+
+    #[test]
+    fn flat_normal_map_leaves_the_normal_unchanged() {
+        let flat = NormalMap {
+            material: Material::new(Lambertian { albedo: gray() }),
+            normals: Texture::new(texture::Constant {
+                color: Color {
+                    r: 0.5,
+                    g: 0.5,
+                    b: 1.0,
+                },
+            }),
+        };
+        let mut hit = hit_at_origin(Material::new(Normals()));
+        // Tangents needn't be unit vectors or perpendicular to the normal.
+        hit.tangents = Some(Tangents {
+            tangent: Vec3 {
+                x: 2.0,
+                y: 0.0,
+                z: 0.5,
+            },
+            bitangent: Vec3 {
+                x: 0.3,
+                y: 1.5,
+                z: -0.2,
+            },
+        });
+        assert_vec3_eq!(flat.normal(&hit).unwrap(), hit.normal);
+
+        let mut back = hit.clone();
+        back.face = Face::Back;
+        back.normal = -hit.normal;
+        assert_vec3_eq!(flat.normal(&back).unwrap(), back.normal);
+
+        let ray = incoming();
+        let (mut mapped_rng, mut plain_rng) = (StdRng::seed_from_u64(3), StdRng::seed_from_u64(3));
+        for _ in 0..10 {
+            let mapped = flat.scatter(&mut mapped_rng, &ray, &hit).unwrap();
+            let plain = flat.material.scatter(&mut plain_rng, &ray, &hit).unwrap();
+            assert_vec3_eq!(mapped.outgoing.direction, plain.outgoing.direction, 1e-5);
+        }
+    }
+
+    #[test]
+    fn transparent_texel_continues_the_ray() {
+        let masked = Masked {
+            material: Material::new(Lambertian { albedo: gray() }),
+            opacity: Texture::new(texture::Constant {
+                color: Color::BLACK,
+            }),
+            threshold: 0.5,
+        };
+        let (ray, hit) = (incoming(), hit_at_origin(Material::new(Normals())));
+        assert!(masked.is_cut_out(&hit));
+
+        let scatter = masked
+            .scatter(&mut StdRng::seed_from_u64(0), &ray, &hit)
+            .unwrap();
+        let continuation = scatter.outgoing;
+        // It starts on the original ray and carries on in the same direction.
+        assert_vec3_eq!(continuation.origin, ray.at(hit.t));
+        assert_vec3_eq!(continuation.direction.cross(ray.direction), Vec3::ZERO);
+        assert!(continuation.direction.dot(ray.direction) > 0.0);
+        assert!(scatter.attenuation.approx_eq(
+            Color {
+                r: 1.0,
+                g: 1.0,
+                b: 1.0
+            },
+            0.0
+        ));
+        assert!(masked.emit(&ray, &hit).approx_eq(Color::BLACK, 0.0));
+    }
+
+    #[test]
+    fn spotlight_falloff_regions() {
+        let down = Vec3 {
+            x: 0.0,
+            y: -1.0,
+            z: 0.0,
+        };
+        let spotlight = SpotLight {
+            brightness: 10.0,
+            color: Color {
+                r: 1.0,
+                g: 1.0,
+                b: 1.0,
+            },
+            direction: 2.0 * down,
+            inner_angle: 20.0,
+            outer_angle: 40.0,
+        };
+        let z = Vec3 {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        };
+        let at_angle = |degrees: Real| down.rotate_around(z, degrees.to_radians());
+
+        assert_eq!(spotlight.falloff(down), 1.0);
+        assert_eq!(spotlight.falloff(at_angle(10.0)), 1.0);
+
+        // Halfway through the falloff band in cosine.
+        let cos_halfway =
+            0.5 * (20.0 as Real).to_radians().cos() + 0.5 * (40.0 as Real).to_radians().cos();
+        let halfway = at_angle(cos_halfway.acos().to_degrees());
+        assert!((spotlight.falloff(halfway) - 0.5).abs() < 1e-4);
+        let falloff = spotlight.falloff(at_angle(35.0));
+        assert!(0.0 < falloff && falloff < 0.5, "{}", falloff);
+
+        assert_eq!(spotlight.falloff(at_angle(50.0)), 0.0);
+        assert_eq!(spotlight.falloff(-down), 0.0);
+        assert_eq!(spotlight.falloff(Vec3::ZERO), 0.0);
+
+        // `emit` looks back along the ray that hit the light.
+        let hit = hit_at_origin(Material::new(Normals()));
+        let emitted = spotlight.emit(&Ray::new(3.0 * down, -down), &hit);
+        assert!(emitted.approx_eq(10.0 * spotlight.color, 1e-4));
+        let emitted = spotlight.emit(&Ray::new(3.0 * down, -at_angle(50.0)), &hit);
+        assert!(emitted.approx_eq(Color::BLACK, 0.0));
+    }
+
+    #[test]
+    fn diffuse_matches_a_constant_textured_lambertian() {
+        let albedo = Color {
+            r: 0.7,
+            g: 0.2,
+            b: 0.4,
+        };
+        let diffuse = Diffuse { albedo };
+        let lambertian = Lambertian {
+            albedo: Texture::new(texture::Constant { color: albedo }),
+        };
+        let (ray, hit) = (incoming(), hit_at_origin(Material::new(Normals())));
+
+        let (mut diffuse_rng, mut lambertian_rng) =
+            (StdRng::seed_from_u64(4), StdRng::seed_from_u64(4));
+        for _ in 0..100 {
+            let from_diffuse = diffuse.scatter(&mut diffuse_rng, &ray, &hit).unwrap();
+            let from_lambertian = lambertian.scatter(&mut lambertian_rng, &ray, &hit).unwrap();
+            assert!(from_diffuse
+                .attenuation
+                .approx_eq(from_lambertian.attenuation, 0.0));
+            assert_eq!(
+                from_diffuse.outgoing.direction,
+                from_lambertian.outgoing.direction
+            );
+        }
+
+        let direction = Vec3 {
+            x: -0.2,
+            y: 0.6,
+            z: 0.3,
+        };
+        let from_diffuse = diffuse.reflectance(&ray, &hit, direction).unwrap();
+        let from_lambertian = lambertian.reflectance(&ray, &hit, direction).unwrap();
+        assert!(from_diffuse.value.approx_eq(from_lambertian.value, 0.0));
+        assert_eq!(from_diffuse.pdf, from_lambertian.pdf);
+        assert!(diffuse
+            .albedo_hint(&hit)
+            .approx_eq(lambertian.albedo_hint(&hit), 0.0));
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{
+    bounds::Bounds3,
+    hit::{Face, Hit},
+    interval::Interval,
+    material::Material,
+    object::{IsObject, Object},
+    ray::Ray,
+    real::Real,
+    texture,
+    vec3::Vec3,
+};
+use rand::Rng;
+
+/**
+A volume of constant density, such as fog or smoke.
+
+A ray passing through the medium has a chance of scattering at every point along the way, so rays
+that travel further inside the medium are more likely to scatter.
+
+Where a ray scatters is random, so a medium isn't an [`IsObject`]. Add it to a
+[`Scene`](crate::scene::Scene) with [`Scene::with_media`](crate::scene::Scene::with_media) instead,
+or describe it in a scene file as a `Medium` (see
+[`ObjectDescription::Medium`](crate::scene::ObjectDescription::Medium)).
+*/
+pub struct ConstantMedium {
+    /// The shape of the volume. Must be convex.
+    pub boundary: Object,
+    pub density: Real,
+    /// Decides how rays scatter inside the volume. Usually `material::Isotropic`.
+    pub phase_function: Material,
+}
+
+impl ConstantMedium {
+    /// The bounds of the volume: those of its boundary.
+    pub fn bounds(&self) -> Bounds3 {
+        self.boundary.bounds()
+    }
+
+    /// Where `ray` scatters inside the medium within `t_range`, if it does, drawn using `rng`.
+    pub fn sample_hit<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        ray: &Ray,
+        t_range: Interval,
+    ) -> Option<Hit> {
+        // Where the ray's line enters and exits the volume, ignoring `t_range`.
+        let entry = self.boundary.hit(ray, Interval::ALL)?;
+        let exit = self
+            .boundary
+            .hit(ray, Interval::new(entry.t + 0.0001, Real::INFINITY))?;
+
+        let t_entry = entry.t.max(t_range.start).max(0.0);
+        let t_exit = exit.t.min(t_range.end);
+        if t_entry >= t_exit {
+            return None;
+        }
+
+        let ray_length = ray.direction.norm();
+        let distance_inside = (t_exit - t_entry) * ray_length;
+        let hit_distance = -(1.0 / self.density) * (1.0 - rng.gen::<Real>()).ln();
+        if hit_distance > distance_inside {
+            return None;
+        }
+
+        let t = t_entry + hit_distance / ray_length;
+        Some(Hit {
+            point: ray.at(t),
+            // Scattering inside a volume doesn't depend on the normal, so any unit vector will do.
+            normal: Vec3 {
+                x: 1.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            t,
+            face: Face::Front,
+            material: self.phase_function.clone(),
+            texture_coord: texture::Coord { u: 0.0, v: 0.0 },
+            tangents: None,
+            object_id: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::Color,
+        material::{Isotropic, Normals},
+        sphere::Sphere,
+        texture::{Texture, UvTransform},
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn rays_scatter_with_beer_lambert_probability() {
+        let medium = ConstantMedium {
+            boundary: Object::new(Sphere {
+                center: Vec3::ZERO,
+                radius: 1.0,
+                material: Material::new(Normals()),
+                uv_transform: UvTransform::default(),
+            }),
+            density: 0.5,
+            phase_function: Material::new(Isotropic {
+                albedo: Texture::new(texture::Constant {
+                    color: Color::BLACK,
+                }),
+            }),
+        };
+        // Through the center, so the ray travels a distance of 2 inside the medium.
+        let ray = Ray::new(
+            Vec3 {
+                x: 0.0,
+                y: 0.0,
+                z: 5.0,
+            },
+            Vec3 {
+                x: 0.0,
+                y: 0.0,
+                z: -1.0,
+            },
+        );
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let samples = 10_000;
+        let scattered = (0..samples)
+            .filter_map(|_| medium.sample_hit(&mut rng, &ray, Interval::new(0.0, Real::INFINITY)))
+            .inspect(|hit| assert!((4.0..=6.0).contains(&hit.t), "{}", hit.t))
+            .count();
+        let fraction = scattered as Real / samples as Real;
+        let expected = 1.0 - (-0.5 * 2.0 as Real).exp();
+        assert!((fraction - expected).abs() < 0.02, "{}", fraction);
+    }
+}
@@ -0,0 +1,306 @@
+use crate::{
+    bounds::Bounds3,
+    hit::{Face, Hit, Tangents},
+    interval::Interval,
+    material::Material,
+    object::{IsObject, Object},
+    ray::Ray,
+    texture,
+    vec3::Vec3,
+};
+use std::sync::Arc;
+
+/// One corner of a triangle, as indices into a `Mesh`'s vertex data.
+#[derive(Debug, Clone, Copy)]
+pub struct Vertex {
+    pub position: usize,
+    pub normal: Option<usize>,
+    pub texture_coord: Option<usize>,
+}
+
+/// Which normals a `Mesh` is shaded with.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum Shading {
+    /// Each triangle's own normal, so the facets show.
+    Flat,
+    /**
+    The vertex normals, interpolated across each triangle, so the mesh looks smoothly curved.
+    Triangles without a normal for every vertex are shaded flat.
+    */
+    #[default]
+    Smooth,
+}
+
+/// A triangle mesh.
+pub struct Mesh {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub texture_coords: Vec<texture::Coord>,
+    pub faces: Vec<[Vertex; 3]>,
+    pub material: Material,
+    pub shading: Shading,
+}
+
+impl Mesh {
+    /// Split the mesh into one object per triangle, so that the triangles can be put in a `Bvh`.
+    pub fn into_objects(self) -> Vec<Object> {
+        let mesh = Arc::new(self);
+        (0..mesh.faces.len())
+            .map(|face| {
+                Object::new(Triangle {
+                    mesh: mesh.clone(),
+                    face,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A single face of a `Mesh`.
+pub struct Triangle {
+    mesh: Arc<Mesh>,
+    face: usize,
+}
+
+impl Triangle {
+    fn vertices(&self) -> &[Vertex; 3] {
+        &self.mesh.faces[self.face]
+    }
+
+    fn positions(&self) -> [Vec3; 3] {
+        self.vertices()
+            .map(|vertex| self.mesh.positions[vertex.position])
+    }
+}
+
+impl IsObject for Triangle {
+    fn hit(&self, ray: &Ray, t_range: Interval) -> Option<Hit> {
+        // Möller–Trumbore intersection.
+        let [p0, p1, p2] = self.positions();
+        let edge1 = p1 - p0;
+        let edge2 = p2 - p0;
+
+        let p = ray.direction.cross(edge2);
+        let determinant = edge1.dot(p);
+        // The ray is parallel to the triangle.
+        if determinant.abs() < 1e-12 {
+            return None;
+        }
+        let inverse_determinant = 1.0 / determinant;
+
+        let s = ray.origin - p0;
+        let u = s.dot(p) * inverse_determinant;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = s.cross(edge1);
+        let v = ray.direction.dot(q) * inverse_determinant;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = edge2.dot(q) * inverse_determinant;
+        if !t_range.contains(t) {
+            return None;
+        }
+
+        // Barycentric weights of the three vertices.
+        let weights = [1.0 - u - v, u, v];
+        let vertices = self.vertices();
+
+        let geometric_normal = edge1.cross(edge2).unit();
+        let outward_normal = match vertices.map(|vertex| vertex.normal) {
+            [Some(n0), Some(n1), Some(n2)] if matches!(self.mesh.shading, Shading::Smooth) => {
+                (weights[0] * self.mesh.normals[n0]
+                    + weights[1] * self.mesh.normals[n1]
+                    + weights[2] * self.mesh.normals[n2])
+                    .unit()
+            }
+            _ => geometric_normal,
+        };
+
+        // The geometric normal decides which side was hit; the shading normal may differ slightly.
+        let (normal, face) = if ray.direction.dot(geometric_normal) < 0.0 {
+            (outward_normal, Face::Front)
+        } else {
+            (-outward_normal, Face::Back)
+        };
+
+        let (texture_coord, tangents) = match vertices.map(|vertex| vertex.texture_coord) {
+            [Some(c0), Some(c1), Some(c2)] => {
+                let coords = [c0, c1, c2].map(|c| &self.mesh.texture_coords[c]);
+                let texture_coord = texture::Coord {
+                    u: weights[0] * coords[0].u
+                        + weights[1] * coords[1].u
+                        + weights[2] * coords[2].u,
+                    v: weights[0] * coords[0].v
+                        + weights[1] * coords[1].v
+                        + weights[2] * coords[2].v,
+                };
+
+                // Solve for the directions along the triangle in which `u` and `v` increase.
+                let (du1, dv1) = (coords[1].u - coords[0].u, coords[1].v - coords[0].v);
+                let (du2, dv2) = (coords[2].u - coords[0].u, coords[2].v - coords[0].v);
+                let uv_determinant = du1 * dv2 - dv1 * du2;
+                let tangents = if uv_determinant.abs() < 1e-12 {
+                    None
+                } else {
+                    Some(Tangents {
+                        tangent: (dv2 * edge1 - dv1 * edge2) / uv_determinant,
+                        bitangent: (du1 * edge2 - du2 * edge1) / uv_determinant,
+                    })
+                };
+
+                (texture_coord, tangents)
+            }
+            // The barycentric coordinates increase along the edges.
+            _ => (
+                texture::Coord { u, v },
+                Some(Tangents {
+                    tangent: edge1,
+                    bitangent: edge2,
+                }),
+            ),
+        };
+
+        Some(Hit {
+            point: ray.at(t),
+            normal,
+            t,
+            face,
+            material: self.mesh.material.clone(),
+            texture_coord,
+            tangents,
+            object_id: None,
+        })
+    }
+
+    fn bounds(&self) -> Bounds3 {
+        let [p0, p1, p2] = self.positions();
+        // Padded because axis-aligned triangles have no thickness.
+        Bounds3::new(p0, p1)
+            .union(&Bounds3::point(p2))
+            .padded(0.0001)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{material::Normals, real::Real, vec3::assert_vec3_eq};
+
+    /**
+    Two triangles folded along the Y axis like a half-open book, with vertex normals that average
+    the faces' along the fold.
+    */
+    fn strip(shading: Shading) -> Vec<Object> {
+        let (left, right) = (
+            Vec3 {
+                x: -1.0,
+                y: 0.0,
+                z: 1.0,
+            }
+            .unit(),
+            Vec3 {
+                x: 1.0,
+                y: 0.0,
+                z: 1.0,
+            }
+            .unit(),
+        );
+        let fold = Vec3 {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        };
+        let vertex = |position, normal| Vertex {
+            position,
+            normal: Some(normal),
+            texture_coord: None,
+        };
+        Mesh {
+            positions: vec![
+                Vec3 {
+                    x: -1.0,
+                    y: 0.0,
+                    z: -1.0,
+                },
+                Vec3 {
+                    x: 0.0,
+                    y: 0.0,
+                    z: 0.0,
+                },
+                Vec3 {
+                    x: 0.0,
+                    y: 1.0,
+                    z: 0.0,
+                },
+                Vec3 {
+                    x: 1.0,
+                    y: 0.0,
+                    z: -1.0,
+                },
+            ],
+            normals: vec![left, fold, right],
+            texture_coords: Vec::new(),
+            faces: vec![
+                [vertex(0, 0), vertex(1, 1), vertex(2, 1)],
+                [vertex(1, 1), vertex(3, 2), vertex(2, 1)],
+            ],
+            material: Material::new(Normals()),
+            shading,
+        }
+        .into_objects()
+    }
+
+    /// The normal where a ray straight down the Z axis, at `x`, hits the strip.
+    fn normal_at(triangles: &[Object], x: Real) -> Vec3 {
+        let ray = Ray::new(
+            Vec3 { x, y: 0.1, z: 5.0 },
+            Vec3 {
+                x: 0.0,
+                y: 0.0,
+                z: -1.0,
+            },
+        );
+        let mut hits = triangles
+            .iter()
+            .filter_map(|triangle| triangle.hit(&ray, Interval::new(0.0, Real::INFINITY)));
+        let hit = hits.next().unwrap();
+        assert!(hits.next().is_none());
+        hit.normal
+    }
+
+    #[test]
+    fn flat_and_smooth_shading() {
+        let flat = strip(Shading::Flat);
+        let left = Vec3 {
+            x: -1.0,
+            y: 0.0,
+            z: 1.0,
+        }
+        .unit();
+        // Each facet has one normal all over.
+        for x in [-0.8, -0.5, -0.05] {
+            assert_vec3_eq!(normal_at(&flat, x), left, 1e-5);
+        }
+
+        let smooth = strip(Shading::Smooth);
+        // Near the fold, the normal is close to the fold's; further out, close to the face's.
+        let near_fold = normal_at(&smooth, -0.05);
+        let far_out = normal_at(&smooth, -0.8);
+        assert!(near_fold.z > far_out.z, "{:?} {:?}", near_fold, far_out);
+        assert!((near_fold.norm() - 1.0).abs() < 1e-5);
+        // It's symmetric about the fold.
+        let mirrored = normal_at(&smooth, 0.05);
+        assert_vec3_eq!(
+            mirrored,
+            Vec3 {
+                x: -near_fold.x,
+                ..near_fold
+            },
+            1e-5
+        );
+    }
+}
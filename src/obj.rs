@@ -0,0 +1,203 @@
+/*!
+Loading triangle meshes from Wavefront OBJ files.
+
+Only geometry is supported: `v`, `vn`, `vt` and `f` statements. Everything else (groups, smoothing,
+materials) is ignored. Polygonal faces are split into triangles with a fan around their first
+vertex.
+*/
+
+use crate::{
+    material::Material,
+    mesh::{Mesh, Shading, Vertex},
+    object::Object,
+    real::Real,
+    texture,
+    vec3::Vec3,
+};
+use std::{fs, io, str::FromStr};
+
+/// Load the triangles of an OBJ file. Every triangle gets the same `material`.
+pub fn load_obj(path: &str, material: Material) -> io::Result<Vec<Object>> {
+    let source = fs::read_to_string(path)?;
+    let mesh = parse_obj(&source, material)?;
+    Ok(mesh.into_objects())
+}
+
+/// Parse the contents of an OBJ file.
+pub fn parse_obj(source: &str, material: Material) -> io::Result<Mesh> {
+    let mut mesh = Mesh {
+        positions: Vec::new(),
+        normals: Vec::new(),
+        texture_coords: Vec::new(),
+        faces: Vec::new(),
+        material,
+        shading: Shading::Smooth,
+    };
+
+    for (line_index, line) in source.lines().enumerate() {
+        let error = |message: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {}", line_index + 1, message),
+            )
+        };
+
+        // Comments run to the end of the line.
+        let line = line.split('#').next().unwrap_or("");
+        let mut words = line.split_whitespace();
+        let keyword = match words.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let arguments: Vec<&str> = words.collect();
+
+        match keyword {
+            "v" => {
+                let [x, y, z] = parse_floats::<3>(&arguments).map_err(error)?;
+                mesh.positions.push(Vec3 { x, y, z });
+            }
+            "vn" => {
+                let [x, y, z] = parse_floats::<3>(&arguments).map_err(error)?;
+                mesh.normals.push(Vec3 { x, y, z }.unit());
+            }
+            "vt" => {
+                // The optional third (`w`) component is ignored.
+                let [u, v] =
+                    parse_floats::<2>(&arguments[..arguments.len().min(2)]).map_err(error)?;
+                mesh.texture_coords.push(texture::Coord { u, v });
+            }
+            "f" => {
+                if arguments.len() < 3 {
+                    return Err(error(format!(
+                        "a face needs at least 3 vertices, got {}",
+                        arguments.len()
+                    )));
+                }
+
+                let vertices = arguments
+                    .iter()
+                    .map(|argument| parse_vertex(argument, &mesh))
+                    .collect::<Result<Vec<Vertex>, String>>()
+                    .map_err(error)?;
+
+                for i in 1..vertices.len() - 1 {
+                    mesh.faces.push([vertices[0], vertices[i], vertices[i + 1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(mesh)
+}
+
+fn parse_floats<const N: usize>(arguments: &[&str]) -> Result<[Real; N], String> {
+    if arguments.len() != N {
+        return Err(format!("expected {} numbers, got {}", N, arguments.len()));
+    }
+
+    let mut values = [0.0; N];
+    for (value, argument) in values.iter_mut().zip(arguments) {
+        *value = Real::from_str(argument).map_err(|err| format!("{:?}: {}", argument, err))?;
+    }
+    Ok(values)
+}
+
+/// Parse a face vertex of the form `v`, `v/vt`, `v//vn` or `v/vt/vn`.
+fn parse_vertex(argument: &str, mesh: &Mesh) -> Result<Vertex, String> {
+    /*
+    OBJ indices start at 1. Negative indices count backwards from the most recently defined
+    element.
+    */
+    fn parse_index(value: &str, count: usize) -> Result<usize, String> {
+        let index = isize::from_str(value).map_err(|err| format!("{:?}: {}", value, err))?;
+        let resolved = if index > 0 {
+            index - 1
+        } else {
+            count as isize + index
+        };
+
+        if index == 0 || resolved < 0 || resolved >= count as isize {
+            Err(format!("index {} is out of range", index))
+        } else {
+            Ok(resolved as usize)
+        }
+    }
+
+    let mut parts = argument.split('/');
+    let position = parse_index(parts.next().unwrap_or(""), mesh.positions.len())?;
+    let texture_coord = match parts.next() {
+        None | Some("") => None,
+        Some(value) => Some(parse_index(value, mesh.texture_coords.len())?),
+    };
+    let normal = match parts.next() {
+        None | Some("") => None,
+        Some(value) => Some(parse_index(value, mesh.normals.len())?),
+    };
+
+    Ok(Vertex {
+        position,
+        normal,
+        texture_coord,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{interval::Interval, material::Normals, object::IsObject, ray::Ray};
+
+    /// A unit square in the XY plane, facing +Z, as two triangles sharing an edge.
+    const SQUARE: &str = "
+# Corners
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+f 1/1/1 2/2/1 3/3/1
+f 1/1/1 3/3/1 -1/-1/1
+";
+
+    #[test]
+    fn two_triangle_square() {
+        let mesh = parse_obj(SQUARE, Material::new(Normals())).unwrap();
+        assert_eq!(mesh.positions.len(), 4);
+        assert_eq!(mesh.texture_coords.len(), 4);
+        assert_eq!(mesh.normals.len(), 1);
+        assert_eq!(mesh.faces.len(), 2);
+        // Negative indices count back from the end.
+        assert_eq!(mesh.faces[1][2].position, 3);
+
+        let triangles = mesh.into_objects();
+        let t_range = Interval::new(0.0, Real::INFINITY);
+        let down = Vec3 {
+            x: 0.0,
+            y: 0.0,
+            z: -1.0,
+        };
+        for (x, y) in [(0.75, 0.25), (0.25, 0.75)] {
+            let ray = Ray::new(Vec3 { x, y, z: 1.0 }, down);
+            let hits: Vec<_> = triangles
+                .iter()
+                .filter_map(|triangle| triangle.hit(&ray, t_range))
+                .collect();
+            assert_eq!(hits.len(), 1, "({}, {})", x, y);
+            assert!((hits[0].texture_coord.u - x).abs() < 1e-5);
+            assert!((hits[0].texture_coord.v - y).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn faces_with_too_few_vertices_are_rejected() {
+        let error = parse_obj("v 0 0 0\nv 1 0 0\nf 1 2\n", Material::new(Normals()))
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().starts_with("line 3:"), "{}", error);
+    }
+}
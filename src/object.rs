@@ -1,9 +1,22 @@
-use crate::{bounds::Bounds3, hit::Hit, ray::Ray, vec3::Vec3};
+use crate::{
+    bounds::Bounds3, hit::Hit, interval::Interval, material::Material, ray::Ray, real::Real,
+    vec3::Vec3,
+};
 use std::sync::Arc;
 
 pub trait IsObject: Send + Sync {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit>;
+    fn hit(&self, ray: &Ray, t_range: Interval) -> Option<Hit>;
     fn bounds(&self) -> Bounds3;
+
+    /**
+    How far `point` is from the object, or `0` if it's inside.
+
+    Objects that don't know their exact distance give the distance to their bounding box, which is
+    never further than the object itself.
+    */
+    fn distance(&self, point: Vec3) -> Real {
+        self.bounds().distance(point)
+    }
 }
 
 #[derive(Clone)]
@@ -13,25 +26,92 @@ impl Object {
     pub fn new<T: IsObject + 'static>(item: T) -> Self {
         Object(Arc::new(item))
     }
+
+    /// The same object, with `id` recorded in its hits to tell which object was hit.
+    #[must_use]
+    pub fn with_id(&self, id: usize) -> Self {
+        Object::new(Tagged {
+            object: self.clone(),
+            id,
+        })
+    }
+
+    /// The same geometry, drawn with a different material.
+    #[must_use]
+    pub fn with_material(&self, material: Material) -> Self {
+        Object::new(MaterialOverride {
+            object: self.clone(),
+            material,
+        })
+    }
+}
+
+/// Replaces the material of every hit on `object`.
+pub struct MaterialOverride {
+    pub object: Object,
+    pub material: Material,
+}
+
+impl IsObject for MaterialOverride {
+    fn hit(&self, ray: &Ray, t_range: Interval) -> Option<Hit> {
+        let mut hit = self.object.hit(ray, t_range)?;
+        hit.material = self.material.clone();
+        Some(hit)
+    }
+
+    fn bounds(&self) -> Bounds3 {
+        self.object.bounds()
+    }
+
+    fn distance(&self, point: Vec3) -> Real {
+        self.object.distance(point)
+    }
+}
+
+/// Records `id` in every hit on `object`, unless an object inside it already has.
+pub struct Tagged {
+    pub object: Object,
+    pub id: usize,
+}
+
+impl IsObject for Tagged {
+    fn hit(&self, ray: &Ray, t_range: Interval) -> Option<Hit> {
+        let mut hit = self.object.hit(ray, t_range)?;
+        hit.object_id.get_or_insert(self.id);
+        Some(hit)
+    }
+
+    fn bounds(&self) -> Bounds3 {
+        self.object.bounds()
+    }
+
+    fn distance(&self, point: Vec3) -> Real {
+        self.object.distance(point)
+    }
 }
 
 impl IsObject for Object {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit> {
-        self.0.hit(ray, t_min, t_max)
+    fn hit(&self, ray: &Ray, t_range: Interval) -> Option<Hit> {
+        self.0.hit(ray, t_range)
     }
 
     fn bounds(&self) -> Bounds3 {
         self.0.bounds()
     }
+
+    fn distance(&self, point: Vec3) -> Real {
+        self.0.distance(point)
+    }
 }
 
 impl<T: IsObject> IsObject for &[T] {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+    fn hit(&self, ray: &Ray, t_range: Interval) -> Option<Hit> {
         let mut result = None;
-        let mut closest_so_far = t_max;
+        let mut t_range = t_range;
         for object in self.iter() {
-            if let Some(hit) = object.hit(ray, t_min, closest_so_far) {
-                closest_so_far = hit.t;
+            // Only hits closer than the closest so far count.
+            if let Some(hit) = object.hit(ray, t_range) {
+                t_range.end = hit.t;
                 result = Some(hit);
             }
         }
@@ -39,21 +119,73 @@ impl<T: IsObject> IsObject for &[T] {
     }
 
     fn bounds(&self) -> Bounds3 {
-        if self.is_empty() {
-            Bounds3::point(Vec3::ZERO)
-        } else {
-            let init = self[0].bounds();
-            self.iter().fold(init, |acc, el| acc.union(&el.bounds()))
-        }
+        self.iter()
+            .fold(Bounds3::EMPTY, |acc, el| acc.union(&el.bounds()))
+    }
+
+    fn distance(&self, point: Vec3) -> Real {
+        self.iter()
+            .map(|object| object.distance(point))
+            .fold(Real::INFINITY, Real::min)
     }
 }
 
 impl<T: IsObject> IsObject for Vec<T> {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit> {
-        self.as_slice().hit(ray, t_min, t_max)
+    fn hit(&self, ray: &Ray, t_range: Interval) -> Option<Hit> {
+        self.as_slice().hit(ray, t_range)
     }
 
     fn bounds(&self) -> Bounds3 {
         self.as_slice().bounds()
     }
+
+    fn distance(&self, point: Vec3) -> Real {
+        self.as_slice().distance(point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bvh::Bvh, material::Normals, sphere::Sphere, texture::UvTransform};
+
+    fn sphere_at(x: Real) -> Object {
+        Object::new(Sphere {
+            center: Vec3 { x, y: 0.0, z: 0.0 },
+            radius: 0.5,
+            material: Material::new(Normals()),
+            uv_transform: UvTransform::default(),
+        })
+    }
+
+    fn hit_id(bvh: &Bvh, x: Real) -> Option<usize> {
+        let ray = Ray::new(
+            Vec3 { x, y: 0.0, z: 5.0 },
+            Vec3 {
+                x: 0.0,
+                y: 0.0,
+                z: -1.0,
+            },
+        );
+        bvh.hit(&ray, Interval::new(0.0, Real::INFINITY))
+            .unwrap()
+            .object_id
+    }
+
+    #[test]
+    fn hits_record_the_id_of_the_tagged_object() {
+        let mut objects: Vec<Object> = (0..8)
+            .map(|i| sphere_at(2.0 * i as Real).with_id(10 + i))
+            .collect();
+        objects.push(sphere_at(-2.0));
+        // The innermost id wins.
+        objects.push(sphere_at(-4.0).with_id(1).with_id(2));
+        let bvh = Bvh::from(objects.as_slice());
+
+        for i in 0..8 {
+            assert_eq!(hit_id(&bvh, 2.0 * i as Real), Some(10 + i));
+        }
+        assert_eq!(hit_id(&bvh, -2.0), None);
+        assert_eq!(hit_id(&bvh, -4.0), Some(1));
+    }
 }
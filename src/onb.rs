@@ -0,0 +1,65 @@
+//! Orthonormal bases, for working in coordinates around a direction.
+
+use crate::vec3::Vec3;
+
+/**
+Three mutually perpendicular unit vectors. Local coordinates `(x, y, z)` are along `u`, `v` and
+`w`, so that directions sampled around the Z axis can be placed around any direction `w`.
+
+```
+use rt_weekend::{onb::Onb, vec3::Vec3};
+
+let n = Vec3 { x: 1.0, y: -2.0, z: 0.5 }.unit();
+let Onb { u, v, w } = Onb::from_w(n);
+for axis in [u, v, w] {
+    assert!((axis.norm() - 1.0).abs() < 1e-6);
+}
+for (a, b) in [(u, v), (v, w), (w, u)] {
+    assert!(a.dot(b).abs() < 1e-6);
+}
+assert!(w.approx_eq(n, 1e-6));
+```
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct Onb {
+    pub u: Vec3,
+    pub v: Vec3,
+    pub w: Vec3,
+}
+
+impl Onb {
+    /// A basis around `w`, which must be a unit vector. See [`Vec3::orthonormal_basis`].
+    pub fn from_w(w: Vec3) -> Self {
+        let (u, v) = w.orthonormal_basis();
+        Onb { u, v, w }
+    }
+
+    /**
+    A basis around the direction of `w`, with `v` as close to `up` as possible, like a camera
+    looking along `-w`. `None` if either is zero, or they're parallel.
+    */
+    pub fn from_w_and_up(w: Vec3, up: Vec3) -> Option<Self> {
+        let w = w.try_unit()?;
+        if up.near_zero() || up.unit().cross(w).near_zero() {
+            return None;
+        }
+        let u = up.cross(w).unit();
+        let v = w.cross(u).unit();
+        Some(Onb { u, v, w })
+    }
+
+    /// The world-space direction with coordinates `a` in this basis.
+    pub fn local(&self, a: Vec3) -> Vec3 {
+        a.x * self.u + a.y * self.v + a.z * self.w
+    }
+
+    /// The coordinates of the world-space direction `a` in this basis. The inverse of
+    /// [`Onb::local`].
+    pub fn to_local(&self, a: Vec3) -> Vec3 {
+        Vec3 {
+            x: a.dot(self.u),
+            y: a.dot(self.v),
+            z: a.dot(self.w),
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{
+    bounds::Bounds3,
+    hit::{Face, Hit, Tangents},
+    interval::Interval,
+    material::Material,
+    object::IsObject,
+    ray::Ray,
+    real::Real,
+    texture,
+    vec3::Vec3,
+};
+
+/**
+A flat surface through `point` that goes on forever, facing along `normal`.
+
+Its bounds are infinite, so a [`Bvh`](crate::bvh::Bvh) can't place it in the hierarchy, and tests
+it against every ray instead. Texture coordinates repeat every unit along the plane.
+*/
+pub struct Plane {
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub material: Material,
+}
+
+impl IsObject for Plane {
+    fn hit(&self, ray: &Ray, t_range: Interval) -> Option<Hit> {
+        let outward_normal = self.normal.unit();
+
+        let denominator = ray.direction.dot(outward_normal);
+        if denominator.abs() < 1e-12 {
+            // The ray is parallel to the plane.
+            return None;
+        }
+
+        let t = (self.point - ray.origin).dot(outward_normal) / denominator;
+        if !t_range.contains(t) {
+            return None;
+        }
+
+        let point = ray.at(t);
+        let (normal, face) = if denominator < 0.0 {
+            (outward_normal, Face::Front)
+        } else {
+            (-outward_normal, Face::Back)
+        };
+
+        let (tangent, bitangent) = outward_normal.orthonormal_basis();
+        let offset = point - self.point;
+        let texture_coord = texture::Coord {
+            u: offset.dot(tangent).rem_euclid(1.0),
+            v: offset.dot(bitangent).rem_euclid(1.0),
+        };
+
+        Some(Hit {
+            point,
+            normal,
+            t,
+            face,
+            material: self.material.clone(),
+            texture_coord,
+            tangents: Some(Tangents { tangent, bitangent }),
+            object_id: None,
+        })
+    }
+
+    fn bounds(&self) -> Bounds3 {
+        Bounds3::INFINITE
+    }
+
+    fn distance(&self, point: Vec3) -> Real {
+        (point - self.point).dot(self.normal.unit()).abs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{material::Normals, vec3::assert_vec3_eq};
+
+    #[test]
+    fn downward_ray_hits_the_ground() {
+        let up = Vec3 {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+        };
+        let ground = Plane {
+            point: Vec3::ZERO,
+            normal: up,
+            material: Material::new(Normals()),
+        };
+        let ray = Ray::new(
+            Vec3 {
+                x: 1.0,
+                y: 3.0,
+                z: -2.0,
+            },
+            Vec3 {
+                x: 0.5,
+                y: -2.0,
+                z: 0.0,
+            },
+        );
+
+        let hit = ground
+            .hit(&ray, Interval::new(0.0, Real::INFINITY))
+            .unwrap();
+        assert!((hit.t - 1.5).abs() < 1e-6, "{}", hit.t);
+        assert_vec3_eq!(
+            hit.point,
+            Vec3 {
+                x: 1.75,
+                y: 0.0,
+                z: -2.0
+            }
+        );
+        assert_vec3_eq!(hit.normal, up);
+        assert!(matches!(hit.face, Face::Front));
+
+        // Pointing away from the plane, so the hit is behind the ray.
+        let away = Ray::new(ray.origin, -ray.direction);
+        assert!(ground
+            .hit(&away, Interval::new(0.0, Real::INFINITY))
+            .is_none());
+    }
+}
@@ -0,0 +1,189 @@
+use crate::{
+    bounds::Bounds3,
+    emitter::IsEmitter,
+    hit::{Face, Hit, Tangents},
+    interval::Interval,
+    material::{IsMaterial, Material},
+    object::IsObject,
+    ray::Ray,
+    real::Real,
+    texture,
+    vec3::Vec3,
+};
+use rand::{Rng, RngCore};
+
+/**
+A flat parallelogram with one corner at `corner` and sides `u` and `v`, facing along `u × v`.
+
+The texture coordinates are how far along `u` and `v` a point is, from 0 at `corner` to 1 at the
+opposite sides.
+*/
+#[derive(Clone)]
+pub struct Quad {
+    pub corner: Vec3,
+    pub u: Vec3,
+    pub v: Vec3,
+    pub material: Material,
+}
+
+impl IsObject for Quad {
+    fn hit(&self, ray: &Ray, t_range: Interval) -> Option<Hit> {
+        let n = self.u.cross(self.v);
+        if n.near_zero() {
+            // The sides are parallel, so there's no area to hit.
+            return None;
+        }
+        let outward_normal = n.unit();
+
+        let denominator = ray.direction.dot(outward_normal);
+        if denominator.abs() < 1e-12 {
+            // The ray is parallel to the quad.
+            return None;
+        }
+
+        let t = (self.corner - ray.origin).dot(outward_normal) / denominator;
+        if !t_range.contains(t) {
+            return None;
+        }
+
+        /*
+        Write the hit point as `corner + alpha * u + beta * v`. Crossing the offset with `v` (or
+        `u`) removes one term, and dotting with `n / (n · n)` divides out what's left.
+        */
+        let point = ray.at(t);
+        let offset = point - self.corner;
+        let w = n / n.dot(n);
+        let alpha = w.dot(offset.cross(self.v));
+        let beta = w.dot(self.u.cross(offset));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+
+        let (normal, face) = if denominator < 0.0 {
+            (outward_normal, Face::Front)
+        } else {
+            (-outward_normal, Face::Back)
+        };
+
+        Some(Hit {
+            point,
+            normal,
+            t,
+            face,
+            material: self.material.clone(),
+            texture_coord: texture::Coord { u: alpha, v: beta },
+            tangents: Some(Tangents {
+                tangent: self.u,
+                bitangent: self.v,
+            }),
+            object_id: None,
+        })
+    }
+
+    fn bounds(&self) -> Bounds3 {
+        // Padded because the quad has no thickness.
+        Bounds3::new(self.corner, self.corner + self.u + self.v)
+            .union(&Bounds3::new(self.corner + self.u, self.corner + self.v))
+            .padded(0.0001)
+    }
+}
+
+/// Sampled uniformly over its area, so it can be used as an area light.
+impl IsEmitter for Quad {
+    fn sample_direction(&self, rng: &mut dyn RngCore, origin: Vec3) -> Vec3 {
+        let point = self.corner + rng.gen::<Real>() * self.u + rng.gen::<Real>() * self.v;
+        point - origin
+    }
+
+    fn direction_pdf(&self, origin: Vec3, direction: Vec3) -> Real {
+        let ray = Ray::new(origin, direction);
+        match self.hit(&ray, Interval::new(0.0, Real::INFINITY)) {
+            Some(hit) => {
+                // Converts the density over the quad's area to one over solid angle.
+                let distance_squared = hit.t * hit.t * direction.norm_squared();
+                let cosine = direction.dot(hit.normal).abs() / direction.norm();
+                distance_squared / (cosine * self.u.cross(self.v).norm())
+            }
+            None => 0.0,
+        }
+    }
+
+    /// The luminance emitted from the middle of the front face, times the area.
+    fn power(&self) -> Real {
+        let n = self.u.cross(self.v);
+        let center = self.corner + 0.5 * (self.u + self.v);
+        let ray = Ray::new(center + n, -n);
+        match self.hit(&ray, Interval::new(0.0, Real::INFINITY)) {
+            Some(hit) => hit.material.emit(&ray, &hit).luminance() * n.norm(),
+            None => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Normals;
+
+    #[test]
+    fn hits_inside_the_sides_and_misses_past_them() {
+        let quad = Quad {
+            corner: Vec3 {
+                x: 1.0,
+                y: 2.0,
+                z: 0.0,
+            },
+            u: Vec3 {
+                x: 2.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            v: Vec3 {
+                x: 0.5,
+                y: 1.0,
+                z: 0.0,
+            },
+            material: Material::new(Normals()),
+        };
+        let t_range = Interval::new(0.0, Real::INFINITY);
+        // A ray straight down onto the point `alpha` along `u` and `beta` along `v`.
+        let ray_to = |alpha: Real, beta: Real| {
+            let point = quad.corner + alpha * quad.u + beta * quad.v;
+            Ray::new(
+                point
+                    + Vec3 {
+                        x: 0.0,
+                        y: 0.0,
+                        z: 3.0,
+                    },
+                Vec3 {
+                    x: 0.0,
+                    y: 0.0,
+                    z: -1.0,
+                },
+            )
+        };
+
+        let steps = [0.0, 0.001, 0.25, 0.5, 0.75, 0.999];
+        for alpha in steps {
+            for beta in steps {
+                let hit = quad
+                    .hit(&ray_to(alpha, beta), t_range)
+                    .unwrap_or_else(|| panic!("missed at alpha = {}, beta = {}", alpha, beta));
+                assert!((hit.t - 3.0).abs() < 1e-4, "{}", hit.t);
+                assert!((hit.texture_coord.u - alpha).abs() < 1e-4);
+                assert!((hit.texture_coord.v - beta).abs() < 1e-4);
+                assert!(matches!(hit.face, Face::Front));
+            }
+        }
+
+        for (alpha, beta) in [(-0.01, 0.5), (1.01, 0.5), (0.5, -0.01), (0.5, 1.01)] {
+            assert!(
+                quad.hit(&ray_to(alpha, beta), t_range).is_none(),
+                "hit at alpha = {}, beta = {}",
+                alpha,
+                beta
+            );
+        }
+    }
+}
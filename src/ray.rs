@@ -1,13 +1,202 @@
-use crate::vec3::Vec3;
+use crate::{interval::Interval, real::Real, vec3::Vec3};
 
+/**
+A ray. Construct with [`Ray::new`].
+
+Positions along the ray, such as the `t` of a [`Hit`](crate::hit::Hit) and the `t_range` it must
+be in, are measured in multiples of [`Ray::direction`], which is left at whatever length it was
+created with. So `t` is only a distance when the direction is a unit vector. Rays leaving a
+surface avoid hitting it again by starting a small distance away (see [`Ray::leaving_surface`])
+rather than by ignoring small `t`, so that doesn't depend on the direction's length either.
+
+```
+use rt_weekend::{
+    interval::Interval,
+    material::{Material, Normals},
+    object::IsObject,
+    ray::Ray,
+    real::Real,
+    sphere::Sphere,
+    texture::UvTransform,
+    vec3::Vec3,
+};
+
+let sphere = Sphere {
+    center: Vec3::ZERO,
+    radius: 1.0,
+    material: Material::new(Normals()),
+    uv_transform: UvTransform::default(),
+};
+let origin = Vec3 { x: 0.0, y: 0.0, z: 5.0 };
+let towards = |z| Ray::new(origin, Vec3 { x: 0.0, y: 0.0, z });
+let hit = |ray: &Ray| sphere.hit(ray, Interval::new(0.0, Real::INFINITY)).unwrap();
+
+// Doubling the direction halves `t`, but the hit is in the same place.
+let (unit, double) = (hit(&towards(-1.0)), hit(&towards(-2.0)));
+assert_eq!(unit.t, 4.0);
+assert_eq!(double.t, 2.0);
+assert_eq!(unit.point, double.point);
+```
+*/
 #[derive(Debug)]
 pub struct Ray {
     pub origin: Vec3,
+    /**
+    The direction of the ray. Not necessarily a unit vector.
+
+    Don't modify this after construction; [`Ray::inverse_direction`] won't be updated. Create a new
+    ray instead.
+    */
     pub direction: Vec3,
+    inverse_direction: Vec3,
 }
 
 impl Ray {
-    pub fn at(&self, t: f64) -> Vec3 {
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Ray {
+            origin,
+            direction,
+            inverse_direction: Vec3 {
+                x: 1.0 / direction.x,
+                y: 1.0 / direction.y,
+                z: 1.0 / direction.z,
+            },
+        }
+    }
+
+    /**
+    A ray in `direction` from `point` on a surface facing along `normal`.
+
+    Rounding leaves computed hit points slightly off the surface, by an amount that grows with the
+    size of their coordinates, so a ray starting exactly at `point` could hit the surface it's
+    leaving. Instead, the origin is moved off the surface along `normal`, to the side that
+    `direction` points toward, by a distance proportional to the largest coordinate of `point`.
+    */
+    pub fn leaving_surface(point: Vec3, normal: Vec3, direction: Vec3) -> Self {
+        let magnitude = point.x.abs().max(point.y.abs()).max(point.z.abs());
+        let offset = Real::EPSILON.sqrt() * (1.0 + magnitude);
+        let origin = if direction.dot(normal) >= 0.0 {
+            point + offset * normal
+        } else {
+            point - offset * normal
+        };
+        Ray::new(origin, direction)
+    }
+
+    /**
+    The point `t` directions along the ray: `origin + t * direction`.
+
+    ```
+    use rt_weekend::{ray::Ray, vec3::Vec3};
+
+    let ray = Ray::new(Vec3::ZERO, Vec3 { x: 0.0, y: 2.0, z: 0.0 });
+    assert_eq!(ray.at(1.5).y, 3.0);
+    assert_eq!(ray.at(-1.0).y, -2.0);
+    ```
+    */
+    pub fn at(&self, t: Real) -> Vec3 {
         self.origin + t * self.direction
     }
+
+    /**
+    The point `distance` along the ray, regardless of the direction's length. NaN if the direction
+    is zero.
+
+    ```
+    use rt_weekend::{ray::Ray, vec3::Vec3};
+
+    let ray = Ray::new(Vec3::ZERO, Vec3 { x: 0.0, y: 2.0, z: 0.0 });
+    assert_eq!(ray.at_unit(1.5).y, 1.5);
+    ```
+    */
+    pub fn at_unit(&self, distance: Real) -> Vec3 {
+        self.origin + distance * self.direction.unit()
+    }
+
+    /**
+    [`Ray::at`] the closest position to `t` in `t_range`, for example to keep a point from going
+    past the end of a segment.
+
+    ```
+    use rt_weekend::{interval::Interval, ray::Ray, vec3::Vec3};
+
+    let ray = Ray::new(Vec3::ZERO, Vec3 { x: 0.0, y: 2.0, z: 0.0 });
+    assert_eq!(ray.at_clamped(5.0, Interval::new(0.0, 1.0)).y, 2.0);
+    assert_eq!(ray.at_clamped(-5.0, Interval::new(0.0, 1.0)).y, 0.0);
+    ```
+    */
+    pub fn at_clamped(&self, t: Real, t_range: Interval) -> Vec3 {
+        self.at(t_range.clamp(t))
+    }
+
+    /// Whether the origin and direction are finite and the direction isn't zero, for checking rays
+    /// while debugging. Other rays can't hit anything.
+    pub fn is_valid(&self) -> bool {
+        self.origin.is_finite() && self.direction.is_finite() && self.direction != Vec3::ZERO
+    }
+
+    /**
+    The reciprocal of each component of [`Ray::direction`], computed once so that testing the ray
+    against many bounding boxes doesn't repeat the divisions.
+
+    Components are infinite where the direction's are zero.
+    */
+    pub fn inverse_direction(&self) -> &Vec3 {
+        &self.inverse_direction
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        material::{Material, Normals},
+        object::IsObject,
+        onb::Onb,
+        sampling::random_unit_vector,
+        sphere::Sphere,
+        texture::UvTransform,
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn grazing_rays_leaving_a_large_sphere_miss_it() {
+        let ground = Sphere {
+            center: Vec3 {
+                x: 0.0,
+                y: -1000.0,
+                z: 0.0,
+            },
+            radius: 1000.0,
+            material: Material::new(Normals()),
+            uv_transform: UvTransform::default(),
+        };
+        let t_range = Interval::new(0.0, Real::INFINITY);
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..1000 {
+            // A point on the surface, as computed by a hit.
+            let target = ground.center + 1000.0 * random_unit_vector(&mut rng);
+            let origin = target + 10.0 * (target - ground.center).unit();
+            let hit = ground
+                .hit(&Ray::new(origin, target - origin), t_range)
+                .unwrap();
+
+            // Almost along the surface, tilted slightly away from it.
+            let Onb { u, .. } = Onb::from_w(hit.normal);
+            let direction = u + 1e-3 * hit.normal;
+            let leaving = Ray::leaving_surface(hit.point, hit.normal, direction);
+            assert!(
+                ground.hit(&leaving, t_range).is_none(),
+                "{:?} leaving {:?}",
+                leaving,
+                hit.point
+            );
+
+            // Tilted into the surface, it only reaches the far side.
+            let direction = u - 1e-3 * hit.normal;
+            let entering = Ray::leaving_surface(hit.point, hit.normal, direction);
+            let far_side = ground.hit(&entering, t_range).unwrap();
+            assert!(far_side.t > 1.0, "{}", far_side.t);
+        }
+    }
 }
@@ -0,0 +1,16 @@
+/*!
+The floating-point type used for geometry and color.
+
+`f64` by default. Enabling the `f32` feature halves the size of vectors, colors and bounding
+boxes, which matters for scenes with very large meshes, at the cost of precision.
+*/
+
+#[cfg(not(feature = "f32"))]
+pub type Real = f64;
+#[cfg(not(feature = "f32"))]
+pub use std::f64::consts;
+
+#[cfg(feature = "f32")]
+pub type Real = f32;
+#[cfg(feature = "f32")]
+pub use std::f32::consts;
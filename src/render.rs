@@ -0,0 +1,1585 @@
+/*!
+Turning a [`Scene`] into an [`Image`].
+
+```
+use rt_weekend::{
+    background::Background,
+    camera::Camera,
+    color::Color,
+    material::{Lambertian, Material},
+    object::Object,
+    render::Renderer,
+    scene::Scene,
+    sphere::Sphere,
+    texture::{self, Texture, UvTransform},
+    vec3::Vec3,
+};
+
+let sphere = Object::new(Sphere {
+    center: Vec3 {
+        x: 0.0,
+        y: 0.0,
+        z: -2.0,
+    },
+    radius: 0.5,
+    material: Material::new(Lambertian {
+        albedo: Texture::new(texture::Constant {
+            color: Color {
+                r: 0.8,
+                g: 0.3,
+                b: 0.3,
+            },
+        }),
+    }),
+    uv_transform: UvTransform::default(),
+});
+let camera = Camera::builder().aspect_ratio(2.0).build().unwrap();
+
+let renderer = Renderer::builder(camera, Scene::new(&[sphere], Vec::new()))
+    .background(Background::default())
+    .dimensions(40, 20)
+    .rays_per_pixel(4)
+    .build()
+    .unwrap();
+let image = renderer.render().gamma_corrected();
+assert_eq!(image.data.len(), 40 * 20);
+
+// The same image, as bytes for a web page or another library.
+let rgba = renderer.render_to_rgba8();
+assert_eq!(rgba.len(), 40 * 20 * 4);
+assert_eq!(rgba[..4], image.to_rgba8()[..4]);
+```
+*/
+
+use crate::{
+    background::Background,
+    bvh::{TraversalCounter, TraversalStats},
+    camera::Camera,
+    color::Color,
+    filter::Filter,
+    hit::Hit,
+    image::Image,
+    interval::Interval,
+    material::IsMaterial,
+    ray::Ray,
+    real::Real,
+    sampler::{SamplePattern, Sampler},
+    scene::Scene,
+    vec3::Vec3,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::prelude::*;
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/**
+The power heuristic for multiple importance sampling: how much to trust a sample taken with
+density `pdf`, when `other_pdf` is the density another strategy would have taken it with.
+*/
+fn power_heuristic(pdf: Real, other_pdf: Real) -> Real {
+    if other_pdf == 0.0 {
+        1.0
+    } else {
+        pdf * pdf / (pdf * pdf + other_pdf * other_pdf)
+    }
+}
+
+/// Counts of what happened while rendering, to help tune the sampling settings.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Stats {
+    /// Rays leaving the camera.
+    pub primary_rays: u64,
+    /// Every ray along a path, including the primary rays.
+    pub path_rays: u64,
+    /// Rays toward lights, checking whether they're visible.
+    pub shadow_rays: u64,
+    /// Paths that left the scene.
+    pub escaped: u64,
+    /// Paths that ended when a material absorbed the ray.
+    pub absorbed: u64,
+    /// Paths cut short by the recursion depth.
+    pub exhausted: u64,
+    /// Paths ended by Russian roulette.
+    pub roulette: u64,
+    /// The most rays in any one path.
+    pub longest_path: u64,
+    /// Only counted when the renderer was built with [`RendererBuilder::traversal_stats`].
+    pub traversal: TraversalStats,
+}
+
+impl Stats {
+    pub fn combine(&mut self, other: &Stats) {
+        self.primary_rays += other.primary_rays;
+        self.path_rays += other.path_rays;
+        self.shadow_rays += other.shadow_rays;
+        self.escaped += other.escaped;
+        self.absorbed += other.absorbed;
+        self.exhausted += other.exhausted;
+        self.roulette += other.roulette;
+        self.longest_path = self.longest_path.max(other.longest_path);
+        self.traversal.combine(&other.traversal);
+    }
+}
+
+/// Light arriving at `hit` directly from a randomly chosen light, that scatters back along `ray`.
+fn sample_lights<R: Rng, C: TraversalCounter>(
+    rng: &mut R,
+    stats: &mut Stats,
+    traversal: &mut C,
+    ray: &Ray,
+    hit: &Hit,
+    scene: &Scene,
+) -> Color {
+    let direction = match scene.sample_light_direction(rng, hit.point) {
+        Some(direction) => direction,
+        None => return Color::BLACK,
+    };
+    let light_pdf = scene.light_pdf(hit.point, direction);
+    let reflectance = match hit.material.reflectance(ray, hit, direction) {
+        Some(reflectance) if light_pdf > 0.0 && reflectance.pdf > 0.0 => reflectance,
+        _ => return Color::BLACK,
+    };
+
+    let shadow_ray = Ray::leaving_surface(hit.point, hit.normal, direction);
+    stats.shadow_rays += 1;
+    match scene.hit_counted(
+        rng,
+        &shadow_ray,
+        Interval::new(0.0, Real::INFINITY),
+        traversal,
+    ) {
+        Some(light_hit) => {
+            let weight = power_heuristic(light_pdf, reflectance.pdf);
+            (weight / light_pdf)
+                * (reflectance.value * light_hit.material.emit(&shadow_ray, &light_hit))
+        }
+        None => Color::BLACK,
+    }
+}
+
+/// What a path sees when it reaches the recursion depth.
+#[derive(Debug, Clone, Copy)]
+pub enum Exhausted {
+    /// This light, as if the path had continued into it.
+    Light(Color),
+    /// The background, as if the path had escaped along the ray it would have traced next.
+    Background,
+    /// This color instead of anything the path found, so that it stands out.
+    Highlight(Color),
+}
+
+/// How long paths can get, and how much light they can carry.
+#[derive(Debug, Clone, Copy)]
+pub struct PathLimits {
+    /// The most rays in a path.
+    pub recursion_depth: usize,
+    /**
+    The number of bounces after which Russian roulette may end a path, or `None` to follow every
+    path to `recursion_depth`.
+
+    Roulette leaves the image's expected brightness unchanged but adds noise, so it's off unless
+    asked for.
+    */
+    pub rr_depth: Option<usize>,
+    pub exhausted: Exhausted,
+    /**
+    The most light, per channel, that a path can bring back from anything it finds after
+    scattering more than once. Light seen directly or after a single scatter is never clamped.
+
+    Lowering it removes bright single-pixel outliers ("fireflies") at the cost of slightly darker
+    indirect lighting.
+    */
+    pub clamp_indirect: Real,
+}
+
+impl Default for PathLimits {
+    fn default() -> Self {
+        PathLimits {
+            recursion_depth: 50,
+            rr_depth: None,
+            exhausted: Exhausted::Light(Color::BLACK),
+            clamp_indirect: Real::INFINITY,
+        }
+    }
+}
+
+/// What happened to one ray of a path traced by [`Renderer::probe`].
+pub struct Bounce<'a> {
+    /// The ray's place in the path, from 0 for the ray leaving the camera.
+    pub depth: usize,
+    pub ray: &'a Ray,
+    /// What the ray hit, or `None` if it escaped to the background.
+    pub hit: Option<&'a Hit>,
+    /// The direction the path continues in, or `None` if it ended here.
+    pub scattered: Option<Vec3>,
+    /// The light found along the path so far.
+    pub radiance: Color,
+    /// The fraction of the light found after this bounce that will reach the camera.
+    pub throughput: Color,
+}
+
+/// What every ray in a render shares: what it's traced through, and how.
+#[derive(Clone, Copy)]
+struct SampleSettings<'a> {
+    camera: &'a Camera,
+    scene: &'a Scene,
+    background: &'a Background,
+    limits: PathLimits,
+    filter: Filter,
+    /// Whether to record each ray's first hit for the albedo and normal buffers.
+    auxiliary: bool,
+}
+
+/**
+A pixel, counting up from the bottom left, and the largest pixel coordinates, which together
+place it on the camera's viewport.
+*/
+#[derive(Clone, Copy)]
+struct PixelContext {
+    x: Real,
+    y: Real,
+    x_total: Real,
+    y_total: Real,
+}
+
+/**
+The light arriving at the camera along `ray`.
+
+The path is followed one bounce at a time. `throughput` is the fraction of the light found at the
+current bounce that makes it back to the camera. With `limits.rr_depth`, once a path is that many
+bounces long it's continued with a probability proportional to its throughput, and the paths that
+survive are weighted up to make up for the ones that don't.
+
+Light reaching the camera after `scatters` scatters is clamped by `limits.clamp_indirect` when
+`scatters` is 2 or more.
+
+`on_bounce` is called for each ray, after its hit has been shaded.
+*/
+fn ray_color<R: Rng, C: TraversalCounter, F: FnMut(&Bounce)>(
+    rng: &mut R,
+    stats: &mut Stats,
+    traversal: &mut C,
+    mut ray: Ray,
+    settings: SampleSettings,
+    on_bounce: &mut F,
+) -> Color {
+    let SampleSettings {
+        scene,
+        background,
+        limits,
+        ..
+    } = settings;
+    let mut radiance = Color::BLACK;
+    let mut throughput = Color {
+        r: 1.0,
+        g: 1.0,
+        b: 1.0,
+    };
+    /*
+    The density with which the previous bounce chose `ray`, if it could also have been chosen by
+    sampling a light. Emission found along `ray` is then weighted against light sampling so that
+    it isn't counted twice.
+    */
+    let mut scatter_pdf: Option<Real> = None;
+    let clamp = |contribution: Color, scatters: usize| {
+        if scatters >= 2 {
+            contribution.clamped(limits.clamp_indirect)
+        } else {
+            contribution
+        }
+    };
+
+    for bounce in 0..limits.recursion_depth {
+        stats.path_rays += 1;
+        let hit = match scene.hit_counted(rng, &ray, Interval::new(0.0, Real::INFINITY), traversal)
+        {
+            Some(hit) => hit,
+            None => {
+                stats.escaped += 1;
+                let radiance = radiance + clamp(throughput * background.color(&ray), bounce);
+                on_bounce(&Bounce {
+                    depth: bounce,
+                    ray: &ray,
+                    hit: None,
+                    scattered: None,
+                    radiance,
+                    throughput,
+                });
+                return radiance;
+            }
+        };
+
+        let material = &hit.material;
+        let emittance = match scatter_pdf {
+            Some(pdf) => {
+                power_heuristic(pdf, scene.light_pdf(ray.origin, ray.direction))
+                    * material.emit(&ray, &hit)
+            }
+            None => material.emit(&ray, &hit),
+        };
+        radiance += clamp(throughput * emittance, bounce);
+
+        let scatter = match material.scatter(rng, &ray, &hit) {
+            Some(scatter) => scatter,
+            None => {
+                stats.absorbed += 1;
+                on_bounce(&Bounce {
+                    depth: bounce,
+                    ray: &ray,
+                    hit: Some(&hit),
+                    scattered: None,
+                    radiance,
+                    throughput,
+                });
+                return radiance;
+            }
+        };
+
+        scatter_pdf = match material.reflectance(&ray, &hit, scatter.outgoing.direction) {
+            Some(reflectance) => {
+                let direct = throughput * sample_lights(rng, stats, traversal, &ray, &hit, scene);
+                radiance += clamp(direct, bounce + 1);
+                Some(reflectance.pdf)
+            }
+            None => None,
+        };
+
+        throughput = throughput * scatter.attenuation;
+        on_bounce(&Bounce {
+            depth: bounce,
+            ray: &ray,
+            hit: Some(&hit),
+            scattered: Some(scatter.outgoing.direction),
+            radiance,
+            throughput,
+        });
+        if limits
+            .rr_depth
+            .is_some_and(|rr_depth| bounce + 1 >= rr_depth)
+        {
+            let survival = throughput.max_channel().min(1.0);
+            if survival < 1.0 {
+                if rng.gen::<Real>() >= survival {
+                    stats.roulette += 1;
+                    return radiance;
+                }
+                throughput = throughput / survival;
+            }
+        }
+
+        // Rays start off the surface they scatter from, so hits at any distance count.
+        ray = Ray::leaving_surface(
+            scatter.outgoing.origin,
+            hit.normal,
+            scatter.outgoing.direction,
+        );
+    }
+
+    stats.exhausted += 1;
+    match limits.exhausted {
+        Exhausted::Light(color) => radiance + clamp(throughput * color, limits.recursion_depth),
+        Exhausted::Background => {
+            radiance + clamp(throughput * background.color(&ray), limits.recursion_depth)
+        }
+        Exhausted::Highlight(color) => color,
+    }
+}
+
+/// The sums (not the averages) of what a pixel's samples saw, each weighted by the filter.
+#[derive(Clone, Copy)]
+struct PixelSum {
+    color: Color,
+    /// The total of the samples' weights.
+    weight: Real,
+    /// The albedo at each ray's first hit. Only computed for auxiliary buffers.
+    albedo: Color,
+    /// The shading normal at each ray's first hit. Only computed for auxiliary buffers.
+    normal: Color,
+}
+
+impl PixelSum {
+    const ZERO: Self = PixelSum {
+        color: Color::BLACK,
+        weight: 0.0,
+        albedo: Color::BLACK,
+        normal: Color::BLACK,
+    };
+}
+
+/// A ray through `pixel`, placed over the support of `filter` by `sampler`, and its weight.
+fn primary_ray<R: Rng>(
+    rng: &mut R,
+    camera: &Camera,
+    sampler: &mut dyn Sampler,
+    filter: Filter,
+    pixel: PixelContext,
+) -> (Ray, Real) {
+    let (du, dv) = sampler.next_2d(rng);
+    let (du, dv) = (filter.position(du), filter.position(dv));
+    let weight = filter.weight(du - 0.5, dv - 0.5);
+    let u = (pixel.x + du) / pixel.x_total;
+    let v = (pixel.y + dv) / pixel.y_total;
+    (camera.get_ray(rng, u, v), weight)
+}
+
+/// Trace `samples` rays through `pixel`, placed over the support of the filter by `sampler`, and
+/// weighted by the filter.
+fn sample_pixel<R: Rng, C: TraversalCounter>(
+    rng: &mut R,
+    stats: &mut Stats,
+    traversal: &mut C,
+    settings: SampleSettings,
+    sampler: &mut dyn Sampler,
+    samples: usize,
+    pixel: PixelContext,
+) -> PixelSum {
+    let mut sum = PixelSum::ZERO;
+    for _ in 0..samples {
+        let (ray, weight) = primary_ray(rng, settings.camera, sampler, settings.filter, pixel);
+
+        let path_rays = stats.path_rays;
+        // The first hit is taken from the traced path, rather than tracing the ray again, which
+        // would draw extra random numbers (in media) and change the image.
+        let mut first_hit = None;
+        let color = ray_color(rng, stats, traversal, ray, settings, &mut |bounce| {
+            if settings.auxiliary && bounce.depth == 0 {
+                first_hit = bounce.hit.map(|hit| {
+                    let normal = Color {
+                        r: hit.normal.x,
+                        g: hit.normal.y,
+                        b: hit.normal.z,
+                    };
+                    (hit.material.albedo_hint(hit), normal)
+                });
+            }
+        });
+        if let Some((albedo, normal)) = first_hit {
+            sum.albedo += weight * albedo;
+            sum.normal += weight * normal;
+        }
+        sum.color += weight * color;
+        sum.weight += weight;
+        stats.primary_rays += 1;
+        stats.longest_path = stats.longest_path.max(stats.path_rays - path_rays);
+    }
+    sum
+}
+
+/**
+The random number generator for pixel `(x, y)` in a particular rendering `pass`.
+
+Giving every pixel its own generator, rather than sharing one per thread, makes the image
+independent of which thread renders which pixel.
+*/
+fn pixel_rng(seed: u64, pass: usize, x: usize, y: usize) -> StdRng {
+    let mut key = [0; 32];
+    for (bytes, value) in key
+        .chunks_exact_mut(8)
+        .zip([seed, pass as u64, x as u64, y as u64])
+    {
+        bytes.copy_from_slice(&value.to_le_bytes());
+    }
+    StdRng::from_seed(key)
+}
+
+/**
+The samples rendered so far, summed per pixel, row-major from the top of the image.
+
+`albedo_sums` and `normal_sums` stay black unless the renderer records auxiliary buffers.
+*/
+pub struct Accumulator {
+    pub width: usize,
+    pub height: usize,
+    /// The filter-weighted sums of each pixel's samples.
+    pub sums: Vec<Color>,
+    /// The total filter weight of each pixel's samples.
+    pub weights: Vec<Real>,
+    pub albedo_sums: Vec<Color>,
+    pub normal_sums: Vec<Color>,
+    /// The number of samples in each pixel, before scaling by any importance map.
+    pub samples: usize,
+    pub stats: Stats,
+    /// Time spent tracing rays, not counting anything done between passes.
+    pub render_time: Duration,
+}
+
+impl Accumulator {
+    fn new(width: usize, height: usize) -> Self {
+        Accumulator {
+            width,
+            height,
+            sums: vec![Color::BLACK; width * height],
+            weights: vec![0.0; width * height],
+            albedo_sums: vec![Color::BLACK; width * height],
+            normal_sums: vec![Color::BLACK; width * height],
+            samples: 0,
+            stats: Stats::default(),
+            render_time: Duration::ZERO,
+        }
+    }
+
+    fn average(&self, sums: &[Color]) -> Image {
+        Image::from_accumulator(sums, &self.weights, self.width, self.height)
+    }
+
+    /// The weighted average of each pixel's samples, in linear color.
+    pub fn image(&self) -> Image {
+        self.average(&self.sums)
+    }
+
+    /// The average albedo at each pixel's first hits.
+    pub fn albedo(&self) -> Image {
+        self.average(&self.albedo_sums)
+    }
+
+    /// The average shading normal at each pixel's first hits, with `(x, y, z)` stored as `(r, g, b)`.
+    pub fn normals(&self) -> Image {
+        self.average(&self.normal_sums)
+    }
+}
+
+/// A rectangle of pixels, from `(x0, y0)` up to but not including `(x1, y1)`, counted from the top
+/// left of the image.
+#[derive(Debug, Clone, Copy)]
+pub struct Crop {
+    pub x0: usize,
+    pub y0: usize,
+    pub x1: usize,
+    pub y1: usize,
+}
+
+impl Crop {
+    /// Whether pixel `(x, y)`, counted from the top left, is in the rectangle.
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        (self.x0..self.x1).contains(&x) && (self.y0..self.y1).contains(&y)
+    }
+}
+
+#[derive(Debug)]
+pub enum RendererError {
+    ThreadPool(rayon::ThreadPoolBuildError),
+    /// The image is zero pixels wide or high.
+    EmptyImage,
+    EmptyCrop,
+    CropOutOfBounds,
+    ImportanceMapSize,
+}
+
+impl std::fmt::Display for RendererError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RendererError::ThreadPool(err) => write!(f, "failed to start threads: {}", err),
+            RendererError::EmptyImage => f.write_str("image width and height must be at least 1"),
+            RendererError::EmptyCrop => f.write_str("crop contains no pixels"),
+            RendererError::CropOutOfBounds => f.write_str("crop extends outside the image"),
+            RendererError::ImportanceMapSize => {
+                f.write_str("importance map doesn't have one value per pixel")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RendererError {}
+
+/**
+Worker threads that renderers can share, so that rendering several frames doesn't start and stop
+threads for each one. See [`RendererBuilder::pool`].
+
+Clones share the same threads. They finish their work and exit once the last clone, and the last
+renderer using them, is dropped.
+
+```
+use rt_weekend::{
+    background::Background,
+    camera::Camera,
+    color::Color,
+    ray::Ray,
+    render::{RenderPool, Renderer},
+    scene::Scene,
+};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+let pool = RenderPool::new(2).unwrap();
+let threads = Arc::new(Mutex::new(HashSet::new()));
+let record = {
+    let threads = threads.clone();
+    move |_: &Ray| {
+        threads.lock().unwrap().insert(thread::current().id());
+        Color::BLACK
+    }
+};
+let frame = || {
+    let camera = Camera::builder().build().unwrap();
+    Renderer::builder(camera, Scene::new(&[], Vec::new()))
+        .background(Background::Function(Arc::new(record.clone())))
+        .dimensions(16, 9)
+        .pool(pool.clone())
+        .build()
+        .unwrap()
+        .render()
+};
+
+// Every ray that misses is recorded by a pool thread, and new threads would have new ids.
+frame();
+frame();
+assert!(threads.lock().unwrap().len() <= pool.num_threads());
+```
+*/
+#[derive(Clone)]
+pub struct RenderPool(Arc<rayon::ThreadPool>);
+
+impl RenderPool {
+    pub fn new(num_threads: usize) -> Result<Self, RendererError> {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .map_err(RendererError::ThreadPool)?;
+        Ok(RenderPool(Arc::new(pool)))
+    }
+
+    pub fn num_threads(&self) -> usize {
+        self.0.current_num_threads()
+    }
+}
+
+/// Renders a scene through a camera, spreading the work over a thread pool.
+pub struct Renderer {
+    camera: Camera,
+    scene: Scene,
+    background: Background,
+    width: usize,
+    height: usize,
+    rays_per_pixel: usize,
+    pattern: SamplePattern,
+    filter: Filter,
+    limits: PathLimits,
+    /// Whether to record the albedo and normal buffers.
+    auxiliary: bool,
+    /// Whether to count BVH traversals in [`Stats::traversal`].
+    traversal_stats: bool,
+    seed: u64,
+    progress: bool,
+    /// The only pixels to render. The others are left black.
+    crop: Option<Crop>,
+    /// Each pixel's total rays, in image order, when they aren't all `rays_per_pixel`.
+    pixel_rays: Option<Vec<usize>>,
+    pool: RenderPool,
+}
+
+impl Renderer {
+    pub fn builder(camera: Camera, scene: Scene) -> RendererBuilder {
+        RendererBuilder {
+            camera,
+            scene,
+            background: Background::default(),
+            width: 1920,
+            height: 1080,
+            rays_per_pixel: 10,
+            pattern: SamplePattern::Random,
+            filter: Filter::Box,
+            limits: PathLimits::default(),
+            auxiliary: false,
+            traversal_stats: false,
+            seed: 0,
+            progress: false,
+            crop: None,
+            importance: None,
+            num_threads: None,
+            pool: None,
+        }
+    }
+
+    fn sample_settings(&self) -> SampleSettings<'_> {
+        SampleSettings {
+            camera: &self.camera,
+            scene: &self.scene,
+            background: &self.background,
+            limits: self.limits,
+            filter: self.filter,
+            auxiliary: self.auxiliary,
+        }
+    }
+
+    /// Pixel `(x, y)`, counting up from the bottom left.
+    fn pixel_context(&self, x: usize, y: usize) -> PixelContext {
+        // A single row or column of pixels still spans the viewport.
+        PixelContext {
+            x: x as Real,
+            y: y as Real,
+            x_total: (self.width - 1).max(1) as Real,
+            y_total: (self.height - 1).max(1) as Real,
+        }
+    }
+
+    /**
+    Pixel `(x, y)`'s first ray and number of rays for the pass covering rays `first` up to
+    `first + samples` of `rays_per_pixel`.
+
+    With an importance map, each pixel's rays are spread over the passes in proportion, so that
+    every pixel finishes in the last pass.
+    */
+    fn pixel_share(&self, x: usize, y: usize, first: usize, samples: usize) -> (usize, usize) {
+        match &self.pixel_rays {
+            None => (first, samples),
+            Some(pixel_rays) => {
+                let total = pixel_rays[(self.height - 1 - y) * self.width + x];
+                let start = total * first / self.rays_per_pixel;
+                let end = total * (first + samples) / self.rays_per_pixel;
+                (start, end - start)
+            }
+        }
+    }
+
+    /**
+    Trace `samples` rays through every pixel, or each pixel's share of them with an importance
+    map, starting with each pixel's `first` ray, spread over the thread pool. Each `pass` uses
+    different random numbers.
+
+    Returns the sum of each pixel's samples, in image order (row-major, from the top), or `None`
+    if `cancel` was set before the pass finished. Rows that have started are finished first.
+    */
+    fn render_pass(
+        &self,
+        pass: usize,
+        first: usize,
+        samples: usize,
+        cancel: &AtomicBool,
+    ) -> Option<(Vec<PixelSum>, Stats)> {
+        let rows_done = AtomicUsize::new(0);
+
+        let rows: Option<Vec<(Vec<PixelSum>, Stats)>> = self.pool.0.install(|| {
+            // `y` counts up from the bottom of the image.
+            (0..self.height)
+                .into_par_iter()
+                .rev()
+                .map(|y| {
+                    if cancel.load(Ordering::Relaxed) {
+                        return None;
+                    }
+
+                    // Counted per row, so that threads don't contend over shared counters.
+                    let mut stats = Stats::default();
+                    let row = if self.traversal_stats {
+                        let mut traversal = TraversalStats::default();
+                        let row =
+                            self.render_row(pass, first, samples, y, &mut stats, &mut traversal);
+                        stats.traversal = traversal;
+                        row
+                    } else {
+                        self.render_row(pass, first, samples, y, &mut stats, &mut ())
+                    };
+
+                    if self.progress {
+                        let rows_done = rows_done.fetch_add(1, Ordering::Relaxed) + 1;
+                        eprint!("\r\x1B[0K");
+                        eprint!("rows remaining: {:?}", self.height - rows_done);
+                    }
+                    Some((row, stats))
+                })
+                .collect()
+        });
+        if self.progress {
+            eprintln!();
+        }
+
+        let mut stats = Stats::default();
+        let mut data = Vec::with_capacity(self.width * self.height);
+        for (row, row_stats) in rows? {
+            data.extend(row);
+            stats.combine(&row_stats);
+        }
+        Some((data, stats))
+    }
+
+    /**
+    Row `y`, counting up from the bottom, of the pass described by [`Renderer::render_pass`]. BVH
+    traversals are counted in `traversal`.
+    */
+    fn render_row<C: TraversalCounter>(
+        &self,
+        pass: usize,
+        first: usize,
+        samples: usize,
+        y: usize,
+        stats: &mut Stats,
+        traversal: &mut C,
+    ) -> Vec<PixelSum> {
+        (0..self.width)
+            .map(|x| {
+                if let Some(crop) = self.crop {
+                    if !crop.contains(x, self.height - 1 - y) {
+                        return PixelSum::ZERO;
+                    }
+                }
+                let (first, samples) = self.pixel_share(x, y, first, samples);
+                let mut rng = pixel_rng(self.seed, pass, x, y);
+                let mut sampler = self.pattern.sampler(&mut rng, first, samples);
+                sample_pixel(
+                    &mut rng,
+                    stats,
+                    traversal,
+                    self.sample_settings(),
+                    sampler.as_mut(),
+                    samples,
+                    self.pixel_context(x, y),
+                )
+            })
+            .collect()
+    }
+
+    /// Render passes of `pass_size` rays per pixel until the image is done or `cancel` is set.
+    fn accumulate<F: FnMut(&Accumulator)>(
+        &self,
+        pass_size: usize,
+        cancel: &AtomicBool,
+        mut on_pass: F,
+    ) -> Option<Accumulator> {
+        let pass_size = pass_size.max(1);
+        let mut accumulator = Accumulator::new(self.width, self.height);
+        while accumulator.samples < self.rays_per_pixel {
+            let samples = pass_size.min(self.rays_per_pixel - accumulator.samples);
+            let start = Instant::now();
+            let (pass, stats) = self.render_pass(
+                accumulator.samples / pass_size,
+                accumulator.samples,
+                samples,
+                cancel,
+            )?;
+            accumulator.render_time += start.elapsed();
+            accumulator.stats.combine(&stats);
+            for (i, pixel) in pass.into_iter().enumerate() {
+                accumulator.sums[i] += pixel.color;
+                accumulator.weights[i] += pixel.weight;
+                accumulator.albedo_sums[i] += pixel.albedo;
+                accumulator.normal_sums[i] += pixel.normal;
+            }
+            accumulator.samples += samples;
+            on_pass(&accumulator);
+        }
+        Some(accumulator)
+    }
+
+    /**
+    Render the image in passes of `pass_size` rays per pixel, calling `on_pass` with the samples
+    so far after each one.
+
+    The result only depends on `pass_size`, not on how many threads there are.
+    */
+    pub fn render_progressive<F: FnMut(&Accumulator)>(
+        &self,
+        pass_size: usize,
+        on_pass: F,
+    ) -> Accumulator {
+        self.accumulate(pass_size, &AtomicBool::new(false), on_pass)
+            .expect("render was cancelled without a way to cancel it")
+    }
+
+    /// Render the image in a single pass, in linear color.
+    pub fn render(&self) -> Image {
+        self.render_progressive(self.rays_per_pixel, |_| ()).image()
+    }
+
+    /**
+    Render the image in a single pass, gamma corrected for display, as 8-bit RGBA in memory. See
+    [`Image::to_rgba8`] for the layout.
+    */
+    pub fn render_to_rgba8(&self) -> Vec<u8> {
+        self.render().gamma_corrected().to_rgba8()
+    }
+
+    /**
+    Like [`Renderer::render`], but gives up and returns `None` soon after `cancel` is set, for
+    example by another thread.
+
+    Each thread finishes the row it's on, so the render stops within about a row's worth of work.
+    */
+    pub fn render_with_cancel(&self, cancel: &AtomicBool) -> Option<Image> {
+        self.accumulate(self.rays_per_pixel, cancel, |_| ())
+            .map(|accumulator| accumulator.image())
+    }
+
+    /**
+    Trace `rays` rays through pixel `(x, y)`, counted from the top left, calling `on_bounce` with
+    the index of the ray and what happened at each of its bounces, for debugging a single pixel.
+
+    Returns the color each ray brought back. They're the same rays as the pixel's first `rays`
+    rays in a single-pass render.
+    */
+    pub fn probe<F: FnMut(usize, &Bounce)>(
+        &self,
+        x: usize,
+        y: usize,
+        rays: usize,
+        mut on_bounce: F,
+    ) -> Vec<Color> {
+        // `y` counts up from the bottom of the image, as in `render_pass`.
+        let y = self.height - 1 - y;
+        let mut rng = pixel_rng(self.seed, 0, x, y);
+        let mut sampler = self.pattern.sampler(&mut rng, 0, rays);
+        let mut stats = Stats::default();
+        (0..rays)
+            .map(|index| {
+                let (ray, _) = primary_ray(
+                    &mut rng,
+                    &self.camera,
+                    sampler.as_mut(),
+                    self.filter,
+                    self.pixel_context(x, y),
+                );
+                ray_color(
+                    &mut rng,
+                    &mut stats,
+                    &mut (),
+                    ray,
+                    self.sample_settings(),
+                    &mut |bounce| on_bounce(index, bounce),
+                )
+            })
+            .collect()
+    }
+}
+
+/**
+Named, defaulted rendering settings.
+
+Defaults to a 1920x1080 image over the default background, with 10 randomly placed rays per
+pixel, a box filter, the default [`PathLimits`], seed 0, and one thread per physical core.
+*/
+pub struct RendererBuilder {
+    camera: Camera,
+    scene: Scene,
+    background: Background,
+    width: usize,
+    height: usize,
+    rays_per_pixel: usize,
+    pattern: SamplePattern,
+    filter: Filter,
+    limits: PathLimits,
+    auxiliary: bool,
+    traversal_stats: bool,
+    seed: u64,
+    progress: bool,
+    crop: Option<Crop>,
+    importance: Option<(Vec<Real>, usize)>,
+    num_threads: Option<usize>,
+    pool: Option<RenderPool>,
+}
+
+impl RendererBuilder {
+    #[must_use]
+    pub fn background(mut self, background: Background) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// The image size, in pixels.
+    #[must_use]
+    pub fn dimensions(mut self, width: usize, height: usize) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    #[must_use]
+    pub fn rays_per_pixel(mut self, rays_per_pixel: usize) -> Self {
+        self.rays_per_pixel = rays_per_pixel;
+        self
+    }
+
+    /// How each pixel's rays are spread over it.
+    #[must_use]
+    pub fn sample_pattern(mut self, pattern: SamplePattern) -> Self {
+        self.pattern = pattern;
+        self
+    }
+
+    /// How much each of a pixel's rays counts toward its color.
+    #[must_use]
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    #[must_use]
+    pub fn limits(mut self, limits: PathLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /**
+    Count the BVH nodes and objects that rays are tested against, in [`Stats::traversal`]. Each
+    thread counts separately and the counts are added up at the end of each pass. Without this,
+    the counting is compiled out of the render.
+
+    ```
+    use rt_weekend::{
+        background::Background,
+        camera::Camera,
+        material::{Material, Normals},
+        object::Object,
+        render::Renderer,
+        scene::Scene,
+        sphere::Sphere,
+        texture::UvTransform,
+        vec3::Vec3,
+    };
+
+    let sphere = |x| {
+        Object::new(Sphere {
+            center: Vec3 { x, y: 0.0, z: -2.0 },
+            radius: 0.5,
+            material: Material::new(Normals()),
+            uv_transform: UvTransform::default(),
+        })
+    };
+    let objects = [sphere(-0.6), sphere(0.6)];
+    let render = |traversal_stats| {
+        let camera = Camera::builder().build().unwrap();
+        Renderer::builder(camera, Scene::new(&objects, Vec::new()))
+            .background(Background::Black)
+            .dimensions(8, 6)
+            .rays_per_pixel(5)
+            .num_threads(3)
+            .traversal_stats(traversal_stats)
+            .build()
+            .unwrap()
+            .render_progressive(5, |_| ())
+            .stats
+    };
+
+    // Nothing scatters, so every path is a single ray.
+    let stats = render(true);
+    assert_eq!(stats.traversal.rays, 8 * 6 * 5);
+    assert!(stats.traversal.node_visits >= stats.traversal.rays);
+    assert!(stats.traversal.primitive_tests <= 2 * stats.traversal.rays);
+    assert!(stats.traversal.tests_per_ray() > 0.0);
+
+    assert_eq!(render(false).traversal.rays, 0);
+    ```
+    */
+    #[must_use]
+    pub fn traversal_stats(mut self, traversal_stats: bool) -> Self {
+        self.traversal_stats = traversal_stats;
+        self
+    }
+
+    /// Also record the albedo and shading normal at each ray's first hit.
+    #[must_use]
+    pub fn auxiliary(mut self, auxiliary: bool) -> Self {
+        self.auxiliary = auxiliary;
+        self
+    }
+
+    /// Renders with the same seed and settings are identical.
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Report the rows remaining on stderr while rendering.
+    #[must_use]
+    pub fn progress(mut self, progress: bool) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Only render the pixels in `crop`, leaving the rest of the image black.
+    #[must_use]
+    pub fn crop(mut self, crop: Crop) -> Self {
+        self.crop = Some(crop);
+        self
+    }
+
+    /**
+    Scale `rays_per_pixel` for each pixel by its value in `map`, which has one value per pixel in
+    image order (row-major, from the top). Each pixel traces at least one ray and at most
+    `max_rays_per_pixel`.
+    */
+    #[must_use]
+    pub fn importance_map(mut self, map: Vec<Real>, max_rays_per_pixel: usize) -> Self {
+        self.importance = Some((map, max_rays_per_pixel));
+        self
+    }
+
+    /// Ignored when rendering on a [`pool`](RendererBuilder::pool).
+    #[must_use]
+    pub fn num_threads(mut self, num_threads: usize) -> Self {
+        self.num_threads = Some(num_threads);
+        self
+    }
+
+    /// Render on `pool`'s threads, instead of starting new ones for this renderer.
+    #[must_use]
+    pub fn pool(mut self, pool: RenderPool) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    /**
+    Check the settings and start the renderer's threads.
+
+    ```
+    use rt_weekend::{
+        camera::Camera,
+        render::{Renderer, RendererError},
+        scene::Scene,
+    };
+
+    let build = |width, height| {
+        let camera = Camera::builder().build().unwrap();
+        Renderer::builder(camera, Scene::new(&[], Vec::new()))
+            .dimensions(width, height)
+            .build()
+    };
+    assert!(matches!(build(1, 0), Err(RendererError::EmptyImage)));
+    assert!(matches!(build(0, 1), Err(RendererError::EmptyImage)));
+    assert!(build(1, 1).is_ok());
+    ```
+    */
+    pub fn build(self) -> Result<Renderer, RendererError> {
+        if self.width == 0 || self.height == 0 {
+            return Err(RendererError::EmptyImage);
+        }
+        if let Some(crop) = self.crop {
+            if crop.x0 >= crop.x1 || crop.y0 >= crop.y1 {
+                return Err(RendererError::EmptyCrop);
+            }
+            if crop.x1 > self.width || crop.y1 > self.height {
+                return Err(RendererError::CropOutOfBounds);
+            }
+        }
+        let pixel_rays = match self.importance {
+            None => None,
+            Some((map, max_rays_per_pixel)) => {
+                if map.len() != self.width * self.height {
+                    return Err(RendererError::ImportanceMapSize);
+                }
+                let base = self.rays_per_pixel as Real;
+                Some(
+                    map.into_iter()
+                        .map(|value| {
+                            ((base * value.max(0.0)).round() as usize)
+                                .clamp(1, max_rays_per_pixel.max(1))
+                        })
+                        .collect(),
+                )
+            }
+        };
+
+        let pool = match self.pool {
+            Some(pool) => pool,
+            None => RenderPool::new(self.num_threads.unwrap_or_else(num_cpus::get_physical))?,
+        };
+        Ok(Renderer {
+            camera: self.camera,
+            scene: self.scene,
+            background: self.background,
+            width: self.width,
+            height: self.height,
+            rays_per_pixel: self.rays_per_pixel,
+            pattern: self.pattern,
+            filter: self.filter,
+            limits: self.limits,
+            auxiliary: self.auxiliary,
+            traversal_stats: self.traversal_stats,
+            seed: self.seed,
+            progress: self.progress,
+            crop: self.crop,
+            pixel_rays,
+            pool,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        emitter::Emitter,
+        material::{DiffuseLight, Isotropic, Lambertian, Material},
+        medium::ConstantMedium,
+        object::Object,
+        sphere::Sphere,
+        texture::{self, Texture, UvTransform},
+    };
+
+    /// Gray spheres resting on a gray ground, so that paths bounce between them, and optionally a
+    /// light above them.
+    fn gray_spheres(lit: bool) -> Scene {
+        let sphere = |y, radius, material| Sphere {
+            center: Vec3 { x: 0.0, y, z: -1.0 },
+            radius,
+            material,
+            uv_transform: UvTransform::default(),
+        };
+        let color = |value| {
+            Texture::new(texture::Constant {
+                color: Color {
+                    r: value,
+                    g: value,
+                    b: value,
+                },
+            })
+        };
+        let gray = || Material::new(Lambertian { albedo: color(0.5) });
+        let mut objects = vec![
+            Object::new(sphere(0.0, 0.5, gray())),
+            Object::new(sphere(-100.5, 100.0, gray())),
+        ];
+        let mut lights = Vec::new();
+        if lit {
+            let light = sphere(1.5, 0.25, Material::new(DiffuseLight { emit: color(4.0) }));
+            objects.push(Object::new(light.clone()));
+            lights.push(Emitter::new(light));
+        }
+        Scene::new(&objects, lights)
+    }
+
+    /// The average of `samples` paths from the origin along `direction`.
+    fn mean_color(
+        scene: &Scene,
+        limits: PathLimits,
+        seed: u64,
+        direction: Vec3,
+        samples: usize,
+    ) -> Color {
+        let camera = Camera::builder().build().unwrap();
+        let background = Background::default();
+        let settings = SampleSettings {
+            camera: &camera,
+            scene,
+            background: &background,
+            limits,
+            filter: Filter::Box,
+            auxiliary: false,
+        };
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut stats = Stats::default();
+        let mut sum = Color::BLACK;
+        for _ in 0..samples {
+            sum += ray_color(
+                &mut rng,
+                &mut stats,
+                &mut (),
+                Ray::new(Vec3::ZERO, direction),
+                settings,
+                &mut |_| {},
+            );
+        }
+        sum / samples as Real
+    }
+
+    fn towards_the_sphere() -> Vec3 {
+        Vec3 {
+            x: 0.0,
+            y: -0.2,
+            z: -1.0,
+        }
+    }
+
+    #[test]
+    fn cancelled_render_returns_none_promptly() {
+        let camera = Camera::builder().build().unwrap();
+        let renderer = Renderer::builder(camera, Scene::new(&[], Vec::new()))
+            .dimensions(1000, 1000)
+            .rays_per_pixel(100_000)
+            .build()
+            .unwrap();
+
+        let start = Instant::now();
+        assert!(renderer
+            .render_with_cancel(&AtomicBool::new(true))
+            .is_none());
+        assert!(
+            start.elapsed() < Duration::from_secs(5),
+            "{:?}",
+            start.elapsed()
+        );
+    }
+
+    #[test]
+    fn crop_leaves_pixels_outside_it_black() {
+        let (width, height) = (16, 12);
+        let crop = Crop {
+            x0: 3,
+            y0: 2,
+            x1: 9,
+            y1: 7,
+        };
+        let camera = Camera::builder().build().unwrap();
+        let image = Renderer::builder(camera, Scene::new(&[], Vec::new()))
+            .background(Background::default())
+            .dimensions(width, height)
+            .rays_per_pixel(2)
+            .crop(crop)
+            .build()
+            .unwrap()
+            .render();
+
+        let mut lit_inside = 0;
+        for (i, color) in image.data.iter().enumerate() {
+            let (x, y) = (i % width, i / width);
+            let is_black = color.r == 0.0 && color.g == 0.0 && color.b == 0.0;
+            if crop.contains(x, y) {
+                lit_inside += usize::from(!is_black);
+            } else {
+                assert!(is_black, "pixel ({}, {}) is {:?}", x, y, color);
+            }
+        }
+        assert!(lit_inside > 0);
+    }
+
+    #[test]
+    fn auxiliary_buffers_leave_the_image_unchanged() {
+        let render = |auxiliary| {
+            let gray = || {
+                Texture::new(texture::Constant {
+                    color: Color {
+                        r: 0.5,
+                        g: 0.5,
+                        b: 0.5,
+                    },
+                })
+            };
+            let sphere = |radius, material| {
+                Object::new(Sphere {
+                    center: Vec3 {
+                        x: 0.0,
+                        y: 0.0,
+                        z: -2.0,
+                    },
+                    radius,
+                    material,
+                    uv_transform: UvTransform::default(),
+                })
+            };
+            let objects = [sphere(0.5, Material::new(Lambertian { albedo: gray() }))];
+            // Where rays scatter in the fog is random, so tracing the first hit again for the
+            // auxiliary buffers would use up random numbers.
+            let fog = ConstantMedium {
+                boundary: sphere(1.5, Material::new(Isotropic { albedo: gray() })),
+                density: 0.5,
+                phase_function: Material::new(Isotropic { albedo: gray() }),
+            };
+            let scene = Scene::new(&objects, Vec::new()).with_media(vec![fog]);
+            Renderer::builder(Camera::builder().build().unwrap(), scene)
+                .background(Background::default())
+                .dimensions(16, 12)
+                .rays_per_pixel(4)
+                .auxiliary(auxiliary)
+                .build()
+                .unwrap()
+                .render_to_rgba8()
+        };
+        assert_eq!(render(false), render(true));
+    }
+
+    #[test]
+    fn uniform_importance_map_traces_rays_per_pixel() {
+        let (width, height) = (5, 3);
+        let renderer = Renderer::builder(
+            Camera::builder().build().unwrap(),
+            Scene::new(&[], Vec::new()),
+        )
+        .dimensions(width, height)
+        .rays_per_pixel(7)
+        .importance_map(vec![1.0; width * height], 100)
+        .num_threads(1)
+        .build()
+        .unwrap();
+        for y in 0..height {
+            for x in 0..width {
+                assert_eq!(renderer.pixel_share(x, y, 0, 7), (0, 7));
+                assert_eq!(renderer.pixel_share(x, y, 3, 2), (3, 2));
+            }
+        }
+    }
+
+    #[test]
+    fn importance_map_must_match_the_image() {
+        let build = |len| {
+            Renderer::builder(
+                Camera::builder().build().unwrap(),
+                Scene::new(&[], Vec::new()),
+            )
+            .dimensions(4, 3)
+            .importance_map(vec![1.0; len], 10)
+            .num_threads(1)
+            .build()
+        };
+        assert!(matches!(build(11), Err(RendererError::ImportanceMapSize)));
+        assert!(matches!(build(13), Err(RendererError::ImportanceMapSize)));
+        assert!(build(12).is_ok());
+    }
+
+    #[test]
+    fn russian_roulette_keeps_the_mean_brightness() {
+        let scene = gray_spheres(false);
+        let without = mean_color(
+            &scene,
+            PathLimits::default(),
+            1,
+            towards_the_sphere(),
+            20_000,
+        );
+        let with = mean_color(
+            &scene,
+            PathLimits {
+                rr_depth: Some(1),
+                ..PathLimits::default()
+            },
+            2,
+            towards_the_sphere(),
+            20_000,
+        );
+        assert!(with.approx_eq(without, 0.02), "{:?} != {:?}", with, without);
+        assert!(without.max_channel() > 0.05, "{:?}", without);
+    }
+
+    #[test]
+    fn russian_roulette_is_off_by_default() {
+        assert_eq!(PathLimits::default().rr_depth, None);
+    }
+
+    /// The light arriving along `ray`, following its path by recursion, as paths were traced before
+    /// they were followed in a loop. Russian roulette and clamping are left out.
+    fn recursive_color<R: Rng>(
+        rng: &mut R,
+        scene: &Scene,
+        background: &Background,
+        ray: &Ray,
+        depth: usize,
+        scatter_pdf: Option<Real>,
+    ) -> Color {
+        if depth == 0 {
+            return Color::BLACK;
+        }
+        let hit: Hit =
+            match scene.hit_counted(rng, ray, Interval::new(0.0, Real::INFINITY), &mut ()) {
+                Some(hit) => hit,
+                None => return background.color(ray),
+            };
+        let emittance = match scatter_pdf {
+            Some(pdf) => {
+                power_heuristic(pdf, scene.light_pdf(ray.origin, ray.direction))
+                    * hit.material.emit(ray, &hit)
+            }
+            None => hit.material.emit(ray, &hit),
+        };
+        let scatter = match hit.material.scatter(rng, ray, &hit) {
+            Some(scatter) => scatter,
+            None => return emittance,
+        };
+        let (direct, scatter_pdf) =
+            match hit
+                .material
+                .reflectance(ray, &hit, scatter.outgoing.direction)
+            {
+                Some(reflectance) => (
+                    sample_lights(rng, &mut Stats::default(), &mut (), ray, &hit, scene),
+                    Some(reflectance.pdf),
+                ),
+                None => (Color::BLACK, None),
+            };
+        let next = Ray::leaving_surface(
+            scatter.outgoing.origin,
+            hit.normal,
+            scatter.outgoing.direction,
+        );
+        emittance
+            + direct
+            + scatter.attenuation
+                * recursive_color(rng, scene, background, &next, depth - 1, scatter_pdf)
+    }
+
+    #[test]
+    fn loop_matches_recursion() {
+        let scene = gray_spheres(true);
+        let background = Background::default();
+        let limits = PathLimits::default();
+        for seed in 0..200 {
+            let direction = Vec3 {
+                x: (seed % 7) as Real * 0.05 - 0.15,
+                y: -0.2,
+                z: -1.0,
+            };
+            let iterative = mean_color(&scene, limits, seed, direction, 1);
+            let recursive = recursive_color(
+                &mut StdRng::seed_from_u64(seed),
+                &scene,
+                &background,
+                &Ray::new(Vec3::ZERO, direction),
+                limits.recursion_depth,
+                None,
+            );
+            assert!(
+                iterative.approx_eq(recursive, 1e-3),
+                "seed {}: {:?} != {:?}",
+                seed,
+                iterative,
+                recursive
+            );
+        }
+    }
+
+    #[test]
+    fn infinite_clamp_leaves_indirect_light_unclamped() {
+        let scene = gray_spheres(true);
+        let mean = |clamp_indirect| {
+            let limits = PathLimits {
+                clamp_indirect,
+                ..PathLimits::default()
+            };
+            mean_color(&scene, limits, 3, towards_the_sphere(), 2_000)
+        };
+        let infinite = mean(Real::INFINITY);
+        // Clamping uses no random numbers, so the paths are the same whatever the limit.
+        let largest = mean(Real::MAX);
+        let low = mean(0.1);
+        assert!(
+            infinite.approx_eq(largest, 0.0),
+            "{:?} != {:?}",
+            infinite,
+            largest
+        );
+        assert!(
+            low.max_channel() < infinite.max_channel() - 0.01,
+            "{:?} isn't darker than {:?}",
+            low,
+            infinite
+        );
+    }
+
+    #[test]
+    fn exhausted_paths_see_the_chosen_color() {
+        // One ray reaches the gray sphere, and the path is cut off where it would scatter.
+        let scene = gray_spheres(false);
+        let end = |exhausted| {
+            let limits = PathLimits {
+                recursion_depth: 1,
+                exhausted,
+                ..PathLimits::default()
+            };
+            mean_color(&scene, limits, 4, towards_the_sphere(), 1)
+        };
+        let red = Color {
+            r: 1.0,
+            g: 0.0,
+            b: 0.0,
+        };
+        let light = end(Exhausted::Light(red));
+        assert!(light.approx_eq(0.5 * red, 1e-6), "{:?}", light);
+        let highlight = end(Exhausted::Highlight(red));
+        assert!(highlight.approx_eq(red, 0.0), "{:?}", highlight);
+    }
+}
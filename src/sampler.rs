@@ -0,0 +1,163 @@
+//! Where in a pixel each of its rays goes.
+
+use crate::real::Real;
+use rand::{Rng, RngCore};
+
+/// A sequence of points in the unit square.
+pub trait Sampler {
+    /// The next point, with both coordinates in `[0, 1)`. `rng` is used for any randomness.
+    fn next_2d(&mut self, rng: &mut dyn RngCore) -> (Real, Real);
+}
+
+/// Independent uniformly random points. They can clump together, which shows up as noise.
+pub struct Random;
+
+impl Sampler for Random {
+    fn next_2d(&mut self, rng: &mut dyn RngCore) -> (Real, Real) {
+        (rng.gen::<Real>(), rng.gen::<Real>())
+    }
+}
+
+/**
+A random point in each cell of a grid, so that every part of the square gets its share.
+
+The grid is as close to square as fits in `count` points. Any points past the end of the grid are
+uniformly random.
+*/
+pub struct Stratified {
+    columns: usize,
+    rows: usize,
+    index: usize,
+}
+
+impl Stratified {
+    pub fn new(count: usize) -> Self {
+        let columns = ((count as Real).sqrt() as usize).max(1);
+        Stratified {
+            columns,
+            rows: count / columns,
+            index: 0,
+        }
+    }
+}
+
+impl Sampler for Stratified {
+    fn next_2d(&mut self, rng: &mut dyn RngCore) -> (Real, Real) {
+        let index = self.index;
+        self.index += 1;
+        if index >= self.columns * self.rows {
+            return (rng.gen::<Real>(), rng.gen::<Real>());
+        }
+        let column = index % self.columns;
+        let row = index / self.columns;
+        (
+            (column as Real + rng.gen::<Real>()) / self.columns as Real,
+            (row as Real + rng.gen::<Real>()) / self.rows as Real,
+        )
+    }
+}
+
+/**
+The radical inverse of `index` in `base`: its digits mirrored around the decimal point, so that
+`0b1101` in base 2 becomes `0.1011`.
+*/
+pub fn radical_inverse(base: usize, mut index: usize) -> Real {
+    let mut result = 0.0;
+    let mut digit_value = 1.0 / base as Real;
+    while index > 0 {
+        result += (index % base) as Real * digit_value;
+        index /= base;
+        digit_value /= base as Real;
+    }
+    result
+}
+
+/**
+The Halton sequence in bases 2 and 3, whose points fill the square evenly however many of them are
+taken.
+
+Every pixel would otherwise use the same points, so each one shifts them by its own random
+`offset`, wrapping around the edges of the square.
+*/
+pub struct Halton {
+    /// The index of the next point. The sequence starts at 1, because the 0th point is `(0, 0)`.
+    index: usize,
+    offset: (Real, Real),
+}
+
+impl Halton {
+    /// The sequence starting at its `first` point (counting from 0), shifted by `offset`.
+    pub fn new(first: usize, offset: (Real, Real)) -> Self {
+        Halton {
+            index: first + 1,
+            offset,
+        }
+    }
+}
+
+impl Sampler for Halton {
+    fn next_2d(&mut self, _: &mut dyn RngCore) -> (Real, Real) {
+        let index = self.index;
+        self.index += 1;
+        let wrap = |value: Real| if value >= 1.0 { value - 1.0 } else { value };
+        (
+            wrap(radical_inverse(2, index) + self.offset.0),
+            wrap(radical_inverse(3, index) + self.offset.1),
+        )
+    }
+}
+
+/// Which [`Sampler`] to place each pixel's rays with.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum SamplePattern {
+    #[default]
+    Random,
+    Stratified,
+    Halton,
+}
+
+impl SamplePattern {
+    /// A sampler for the `count` rays of a pixel starting with its `first` ray.
+    pub fn sampler(self, rng: &mut dyn RngCore, first: usize, count: usize) -> Box<dyn Sampler> {
+        match self {
+            SamplePattern::Random => Box::new(Random),
+            SamplePattern::Stratified => Box::new(Stratified::new(count)),
+            SamplePattern::Halton => {
+                Box::new(Halton::new(first, (rng.gen::<Real>(), rng.gen::<Real>())))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn halton_starts_with_the_radical_inverses() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut halton = Halton::new(0, (0.0, 0.0));
+        let expected = [
+            (1.0 / 2.0, 1.0 / 3.0),
+            (1.0 / 4.0, 2.0 / 3.0),
+            (3.0 / 4.0, 1.0 / 9.0),
+            (1.0 / 8.0, 4.0 / 9.0),
+            (5.0 / 8.0, 7.0 / 9.0),
+        ];
+        for (x, y) in expected {
+            let (u, v) = halton.next_2d(&mut rng);
+            assert!(
+                (u - x).abs() < 1e-6 && (v - y).abs() < 1e-6,
+                "({}, {})",
+                u,
+                v
+            );
+        }
+
+        // Later pixels' rays carry on from where earlier ones stopped, and offsets wrap around.
+        let (u, v) = Halton::new(2, (0.5, 0.5)).next_2d(&mut rng);
+        assert!((u - 0.25).abs() < 1e-6, "{}", u);
+        assert!((v - (1.0 / 9.0 + 0.5)).abs() < 1e-6, "{}", v);
+    }
+}
@@ -0,0 +1,190 @@
+//! Random directions and points with particular distributions.
+
+use crate::{
+    real::{consts, Real},
+    vec3::Vec3,
+};
+use rand::Rng;
+
+/**
+A random unit vector in the hemisphere around +Z, with density `cos(theta) / π` where `theta` is
+the angle from +Z.
+
+Projects a uniformly sampled point on the unit disc up onto the hemisphere (Malley's method).
+*/
+pub fn random_cosine_direction<R: Rng + ?Sized>(rng: &mut R) -> Vec3 {
+    let r_squared = rng.gen::<Real>();
+    let r = r_squared.sqrt();
+    let phi = 2.0 * consts::PI * rng.gen::<Real>();
+    Vec3 {
+        x: r * phi.cos(),
+        y: r * phi.sin(),
+        z: (1.0 - r_squared).sqrt(),
+    }
+}
+
+/// A random unit vector, uniformly distributed over the sphere of directions.
+pub fn random_unit_vector<R: Rng + ?Sized>(rng: &mut R) -> Vec3 {
+    let z = 1.0 - 2.0 * rng.gen::<Real>();
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    let phi = 2.0 * consts::PI * rng.gen::<Real>();
+    Vec3 {
+        x: r * phi.cos(),
+        y: r * phi.sin(),
+        z,
+    }
+}
+
+/**
+A random point inside the unit sphere, uniformly distributed by volume.
+
+The distance from the center is the cube root of a uniform sample, because the volume within
+radius `r` grows as `r³`.
+*/
+pub fn random_in_unit_sphere<R: Rng + ?Sized>(rng: &mut R) -> Vec3 {
+    rng.gen::<Real>().cbrt() * random_unit_vector(rng)
+}
+
+/**
+A random point inside the unit disc in the XY plane, uniformly distributed by area.
+
+The distance from the center is the square root of a uniform sample, because the area within
+radius `r` grows as `r²`.
+*/
+pub fn random_in_unit_disc<R: Rng + ?Sized>(rng: &mut R) -> Vec3 {
+    let r = rng.gen::<Real>().sqrt();
+    let theta = 2.0 * consts::PI * rng.gen::<Real>();
+    Vec3 {
+        x: r * theta.cos(),
+        y: r * theta.sin(),
+        z: 0.0,
+    }
+}
+
+/**
+A random point inside a regular polygon with `sides` corners on the unit circle in the XY plane,
+uniformly distributed by area. One corner points along +Y.
+
+The polygon is split into `sides` equal triangles around the center, and a point is chosen in one
+of them by reflecting points that land beyond the outer edge back inside.
+*/
+pub fn random_in_regular_polygon<R: Rng + ?Sized>(rng: &mut R, sides: u32) -> Vec3 {
+    let corner = |i: u32| {
+        let theta = consts::PI / 2.0 + 2.0 * consts::PI * i as Real / sides as Real;
+        Vec3 {
+            x: theta.cos(),
+            y: theta.sin(),
+            z: 0.0,
+        }
+    };
+    let triangle = rng.gen_range(0..sides);
+    let (mut a, mut b) = (rng.gen::<Real>(), rng.gen::<Real>());
+    if a + b > 1.0 {
+        a = 1.0 - a;
+        b = 1.0 - b;
+    }
+    a * corner(triangle) + b * corner(triangle + 1)
+}
+
+/**
+A random choice of index, each with probability proportional to its weight.
+
+When every weight is zero, every index is equally likely.
+*/
+pub struct Discrete {
+    /// The probability of choosing each index or any before it.
+    cdf: Vec<Real>,
+}
+
+impl Discrete {
+    /// A distribution over the indices of `weights`, which must be non-negative and not empty.
+    pub fn new<I: IntoIterator<Item = Real>>(weights: I) -> Self {
+        let mut total = 0.0;
+        let mut cdf: Vec<Real> = weights
+            .into_iter()
+            .map(|weight| {
+                total += weight;
+                total
+            })
+            .collect();
+        assert!(!cdf.is_empty(), "no weights");
+
+        if total > 0.0 {
+            for value in cdf.iter_mut() {
+                *value /= total;
+            }
+        } else {
+            let count = cdf.len() as Real;
+            for (index, value) in cdf.iter_mut().enumerate() {
+                *value = (index + 1) as Real / count;
+            }
+        }
+        Discrete { cdf }
+    }
+
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        let u = rng.gen::<Real>();
+        self.cdf
+            .partition_point(|&value| value <= u)
+            .min(self.cdf.len() - 1)
+    }
+
+    /// The probability that [`Discrete::sample`] returns `index`.
+    pub fn probability(&self, index: usize) -> Real {
+        match index {
+            0 => self.cdf[0],
+            _ => self.cdf[index] - self.cdf[index - 1],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn cosine_directions_follow_the_cosine() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let samples = 100_000;
+        let bins = 10;
+        let mut histogram = vec![0; bins];
+        for _ in 0..samples {
+            let direction = random_cosine_direction(&mut rng);
+            assert!((direction.norm() - 1.0).abs() < 1e-4, "{:?}", direction);
+            assert!(direction.z >= 0.0, "{:?}", direction);
+            histogram[((direction.z * bins as Real) as usize).min(bins - 1)] += 1;
+        }
+
+        // A density of `cos(theta) / π` over solid angle is a density of `2z` over `z`.
+        for (bin, count) in histogram.into_iter().enumerate() {
+            let (low, high) = (bin as Real / bins as Real, (bin + 1) as Real / bins as Real);
+            let expected = high * high - low * low;
+            let fraction = count as Real / samples as Real;
+            assert!(
+                (fraction - expected).abs() < 0.005,
+                "bin {}: {} instead of {}",
+                bin,
+                fraction,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn points_in_the_unit_sphere_are_inside_it() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let samples = 100_000;
+        let mut inner = 0;
+        for _ in 0..samples {
+            let norm = random_in_unit_sphere(&mut rng).norm();
+            assert!(norm <= 1.0 + 1e-6, "{}", norm);
+            if norm < 0.5 {
+                inner += 1;
+            }
+        }
+        // Uniform by volume, so an eighth are within half the radius.
+        let fraction = inner as Real / samples as Real;
+        assert!((fraction - 0.125).abs() < 0.005, "{}", fraction);
+    }
+}
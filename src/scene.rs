@@ -0,0 +1,1004 @@
+/*!
+Scenes, and scenes described by JSON files.
+
+A [`Scene`] is what gets rendered: the objects, the lights that can be sampled directly, and any
+participating media.
+
+The description types in this module mirror the renderer's objects, materials and textures, but
+can be serialised and deserialised. A [`Description`] is turned into something renderable with
+[`Description::camera`] and [`Description::objects_and_lights`].
+*/
+
+use crate::{
+    bounds::Bounds3,
+    bvh::{Bvh, TraversalCounter},
+    camera::{Camera, CameraError, Projection},
+    color::Color,
+    cylinder::Cylinder,
+    disk::Disk,
+    emitter::{Emitter, IsEmitter},
+    hit::Hit,
+    interval::Interval,
+    material::{self, Material},
+    medium::ConstantMedium,
+    object::{IsObject, Object},
+    plane::Plane,
+    quad::Quad,
+    ray::Ray,
+    real::Real,
+    sampling::Discrete,
+    sphere::Sphere,
+    texture::{self, ColorSpace, GradientAxis, Texture, UvTransform},
+    vec3::Vec3,
+};
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::{fs, io};
+
+pub struct Scene {
+    pub world: Bvh,
+    /// Emissive objects in `world` that are sampled directly. Emission from anything else is only
+    /// found by scattering.
+    pub lights: Vec<Emitter>,
+    /// Volumes that rays can scatter inside, such as fog. Set with [`Scene::with_media`].
+    pub media: Vec<ConstantMedium>,
+    /// Which light to sample, weighted by [`IsEmitter::power`]. `None` when there are no lights.
+    light_distribution: Option<Discrete>,
+}
+
+impl Scene {
+    pub fn new(objects: &[Object], lights: Vec<Emitter>) -> Self {
+        let light_distribution = if lights.is_empty() {
+            None
+        } else {
+            Some(Discrete::new(lights.iter().map(IsEmitter::power)))
+        };
+        Scene {
+            world: Bvh::from(objects),
+            lights,
+            media: Vec::new(),
+            light_distribution,
+        }
+    }
+
+    /// The same scene, with rays scattering inside `media`.
+    #[must_use]
+    pub fn with_media(mut self, media: Vec<ConstantMedium>) -> Self {
+        self.media = media;
+        self
+    }
+
+    /**
+    The first place `ray` hits in `t_range`: a surface in `world`, or a point where it scatters
+    inside one of the `media`, drawn using `rng`. Traversals of `world` are recorded in `counter`,
+    as in [`Bvh::hit_counted`].
+    */
+    pub fn hit_counted<R: Rng + ?Sized, C: TraversalCounter>(
+        &self,
+        rng: &mut R,
+        ray: &Ray,
+        t_range: Interval,
+        counter: &mut C,
+    ) -> Option<Hit> {
+        let mut result = self.world.hit_counted(ray, t_range, counter);
+        for medium in &self.media {
+            let t_range = match &result {
+                Some(hit) => Interval {
+                    end: hit.t,
+                    ..t_range
+                },
+                None => t_range,
+            };
+            if let Some(hit) = medium.sample_hit(rng, ray, t_range) {
+                result = Some(hit);
+            }
+        }
+        result
+    }
+
+    /**
+    The object closest to `point`, and how far away it is, for placing new objects without
+    overlapping the others. See [`Bvh::nearest`].
+    */
+    pub fn nearest_object(&self, point: Vec3) -> Option<(Real, &Object)> {
+        self.world.nearest(point)
+    }
+
+    /**
+    The index of a random light, with brighter and larger lights more likely, or `None` if there
+    are no lights.
+
+    ```
+    use rand::{rngs::StdRng, SeedableRng};
+    use rt_weekend::{
+        color::Color,
+        emitter::Emitter,
+        material::{Light, Material},
+        object::Object,
+        scene::Scene,
+        sphere::Sphere,
+        texture::UvTransform,
+        vec3::Vec3,
+    };
+
+    let light = |x, brightness| Sphere {
+        center: Vec3 { x, y: 0.0, z: 0.0 },
+        radius: 1.0,
+        material: Material::new(Light {
+            brightness,
+            color: Color {
+                r: 1.0,
+                g: 1.0,
+                b: 1.0,
+            },
+        }),
+        uv_transform: UvTransform::default(),
+    };
+    let lights = [light(-2.0, 9.0), light(2.0, 1.0)];
+    let objects: Vec<Object> = lights.iter().cloned().map(Object::new).collect();
+    let scene = Scene::new(&objects, lights.into_iter().map(Emitter::new).collect());
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let samples = 10_000;
+    let bright = (0..samples)
+        .filter(|_| scene.choose_light(&mut rng) == Some(0))
+        .count();
+    let fraction = bright as f64 / samples as f64;
+    assert!((0.88..0.92).contains(&fraction), "{}", fraction);
+    ```
+    */
+    pub fn choose_light(&self, rng: &mut dyn RngCore) -> Option<usize> {
+        self.light_distribution
+            .as_ref()
+            .map(|distribution| distribution.sample(rng))
+    }
+
+    /// A random direction from `origin` toward one of the lights, chosen by [`Scene::choose_light`].
+    pub fn sample_light_direction(&self, rng: &mut dyn RngCore, origin: Vec3) -> Option<Vec3> {
+        let light = &self.lights[self.choose_light(rng)?];
+        Some(light.sample_direction(rng, origin))
+    }
+
+    /// The probability density that [`Scene::sample_light_direction`] returns `direction`.
+    pub fn light_pdf(&self, origin: Vec3, direction: Vec3) -> Real {
+        match &self.light_distribution {
+            Some(distribution) => self
+                .lights
+                .iter()
+                .enumerate()
+                .map(|(index, light)| {
+                    distribution.probability(index) * light.direction_pdf(origin, direction)
+                })
+                .sum(),
+            None => 0.0,
+        }
+    }
+}
+
+/// The surfaces in [`Scene::world`]. Scattering inside media is random, so it's only found by
+/// [`Scene::hit_counted`].
+impl IsObject for Scene {
+    fn hit(&self, ray: &Ray, t_range: Interval) -> Option<Hit> {
+        self.world.hit(ray, t_range)
+    }
+
+    fn bounds(&self) -> Bounds3 {
+        self.world.bounds()
+    }
+
+    fn distance(&self, point: Vec3) -> Real {
+        self.world.distance(point)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Description {
+    pub camera: CameraDescription,
+    pub objects: Vec<NamedObjectDescription>,
+}
+
+/// An object, with an optional name to tell it apart from the others when debugging.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NamedObjectDescription {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub object: ObjectDescription,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CameraDescription {
+    pub look_from: Vec3,
+    pub look_at: Vec3,
+    #[serde(default = "CameraDescription::default_up")]
+    pub up: Vec3,
+    /// Vertical field of view, in degrees.
+    pub v_fov: Real,
+    #[serde(default)]
+    pub aperture: Real,
+    /// Defaults to the distance between `look_from` and `look_at`.
+    #[serde(default)]
+    pub focal_distance: Option<Real>,
+    #[serde(default)]
+    pub projection: Projection,
+}
+
+impl CameraDescription {
+    fn default_up() -> Vec3 {
+        Vec3 {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ObjectDescription {
+    Sphere {
+        center: Vec3,
+        radius: Real,
+        material: MaterialDescription,
+        #[serde(default)]
+        uv_transform: UvTransform,
+    },
+    Cylinder {
+        center: Vec3,
+        radius: Real,
+        height: Real,
+        #[serde(default)]
+        capped: bool,
+        material: MaterialDescription,
+    },
+    Disk {
+        center: Vec3,
+        normal: Vec3,
+        radius: Real,
+        #[serde(default)]
+        inner_radius: Real,
+        material: MaterialDescription,
+    },
+    /// An infinite plane through `point`, facing along `normal`.
+    Plane {
+        point: Vec3,
+        normal: Vec3,
+        material: MaterialDescription,
+    },
+    /// A parallelogram with a corner at `corner` and sides `u` and `v`.
+    Quad {
+        corner: Vec3,
+        u: Vec3,
+        v: Vec3,
+        material: MaterialDescription,
+    },
+    /**
+    Fog or smoke filling `boundary`, which must be convex. See [`ConstantMedium`].
+
+    Only the boundary's shape is used, so its material doesn't matter. `phase_function` is
+    usually `Isotropic`.
+    */
+    Medium {
+        boundary: Box<ObjectDescription>,
+        density: Real,
+        phase_function: MaterialDescription,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum MaterialDescription {
+    Lambertian {
+        albedo: TextureDescription,
+    },
+    LambertianEmissive {
+        albedo: TextureDescription,
+        emission: Color,
+    },
+    OrenNayar {
+        albedo: TextureDescription,
+        roughness: Real,
+    },
+    Metal {
+        albedo: Color,
+        fuzziness: Real,
+    },
+    Microfacet {
+        albedo: Color,
+        roughness: Real,
+        /// The roughness along the direction of increasing `v`, for brushed metals. Defaults to
+        /// `roughness`, which is otherwise the roughness along `u`.
+        #[serde(default)]
+        roughness_v: Option<Real>,
+    },
+    Dielectric {
+        refractive_index: Real,
+        /// How much the refractive index differs between colors. See [`material::Dielectric`].
+        #[serde(default)]
+        dispersion: Real,
+    },
+    Light {
+        brightness: Real,
+        color: Color,
+    },
+    /// See [`material::SpotLight`]. Angles are in degrees.
+    SpotLight {
+        brightness: Real,
+        color: Color,
+        direction: Vec3,
+        inner_angle: Real,
+        outer_angle: Real,
+    },
+    DiffuseLight {
+        emit: TextureDescription,
+    },
+    Isotropic {
+        albedo: TextureDescription,
+    },
+    NormalMap {
+        material: Box<MaterialDescription>,
+        normals: TextureDescription,
+    },
+    Masked {
+        material: Box<MaterialDescription>,
+        opacity: TextureDescription,
+        /// Defaults to 0.5.
+        #[serde(default = "MaterialDescription::default_threshold")]
+        threshold: Real,
+    },
+    /// See [`material::Mix`]. `weight` is the chance of scattering off `first`.
+    Mix {
+        first: Box<MaterialDescription>,
+        second: Box<MaterialDescription>,
+        weight: Real,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum TextureDescription {
+    Constant {
+        color: Color,
+        /// Defaults to linear.
+        #[serde(default = "TextureDescription::default_constant_color_space")]
+        color_space: ColorSpace,
+    },
+    UV,
+    Image {
+        path: String,
+        /// Defaults to sRGB.
+        #[serde(default)]
+        color_space: ColorSpace,
+    },
+    Gradient {
+        start: Color,
+        end: Color,
+        #[serde(default)]
+        along: GradientAxis,
+    },
+    /// A hue and brightness pattern with grid lines, for checking texture coordinates.
+    Polar {
+        #[serde(default = "TextureDescription::default_polar_spacing")]
+        spacing: Real,
+    },
+    Mix {
+        a: Box<TextureDescription>,
+        b: Box<TextureDescription>,
+        factor: MixFactorDescription,
+    },
+}
+
+/// Either a number, or a texture to use as a mask.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MixFactorDescription {
+    Constant(Real),
+    Mask(Box<TextureDescription>),
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+}
+
+impl From<io::Error> for LoadError {
+    fn from(err: io::Error) -> Self {
+        LoadError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for LoadError {
+    fn from(err: serde_json::Error) -> Self {
+        LoadError::Parse(err)
+    }
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Io(err) => err.fmt(f),
+            LoadError::Parse(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl Description {
+    /**
+    The Cornell box: a white room with one red and one green wall, two white boxes, and a light
+    panel in the ceiling. Meant to be rendered against a black background.
+
+    ```
+    use rt_weekend::scene::Description;
+
+    let (objects, lights) = Description::cornell_box().objects_and_lights();
+    assert_eq!(objects.len(), 18);
+    assert!(!lights.is_empty());
+    ```
+    */
+    pub fn cornell_box() -> Self {
+        let diffuse = |r, g, b| MaterialDescription::Lambertian {
+            albedo: TextureDescription::Constant {
+                color: Color { r, g, b },
+                color_space: ColorSpace::Linear,
+            },
+        };
+        let point = |x, y, z| Vec3 { x, y, z };
+        let quad = |name: &str, corner, u, v, material| NamedObjectDescription {
+            name: Some(name.to_string()),
+            object: ObjectDescription::Quad {
+                corner,
+                u,
+                v,
+                material,
+            },
+        };
+        // The six sides of a box from the origin to `size`, facing out, turned `angle` degrees
+        // around the Y axis and moved to `offset`.
+        let cuboid = |name: &str, size: Vec3, angle: Real, offset: Vec3| {
+            let up = point(0.0, 1.0, 0.0);
+            let place = |v: Vec3| v.rotate_around(up, angle.to_radians());
+            let (x, y, z) = (
+                point(size.x, 0.0, 0.0),
+                point(0.0, size.y, 0.0),
+                point(0.0, 0.0, size.z),
+            );
+            [
+                (Vec3::ZERO, y, x),
+                (z, x, y),
+                (Vec3::ZERO, z, y),
+                (x, y, z),
+                (Vec3::ZERO, x, z),
+                (y, z, x),
+            ]
+            .into_iter()
+            .map(|(corner, u, v)| {
+                quad(
+                    name,
+                    place(corner) + offset,
+                    place(u),
+                    place(v),
+                    diffuse(0.73, 0.73, 0.73),
+                )
+            })
+            .collect::<Vec<_>>()
+        };
+
+        let mut objects = vec![
+            quad(
+                "green wall",
+                point(555.0, 0.0, 0.0),
+                point(0.0, 555.0, 0.0),
+                point(0.0, 0.0, 555.0),
+                diffuse(0.12, 0.45, 0.15),
+            ),
+            quad(
+                "red wall",
+                Vec3::ZERO,
+                point(0.0, 555.0, 0.0),
+                point(0.0, 0.0, 555.0),
+                diffuse(0.65, 0.05, 0.05),
+            ),
+            quad(
+                "light",
+                point(343.0, 554.0, 332.0),
+                point(-130.0, 0.0, 0.0),
+                point(0.0, 0.0, -105.0),
+                MaterialDescription::DiffuseLight {
+                    emit: TextureDescription::Constant {
+                        color: Color {
+                            r: 15.0,
+                            g: 15.0,
+                            b: 15.0,
+                        },
+                        color_space: ColorSpace::Linear,
+                    },
+                },
+            ),
+            quad(
+                "floor",
+                Vec3::ZERO,
+                point(555.0, 0.0, 0.0),
+                point(0.0, 0.0, 555.0),
+                diffuse(0.73, 0.73, 0.73),
+            ),
+            quad(
+                "ceiling",
+                point(555.0, 555.0, 555.0),
+                point(-555.0, 0.0, 0.0),
+                point(0.0, 0.0, -555.0),
+                diffuse(0.73, 0.73, 0.73),
+            ),
+            quad(
+                "back wall",
+                point(0.0, 0.0, 555.0),
+                point(555.0, 0.0, 0.0),
+                point(0.0, 555.0, 0.0),
+                diffuse(0.73, 0.73, 0.73),
+            ),
+        ];
+        objects.extend(cuboid(
+            "tall box",
+            point(165.0, 330.0, 165.0),
+            15.0,
+            point(265.0, 0.0, 295.0),
+        ));
+        objects.extend(cuboid(
+            "short box",
+            point(165.0, 165.0, 165.0),
+            -18.0,
+            point(130.0, 0.0, 65.0),
+        ));
+
+        Description {
+            camera: CameraDescription {
+                look_from: point(278.0, 278.0, -800.0),
+                look_at: point(278.0, 278.0, 0.0),
+                up: CameraDescription::default_up(),
+                v_fov: 40.0,
+                aperture: 0.0,
+                focal_distance: None,
+                projection: Projection::default(),
+            },
+            objects,
+        }
+    }
+
+    /// Read a scene description from a JSON file.
+    pub fn load(path: &str) -> Result<Self, LoadError> {
+        let source = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&source)?)
+    }
+
+    /**
+    The camera, for an image `aspect_ratio` times as wide as it's high, in pixels of
+    `pixel_aspect` (see
+    [`CameraBuilder::pixel_aspect`](crate::camera::CameraBuilder::pixel_aspect)).
+    */
+    pub fn camera(&self, aspect_ratio: Real, pixel_aspect: Real) -> Result<Camera, CameraError> {
+        let camera = &self.camera;
+        let builder = Camera::builder()
+            .aspect_ratio(aspect_ratio)
+            .pixel_aspect(pixel_aspect)
+            .v_fov(camera.v_fov)
+            .up(camera.up)
+            .look_from(camera.look_from)
+            .look_at(camera.look_at)
+            .aperture(camera.aperture)
+            .projection(camera.projection);
+        match camera.focal_distance {
+            Some(focal_distance) => builder.focal_distance(focal_distance),
+            None => builder,
+        }
+        .build()
+    }
+
+    /**
+    The scene's objects, and the emissive ones that can be sampled as lights. Media are left out;
+    see [`Description::media`].
+
+    Named objects have their index in the file as their id (see [`Object::with_id`]).
+    */
+    pub fn objects_and_lights(&self) -> (Vec<Object>, Vec<Emitter>) {
+        let mut objects = Vec::with_capacity(self.objects.len());
+        let mut lights = Vec::new();
+        for (id, NamedObjectDescription { name, object }) in self.objects.iter().enumerate() {
+            let built = match object {
+                ObjectDescription::Sphere {
+                    center,
+                    radius,
+                    material,
+                    uv_transform,
+                } => {
+                    let sphere = Sphere {
+                        center: *center,
+                        radius: *radius,
+                        material: material.build(),
+                        uv_transform: *uv_transform,
+                    };
+                    if material.is_emissive() {
+                        lights.push(Emitter::new(sphere.clone()));
+                    }
+                    Object::new(sphere)
+                }
+                ObjectDescription::Quad {
+                    corner,
+                    u,
+                    v,
+                    material,
+                } => {
+                    let quad = Quad {
+                        corner: *corner,
+                        u: *u,
+                        v: *v,
+                        material: material.build(),
+                    };
+                    if material.is_emissive() {
+                        lights.push(Emitter::new(quad.clone()));
+                    }
+                    Object::new(quad)
+                }
+                _ => match object.build() {
+                    Some(built) => built,
+                    None => continue,
+                },
+            };
+            objects.push(match name {
+                Some(_) => built.with_id(id),
+                None => built,
+            });
+        }
+        (objects, lights)
+    }
+
+    /// The scene's media, to add to a [`Scene`] with [`Scene::with_media`].
+    pub fn media(&self) -> Vec<ConstantMedium> {
+        self.objects
+            .iter()
+            .filter_map(|NamedObjectDescription { object, .. }| object.build_medium())
+            .collect()
+    }
+
+    /// The name of the object with id `id`, if it has one.
+    pub fn object_name(&self, id: usize) -> Option<&str> {
+        self.objects.get(id)?.name.as_deref()
+    }
+}
+
+impl ObjectDescription {
+    /// The object, or `None` for a [`ObjectDescription::Medium`], which isn't one.
+    pub fn build(&self) -> Option<Object> {
+        let object = match self {
+            ObjectDescription::Sphere {
+                center,
+                radius,
+                material,
+                uv_transform,
+            } => Object::new(Sphere {
+                center: *center,
+                radius: *radius,
+                material: material.build(),
+                uv_transform: *uv_transform,
+            }),
+            ObjectDescription::Cylinder {
+                center,
+                radius,
+                height,
+                capped,
+                material,
+            } => Object::new(Cylinder {
+                center: *center,
+                radius: *radius,
+                height: *height,
+                capped: *capped,
+                material: material.build(),
+            }),
+            ObjectDescription::Disk {
+                center,
+                normal,
+                radius,
+                inner_radius,
+                material,
+            } => Object::new(Disk {
+                center: *center,
+                normal: *normal,
+                radius: *radius,
+                inner_radius: *inner_radius,
+                material: material.build(),
+            }),
+            ObjectDescription::Plane {
+                point,
+                normal,
+                material,
+            } => Object::new(Plane {
+                point: *point,
+                normal: *normal,
+                material: material.build(),
+            }),
+            ObjectDescription::Quad {
+                corner,
+                u,
+                v,
+                material,
+            } => Object::new(Quad {
+                corner: *corner,
+                u: *u,
+                v: *v,
+                material: material.build(),
+            }),
+            ObjectDescription::Medium { .. } => return None,
+        };
+        Some(object)
+    }
+
+    /// The medium, or `None` for anything but a [`ObjectDescription::Medium`].
+    pub fn build_medium(&self) -> Option<ConstantMedium> {
+        match self {
+            ObjectDescription::Medium {
+                boundary,
+                density,
+                phase_function,
+            } => Some(ConstantMedium {
+                boundary: boundary.build()?,
+                density: *density,
+                phase_function: phase_function.build(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl MaterialDescription {
+    fn default_threshold() -> Real {
+        0.5
+    }
+
+    pub fn is_emissive(&self) -> bool {
+        match self {
+            MaterialDescription::Light { .. }
+            | MaterialDescription::SpotLight { .. }
+            | MaterialDescription::DiffuseLight { .. }
+            | MaterialDescription::LambertianEmissive { .. } => true,
+            MaterialDescription::Lambertian { .. }
+            | MaterialDescription::OrenNayar { .. }
+            | MaterialDescription::Metal { .. }
+            | MaterialDescription::Microfacet { .. }
+            | MaterialDescription::Dielectric { .. }
+            | MaterialDescription::Isotropic { .. } => false,
+            MaterialDescription::NormalMap { material, .. }
+            | MaterialDescription::Masked { material, .. } => material.is_emissive(),
+            MaterialDescription::Mix { first, second, .. } => {
+                first.is_emissive() || second.is_emissive()
+            }
+        }
+    }
+
+    pub fn build(&self) -> Material {
+        match self {
+            MaterialDescription::Lambertian { albedo } => Material::new(material::Lambertian {
+                albedo: albedo.build(),
+            }),
+            MaterialDescription::LambertianEmissive { albedo, emission } => {
+                Material::new(material::LambertianEmissive {
+                    albedo: albedo.build(),
+                    emission: *emission,
+                })
+            }
+            MaterialDescription::OrenNayar { albedo, roughness } => {
+                Material::new(material::OrenNayar::new(albedo.build(), *roughness))
+            }
+            MaterialDescription::Metal { albedo, fuzziness } => {
+                Material::new(material::Metal::new(*albedo, *fuzziness))
+            }
+            MaterialDescription::Microfacet {
+                albedo,
+                roughness,
+                roughness_v,
+            } => Material::new(material::Microfacet::anisotropic(
+                *albedo,
+                *roughness,
+                roughness_v.unwrap_or(*roughness),
+            )),
+            MaterialDescription::Dielectric {
+                refractive_index,
+                dispersion,
+            } => Material::new(material::Dielectric {
+                refractive_index: *refractive_index,
+                dispersion: *dispersion,
+            }),
+            MaterialDescription::Light { brightness, color } => Material::new(material::Light {
+                brightness: *brightness,
+                color: *color,
+            }),
+            MaterialDescription::SpotLight {
+                brightness,
+                color,
+                direction,
+                inner_angle,
+                outer_angle,
+            } => Material::new(material::SpotLight {
+                brightness: *brightness,
+                color: *color,
+                direction: *direction,
+                inner_angle: *inner_angle,
+                outer_angle: *outer_angle,
+            }),
+            MaterialDescription::DiffuseLight { emit } => {
+                Material::new(material::DiffuseLight { emit: emit.build() })
+            }
+            MaterialDescription::Isotropic { albedo } => Material::new(material::Isotropic {
+                albedo: albedo.build(),
+            }),
+            MaterialDescription::NormalMap { material, normals } => {
+                Material::new(material::NormalMap {
+                    material: material.build(),
+                    normals: normals.build(),
+                })
+            }
+            MaterialDescription::Masked {
+                material,
+                opacity,
+                threshold,
+            } => Material::new(material::Masked {
+                material: material.build(),
+                opacity: opacity.build(),
+                threshold: *threshold,
+            }),
+            MaterialDescription::Mix {
+                first,
+                second,
+                weight,
+            } => Material::new(material::Mix {
+                first: first.build(),
+                second: second.build(),
+                weight: *weight,
+            }),
+        }
+    }
+}
+
+impl TextureDescription {
+    fn default_constant_color_space() -> ColorSpace {
+        ColorSpace::Linear
+    }
+
+    fn default_polar_spacing() -> Real {
+        0.1
+    }
+
+    pub fn build(&self) -> Texture {
+        match self {
+            TextureDescription::Constant { color, color_space } => {
+                Texture::new(texture::Constant {
+                    color: color_space.to_linear(*color),
+                })
+            }
+            TextureDescription::UV => Texture::new(texture::UV()),
+            TextureDescription::Image { path, color_space } => {
+                Texture::new(texture::Image::new(path, *color_space))
+            }
+            TextureDescription::Gradient { start, end, along } => Texture::new(texture::Gradient {
+                start: *start,
+                end: *end,
+                along: *along,
+            }),
+            TextureDescription::Polar { spacing } => Texture::new(texture::Polar::new(*spacing)),
+            TextureDescription::Mix { a, b, factor } => Texture::new(texture::Mix {
+                a: a.build(),
+                b: b.build(),
+                factor: match factor {
+                    MixFactorDescription::Constant(factor) => texture::MixFactor::Constant(*factor),
+                    MixFactorDescription::Mask(mask) => texture::MixFactor::Mask(mask.build()),
+                },
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example_scene_round_trips() {
+        let description: Description =
+            serde_json::from_str(include_str!("../scenes/example.json")).unwrap();
+
+        let camera = &description.camera;
+        assert_eq!(
+            camera.look_from,
+            Vec3 {
+                x: 0.0,
+                y: 1.5,
+                z: -6.0
+            }
+        );
+        assert_eq!(camera.v_fov, 35.0);
+        assert_eq!(camera.aperture, 0.05);
+        assert_eq!(camera.focal_distance, None);
+        assert_eq!(camera.up, CameraDescription::default_up());
+
+        assert_eq!(description.objects.len(), 4);
+        match &description.objects[0].object {
+            ObjectDescription::Sphere {
+                radius, material, ..
+            } => {
+                assert_eq!(*radius, 1000.0);
+                assert!(matches!(material, MaterialDescription::Lambertian { .. }));
+            }
+            object => panic!("expected a sphere, got {:?}", object),
+        }
+        assert!(matches!(
+            description.objects[3].object,
+            ObjectDescription::Sphere {
+                material: MaterialDescription::Metal { .. },
+                ..
+            }
+        ));
+
+        let json = serde_json::to_string(&description).unwrap();
+        let reparsed: Description = serde_json::from_str(&json).unwrap();
+        assert_eq!(format!("{:?}", reparsed), format!("{:?}", description));
+    }
+
+    #[test]
+    fn media_are_built_separately_from_objects() {
+        let description: Description = serde_json::from_str(
+            r#"{
+                "camera": {
+                    "look_from": { "x": 0, "y": 0, "z": 0 },
+                    "look_at": { "x": 0, "y": 0, "z": -1 },
+                    "v_fov": 40
+                },
+                "objects": [
+                    {
+                        "type": "Sphere",
+                        "center": { "x": 0, "y": 0, "z": -2 },
+                        "radius": 0.5,
+                        "material": { "type": "Lambertian", "albedo": { "type": "UV" } }
+                    },
+                    {
+                        "name": "fog",
+                        "type": "Medium",
+                        "boundary": {
+                            "type": "Sphere",
+                            "center": { "x": 0, "y": 0, "z": -2 },
+                            "radius": 2,
+                            "material": { "type": "Lambertian", "albedo": { "type": "UV" } }
+                        },
+                        "density": 0.1,
+                        "phase_function": { "type": "Isotropic", "albedo": { "type": "UV" } }
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let (objects, lights) = description.objects_and_lights();
+        assert_eq!(objects.len(), 1);
+        assert!(lights.is_empty());
+
+        let media = description.media();
+        assert_eq!(media.len(), 1);
+        assert_eq!(media[0].density, 0.1);
+        let bounds = media[0].bounds();
+        assert_eq!(
+            *bounds.min(),
+            Vec3 {
+                x: -2.0,
+                y: -2.0,
+                z: -4.0
+            }
+        );
+        assert_eq!(
+            *bounds.max(),
+            Vec3 {
+                x: 2.0,
+                y: 2.0,
+                z: 0.0
+            }
+        );
+    }
+}
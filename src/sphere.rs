@@ -1,22 +1,104 @@
 use crate::{
     bounds::Bounds3,
-    hit::{Face, Hit},
-    material::Material,
+    emitter::IsEmitter,
+    hit::{Face, Hit, Tangents},
+    interval::Interval,
+    material::{IsMaterial, Material},
     object::IsObject,
+    onb::Onb,
     ray::Ray,
-    texture,
+    real::{consts, Real},
+    sampling::random_unit_vector,
+    texture::{self, UvTransform},
     vec3::Vec3,
 };
-use std::f64::consts as f64;
+use rand::{Rng, RngCore};
 
+/**
+A sphere. A negative `radius` turns it inside out, so that its normals point toward the center.
+
+Nesting an inside-out dielectric sphere in a normal one with the same material makes a hollow
+glass shell: rays that reach the inner sphere from the glass see a surface they're leaving.
+*/
+#[derive(Clone)]
 pub struct Sphere {
     pub center: Vec3,
-    pub radius: f64,
+    pub radius: Real,
     pub material: Material,
+    /// Where the texture goes. The default puts the seam at `-x` and the poles on the Y axis.
+    pub uv_transform: UvTransform,
+}
+
+impl Sphere {
+    #[must_use]
+    pub fn with_material(self, material: Material) -> Sphere {
+        Sphere { material, ..self }
+    }
+
+    /**
+    The cosine of the half-angle of the cone of directions from `origin` that hit the sphere, and
+    `1 -` that cosine.
+
+    Returns `None` when `origin` is inside the sphere, where every direction hits it.
+    */
+    fn cone(&self, origin: Vec3) -> Option<(Real, Real)> {
+        let distance_squared = (self.center - origin).norm_squared();
+        let radius_squared = self.radius.powi(2);
+        if distance_squared <= radius_squared {
+            None
+        } else {
+            let sin_squared = radius_squared / distance_squared;
+            let cos_max = (1.0 - sin_squared).sqrt();
+            // Equal to `1 - cos_max`, but accurate for small, distant spheres.
+            Some((cos_max, sin_squared / (1.0 + cos_max)))
+        }
+    }
+
+    /**
+    A random unit vector from `origin` toward the sphere, distributed uniformly over the cone of
+    directions that hit it.
+
+    From inside the sphere, the direction is uniform over all directions.
+    */
+    pub fn random_toward<R: Rng + ?Sized>(&self, rng: &mut R, origin: Vec3) -> Vec3 {
+        match self.cone(origin) {
+            Some((_, one_minus_cos_max)) => {
+                let phi = 2.0 * consts::PI * rng.gen::<Real>();
+                let one_minus_z = rng.gen::<Real>() * one_minus_cos_max;
+                let z = 1.0 - one_minus_z;
+                let r = (one_minus_z * (1.0 + z)).sqrt();
+                Onb::from_w((self.center - origin).unit()).local(Vec3 {
+                    x: r * phi.cos(),
+                    y: r * phi.sin(),
+                    z,
+                })
+            }
+            None => random_unit_vector(rng),
+        }
+    }
+
+    /// The probability density, with respect to solid angle, that [`Sphere::random_toward`]
+    /// returns `direction` from `origin`.
+    pub fn pdf_value(&self, origin: Vec3, direction: Vec3) -> Real {
+        match self.cone(origin) {
+            Some((cos_max, one_minus_cos_max)) => {
+                let direction = match direction.try_unit() {
+                    Some(direction) => direction,
+                    None => return 0.0,
+                };
+                if direction.dot((self.center - origin).unit()) < cos_max {
+                    0.0
+                } else {
+                    1.0 / (2.0 * consts::PI * one_minus_cos_max)
+                }
+            }
+            None => 1.0 / (4.0 * consts::PI),
+        }
+    }
 }
 
 impl IsObject for Sphere {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<Hit> {
+    fn hit(&self, ray: &Ray, t_range: Interval) -> Option<Hit> {
         debug_assert!(
             !(ray.origin.x.is_nan()
                 || ray.origin.y.is_nan()
@@ -28,17 +110,34 @@ impl IsObject for Sphere {
             ray
         );
 
+        let offset = ray.origin - self.center;
         let a = ray.direction.norm_squared();
-        let half_b = ray.direction.dot(ray.origin - self.center);
-        let c = (ray.origin - self.center).norm_squared() - self.radius.powi(2);
-        let discriminant = half_b * half_b - a * c;
+        let half_b = ray.direction.dot(offset);
+        let c = offset.norm_squared() - self.radius.powi(2);
+
+        /*
+        Computed as in "Precision Improvements for Ray/Sphere Intersection" (Ray Tracing Gems,
+        2019), which avoids most of the cancellation in `half_b * half_b - a * c` and in the
+        smaller root. Without this, large spheres are visibly bumpy at `f32` precision.
+        */
+        let closest = offset - (half_b / a) * ray.direction;
+        let discriminant = a * (self.radius.powi(2) - closest.norm_squared());
         if discriminant < 0.0 {
             None
         } else {
-            let mut t = (-half_b - discriminant.sqrt()) / a;
-            if t < t_min || t_max < t {
-                t = (-half_b + discriminant.sqrt()) / a;
-                if t < t_min || t_max < t {
+            let q = -(half_b + half_b.signum() * discriminant.sqrt());
+            let (near, far) = {
+                let (t_0, t_1) = (q / a, c / q);
+                if t_0 <= t_1 {
+                    (t_0, t_1)
+                } else {
+                    (t_1, t_0)
+                }
+            };
+            let mut t = near;
+            if !t_range.contains(t) {
+                t = far;
+                if !t_range.contains(t) {
                     return None;
                 }
             }
@@ -76,23 +175,40 @@ impl IsObject for Sphere {
                 normal,
             );
 
-            let phi = (-normal.z).atan2(normal.x) + f64::PI;
+            let phi = (-normal.z).atan2(normal.x) + consts::PI;
             debug_assert!(phi >= 0.0, "phi: {:?}, normal: {:?}", phi, normal);
-            debug_assert!(phi < 2.0 * f64::PI, "phi: {:?}", phi);
+            debug_assert!(phi <= 2.0 * consts::PI, "phi: {:?}", phi);
 
-            let theta = (-normal.y).acos();
+            // Reaches `PI` exactly at the top pole.
+            let theta = (-normal.y).clamp(-1.0, 1.0).acos();
             debug_assert!(theta >= 0.0, "theta: {:?}", theta);
-            debug_assert!(theta < f64::PI, "theta: {:?}", theta);
+            debug_assert!(theta <= consts::PI, "theta: {:?}", theta);
 
-            let u = phi / (2.0 * f64::PI);
+            let u = phi / (2.0 * consts::PI);
             debug_assert!(u >= 0.0, "u: {:?}", u);
-            debug_assert!(u < 1.0, "u: {:?}", u);
+            debug_assert!(u <= 1.0, "u: {:?}", u);
 
-            let v = theta / f64::PI;
+            let v = theta / consts::PI;
             debug_assert!(v >= 0.0, "v: {:?}", v);
-            debug_assert!(v < 1.0, "v: {:?}", v);
+            debug_assert!(v <= 1.0, "v: {:?}", v);
+
+            let texture_coord = self.uv_transform.apply(texture::Coord { u, v });
 
-            let texture_coord = texture::Coord { u, v };
+            // `u` runs around the Y axis and `v` from the bottom pole to the top. Neither is
+            // defined at the poles. They're scaled to how far the point moves as `u` and `v`
+            // change, which a rotated texture needs to mix them correctly.
+            let around = Vec3 {
+                x: outward_normal.z,
+                y: 0.0,
+                z: -outward_normal.x,
+            };
+            let radius = self.radius.abs();
+            let tangents = around.try_unit().map(|tangent| {
+                self.uv_transform.apply_to_tangents(Tangents {
+                    tangent: 2.0 * consts::PI * radius * around,
+                    bitangent: consts::PI * radius * outward_normal.cross(tangent),
+                })
+            });
 
             Some(Hit {
                 point,
@@ -101,16 +217,195 @@ impl IsObject for Sphere {
                 face,
                 material: self.material.clone(),
                 texture_coord,
+                tangents,
+                object_id: None,
             })
         }
     }
 
+    fn distance(&self, point: Vec3) -> Real {
+        ((point - self.center).norm() - self.radius.abs()).max(0.0)
+    }
+
     fn bounds(&self) -> Bounds3 {
+        let radius = self.radius.abs();
         let corner = Vec3 {
-            x: self.radius,
-            y: self.radius,
-            z: self.radius,
+            x: radius,
+            y: radius,
+            z: radius,
         };
         Bounds3::new(self.center - corner, self.center + corner)
     }
 }
+
+impl IsEmitter for Sphere {
+    fn sample_direction(&self, rng: &mut dyn RngCore, origin: Vec3) -> Vec3 {
+        self.random_toward(rng, origin)
+    }
+
+    fn direction_pdf(&self, origin: Vec3, direction: Vec3) -> Real {
+        self.pdf_value(origin, direction)
+    }
+
+    /// The luminance emitted where rays along each axis hit the sphere, averaged, times its area.
+    fn power(&self) -> Real {
+        let radius = self.radius.abs();
+        let axes = [
+            Vec3 {
+                x: 1.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            Vec3 {
+                x: 0.0,
+                y: 1.0,
+                z: 0.0,
+            },
+            Vec3 {
+                x: 0.0,
+                y: 0.0,
+                z: 1.0,
+            },
+        ];
+        let emitted: Real = axes
+            .iter()
+            .flat_map(|&axis| [axis, -axis])
+            .filter_map(|axis| {
+                let ray = Ray::new(self.center + 2.0 * radius * axis, -axis);
+                let hit = self.hit(&ray, Interval::new(0.0, Real::INFINITY))?;
+                Some(hit.material.emit(&ray, &hit).luminance())
+            })
+            .sum();
+        emitted / 6.0 * 4.0 * consts::PI * radius * radius
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{material::Normals, vec3::assert_vec3_eq};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn unit_sphere() -> Sphere {
+        Sphere {
+            center: Vec3::ZERO,
+            radius: 1.0,
+            material: Material::new(Normals()),
+            uv_transform: UvTransform::default(),
+        }
+    }
+
+    /// Checks that directions sampled from `origin` hit the sphere and that the pdf integrates to 1.
+    fn check_sampling(origin: Vec3) {
+        let sphere = unit_sphere();
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for _ in 0..1_000 {
+            let direction = sphere.random_toward(&mut rng, origin);
+            assert!((direction.norm() - 1.0).abs() < 1e-4, "{:?}", direction);
+            assert!(sphere.pdf_value(origin, direction) > 0.0, "{:?}", direction);
+            let ray = Ray::new(origin, direction);
+            assert!(
+                sphere
+                    .hit(&ray, Interval::new(0.0, Real::INFINITY))
+                    .is_some(),
+                "{:?}",
+                direction
+            );
+        }
+
+        // Monte Carlo estimate of the integral of the pdf over all directions.
+        let samples = 100_000;
+        let integral = (0..samples)
+            .map(|_| sphere.pdf_value(origin, random_unit_vector(&mut rng)))
+            .sum::<Real>()
+            * 4.0
+            * consts::PI
+            / samples as Real;
+        assert!((integral - 1.0).abs() < 0.05, "{}", integral);
+    }
+
+    #[test]
+    fn sampling_from_outside_covers_the_cone() {
+        check_sampling(Vec3 {
+            x: 0.0,
+            y: 1.0,
+            z: 1.5,
+        });
+    }
+
+    #[test]
+    fn sampling_from_inside_covers_all_directions() {
+        check_sampling(Vec3 {
+            x: 0.2,
+            y: -0.3,
+            z: 0.1,
+        });
+    }
+
+    #[test]
+    fn tangents_are_perpendicular_to_the_normal() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for uv_transform in [
+            UvTransform::default(),
+            UvTransform {
+                rotation: 30.0,
+                offset: texture::Coord { u: 0.1, v: 0.0 },
+            },
+        ] {
+            let sphere = Sphere {
+                uv_transform,
+                ..unit_sphere()
+            };
+            for _ in 0..1000 {
+                // From a random point outside, toward a random point inside.
+                let origin = 3.0 * random_unit_vector(&mut rng);
+                let target = 0.9 * random_unit_vector(&mut rng);
+                let ray = Ray::new(origin, target - origin);
+                let hit = sphere
+                    .hit(&ray, Interval::new(0.0, Real::INFINITY))
+                    .unwrap();
+                let tangents = hit.tangents.unwrap();
+                for direction in [tangents.tangent, tangents.bitangent] {
+                    assert!(direction.norm() > 0.1, "{:?}", direction);
+                    assert!(
+                        direction.unit().dot(hit.normal).abs() < 1e-4,
+                        "{:?} at {:?}",
+                        direction,
+                        hit.point
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn negative_radius_turns_the_sphere_inside_out() {
+        let inside_out = Sphere {
+            radius: -1.0,
+            ..unit_sphere()
+        };
+        let t_range = Interval::new(0.001, Real::INFINITY);
+        let x = Vec3 {
+            x: 1.0,
+            y: 0.0,
+            z: 0.0,
+        };
+
+        // From the center, the surface faces inward, toward the ray.
+        let from_center = inside_out.hit(&Ray::new(Vec3::ZERO, x), t_range).unwrap();
+        assert!(matches!(from_center.face, Face::Front));
+        assert_vec3_eq!(from_center.normal, -x);
+
+        // From outside, it's the back of the surface.
+        let from_outside = inside_out.hit(&Ray::new(3.0 * x, -x), t_range).unwrap();
+        assert!((from_outside.t - 2.0).abs() < 1e-6);
+        assert!(matches!(from_outside.face, Face::Back));
+        assert_vec3_eq!(from_outside.normal, x);
+
+        let bounds = inside_out.bounds();
+        assert_vec3_eq!(*bounds.min(), *unit_sphere().bounds().min());
+        assert_vec3_eq!(*bounds.max(), *unit_sphere().bounds().max());
+        assert!(bounds.max().x > bounds.min().x);
+    }
+}
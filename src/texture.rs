@@ -1,10 +1,52 @@
-use crate::color::Color;
-use image::{GenericImageView, RgbImage};
-use std::sync::Arc;
+use crate::{color::Color, hit::Tangents, real::Real};
+use image::{ImageResult, RgbImage};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock, Weak},
+};
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
 pub struct Coord {
-    pub u: f64,
-    pub v: f64,
+    pub u: Real,
+    pub v: Real,
+}
+
+/**
+Moves a texture over a surface without editing the image: texture coordinates are rotated about
+`(0.5, 0.5)`, then offset, then wrapped back into `[0, 1)`.
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct UvTransform {
+    /// Counterclockwise, in degrees.
+    #[serde(default)]
+    pub rotation: Real,
+    #[serde(default)]
+    pub offset: Coord,
+}
+
+impl UvTransform {
+    /// Where `coord` moves to. The default, zero transform leaves it exactly as it is.
+    pub fn apply(&self, coord: Coord) -> Coord {
+        if *self == UvTransform::default() {
+            return coord;
+        }
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let (u, v) = (coord.u - 0.5, coord.v - 0.5);
+        Coord {
+            u: (0.5 + cos * u - sin * v + self.offset.u).rem_euclid(1.0),
+            v: (0.5 + sin * u + cos * v + self.offset.v).rem_euclid(1.0),
+        }
+    }
+
+    /// The directions of increasing transformed coordinates, given those of the original ones.
+    pub fn apply_to_tangents(&self, tangents: Tangents) -> Tangents {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        Tangents {
+            tangent: cos * tangents.tangent - sin * tangents.bitangent,
+            bitangent: sin * tangents.tangent + cos * tangents.bitangent,
+        }
+    }
 }
 
 pub trait IsTexture: Send + Sync {
@@ -47,38 +89,386 @@ impl IsTexture for UV {
     }
 }
 
+/**
+Shows how texture coordinates are laid out, for finding seams and distortion. `u` sets the hue and
+`v` the brightness, with grid lines every `spacing` along both. Construct with [`Polar::new`].
+*/
+pub struct Polar {
+    pub spacing: Real,
+    /// The width of the grid lines, in texture coordinates.
+    pub line_width: Real,
+    pub line_color: Color,
+}
+
+impl Polar {
+    /// Black grid lines, a twentieth of `spacing` wide.
+    pub fn new(spacing: Real) -> Self {
+        Polar {
+            spacing,
+            line_width: spacing / 20.0,
+            line_color: Color::BLACK,
+        }
+    }
+
+    fn on_line(&self, coordinate: Real) -> bool {
+        let nearest_line = (coordinate / self.spacing).round() * self.spacing;
+        (coordinate - nearest_line).abs() <= self.line_width / 2.0
+    }
+}
+
+impl IsTexture for Polar {
+    fn color(&self, c: &Coord) -> Color {
+        if self.on_line(c.u) || self.on_line(c.v) {
+            return self.line_color;
+        }
+
+        // A fully saturated hue, going red, yellow, green, cyan, blue, magenta and back to red.
+        let hue = 6.0 * c.u.rem_euclid(1.0);
+        let channel = |offset: Real| {
+            let distance = (hue - offset).rem_euclid(6.0);
+            (2.0 - distance.min(6.0 - distance)).clamp(0.0, 1.0)
+        };
+        // Dark enough at `v == 0` to tell apart from bright, but not so dark the hue is lost.
+        let value = 0.25 + 0.75 * c.v.clamp(0.0, 1.0);
+        Color {
+            r: value * channel(0.0),
+            g: value * channel(2.0),
+            b: value * channel(4.0),
+        }
+    }
+}
+
+/// Which texture coordinate a [`Gradient`] varies along.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum GradientAxis {
+    #[default]
+    U,
+    V,
+}
+
+/// Blends linearly from `start`, where the coordinate is `0`, to `end`, where it's `1`.
+pub struct Gradient {
+    pub start: Color,
+    pub end: Color,
+    pub along: GradientAxis,
+}
+
+impl IsTexture for Gradient {
+    fn color(&self, c: &Coord) -> Color {
+        let t = match self.along {
+            GradientAxis::U => c.u,
+            GradientAxis::V => c.v,
+        };
+        self.start.lerp(self.end, t)
+    }
+}
+
+/// How much of its second texture a [`Mix`] shows.
+pub enum MixFactor {
+    /// The same everywhere.
+    Constant(Real),
+    /// Varies over the surface. The average of the mask's channels is used, so it's usually
+    /// grayscale.
+    Mask(Texture),
+}
+
+/// Blends two textures: `a` where the factor is `0`, and `b` where it's `1`.
+pub struct Mix {
+    pub a: Texture,
+    pub b: Texture,
+    pub factor: MixFactor,
+}
+
+impl IsTexture for Mix {
+    fn color(&self, c: &Coord) -> Color {
+        let t = match &self.factor {
+            MixFactor::Constant(factor) => *factor,
+            MixFactor::Mask(mask) => {
+                let color = mask.color(c);
+                (color.r + color.g + color.b) / 3.0
+            }
+        };
+        self.a.color(c).lerp(self.b.color(c), t)
+    }
+}
+
+/// How stored color values relate to the linear colors the renderer works with.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum ColorSpace {
+    /// Encoded with the sRGB transfer function, like most 8-bit images.
+    #[default]
+    Srgb,
+    /// Already linear, like normal maps and data textures.
+    Linear,
+}
+
+impl ColorSpace {
+    /// Convert a color stored in this color space to linear RGB.
+    pub fn to_linear(&self, color: Color) -> Color {
+        match self {
+            ColorSpace::Srgb => color.srgb_to_linear(),
+            ColorSpace::Linear => color,
+        }
+    }
+}
+
+/**
+Decoded images, by path, so that textures loaded from the same file share one copy.
+
+Entries are weak so that an image is freed once no texture uses it.
+*/
+static IMAGE_CACHE: OnceLock<Mutex<HashMap<String, Weak<RgbImage>>>> = OnceLock::new();
+
+/// The image at `path`, decoded once and shared by everything that loads it.
+fn load_cached(path: &str) -> ImageResult<Arc<RgbImage>> {
+    let cache = IMAGE_CACHE.get_or_init(Default::default);
+    if let Some(image) = cache
+        .lock()
+        .unwrap()
+        .get(path)
+        .and_then(|image| image.upgrade())
+    {
+        return Ok(image);
+    }
+
+    // Decoded without holding the lock, so that different files can load in parallel. If two
+    // threads load the same file at once, the last one to finish is cached.
+    let image = Arc::new(
+        image::io::Reader::open(path)?
+            .with_guessed_format()?
+            .decode()?
+            .to_rgb8(),
+    );
+    cache
+        .lock()
+        .unwrap()
+        .insert(path.to_string(), Arc::downgrade(&image));
+    Ok(image)
+}
+
+/**
+A texture read from an image file, in any format the `image` crate can decode. The format is
+recognised from the file's contents, falling back to its extension.
+
+Images loaded from the same path share one decoded copy.
+*/
 pub struct Image {
-    image: RgbImage,
-    width: f64,
-    height: f64,
+    image: Arc<RgbImage>,
+    width: Real,
+    height: Real,
+    /// The linear value of each 8-bit channel value.
+    levels: [Real; 256],
 }
 
 impl Image {
-    pub fn new(path: &str) -> Self {
-        let reader = image::io::Reader::open(path)
-            .unwrap_or_else(|err| panic!("failed to open image: {}", err));
-        let image = reader
-            .decode()
-            .unwrap_or_else(|err| panic!("failed to decode image: {}", err));
-        let width = image.width() as f64;
-        let height = image.height() as f64;
-        Image {
+    /// Like [`Image::try_new`], but panics if the image can't be loaded.
+    pub fn new(path: &str, color_space: ColorSpace) -> Self {
+        Image::try_new(path, color_space)
+            .unwrap_or_else(|err| panic!("failed to load image {}: {}", path, err))
+    }
+
+    /// Load the image at `path`, whose pixels are stored in `color_space`.
+    pub fn try_new(path: &str, color_space: ColorSpace) -> ImageResult<Self> {
+        let image = load_cached(path)?;
+        let width = image.width() as Real;
+        let height = image.height() as Real;
+
+        let mut levels = [0.0; 256];
+        for (level, value) in levels.iter_mut().enumerate() {
+            let value_real = level as Real / 255.0;
+            *value = color_space
+                .to_linear(Color {
+                    r: value_real,
+                    g: value_real,
+                    b: value_real,
+                })
+                .r;
+        }
+
+        Ok(Image {
             width,
             height,
-            image: image.to_rgb8(),
-        }
+            image,
+            levels,
+        })
+    }
+
+    /// The decoded pixels, shared with any other textures loaded from the same path.
+    pub fn pixels(&self) -> &Arc<RgbImage> {
+        &self.image
     }
 }
 
 impl IsTexture for Image {
     fn color(&self, c: &Coord) -> Color {
-        let x = (c.u * self.width).trunc() as u32;
-        let y = ((1.0 - c.v) * self.height).trunc() as u32;
+        // `u == 1` and `v == 0` would land just past the last pixel.
+        let x = ((c.u * self.width).trunc() as u32).min(self.image.width() - 1);
+        let y = (((1.0 - c.v) * self.height).trunc() as u32).min(self.image.height() - 1);
         let pixel = self.image.get_pixel(x, y);
         Color {
-            r: pixel[0] as f64 / 255.0,
-            g: pixel[1] as f64 / 255.0,
-            b: pixel[2] as f64 / 255.0,
+            r: self.levels[pixel[0] as usize],
+            g: self.levels[pixel[1] as usize],
+            b: self.levels[pixel[2] as usize],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Color = Color {
+        r: 1.0,
+        g: 0.0,
+        b: 0.0,
+    };
+    const BLUE: Color = Color {
+        r: 0.0,
+        g: 0.0,
+        b: 1.0,
+    };
+
+    #[test]
+    fn gradient_endpoints_and_midpoint() {
+        let along_v = Gradient {
+            start: RED,
+            end: BLUE,
+            along: GradientAxis::V,
+        };
+        let at = |u, v| along_v.color(&Coord { u, v });
+        assert!(at(0.3, 0.0).approx_eq(RED, 1e-6));
+        assert!(at(0.3, 1.0).approx_eq(BLUE, 1e-6));
+        assert!(at(0.3, 0.5).approx_eq(
+            Color {
+                r: 0.5,
+                g: 0.0,
+                b: 0.5
+            },
+            1e-6
+        ));
+        // Only `v` matters.
+        assert!(at(0.9, 0.5).approx_eq(at(0.1, 0.5), 1e-6));
+    }
+
+    #[test]
+    fn polar_grid_lines_have_the_line_color() {
+        let mut polar = Polar::new(0.25);
+        polar.line_color = Color {
+            r: 0.1,
+            g: 0.2,
+            b: 0.3,
+        };
+        let at = |u, v| polar.color(&Coord { u, v });
+
+        for (u, v) in [(0.5, 0.6), (0.3, 0.75), (0.0, 0.1), (0.251, 0.4)] {
+            assert!(at(u, v).approx_eq(polar.line_color, 0.0), "({}, {})", u, v);
+        }
+        // Between the lines, it's a bright hue: red at `u = 0`.
+        let between = at(0.05, 0.875);
+        assert!(!between.approx_eq(polar.line_color, 0.1));
+        assert!(
+            between.r > 0.9 && between.g < 0.5 && between.b == 0.0,
+            "{:?}",
+            between
+        );
+    }
+
+    #[test]
+    fn srgb_images_are_decoded_to_linear() {
+        let path = std::env::temp_dir().join(format!(
+            "{}-srgb_images_are_decoded_to_linear.png",
+            std::process::id()
+        ));
+        let path = path.to_str().unwrap();
+        RgbImage::from_pixel(1, 1, image::Rgb([188, 255, 0]))
+            .save(path)
+            .unwrap();
+        let srgb = Image::new(path, ColorSpace::Srgb);
+        let linear = Image::new(path, ColorSpace::Linear);
+        std::fs::remove_file(path).unwrap();
+
+        let coord = Coord { u: 0.5, v: 0.5 };
+        // Byte 188 is sRGB's middle gray.
+        let color = srgb.color(&coord);
+        assert!((color.r - 0.5).abs() < 0.005, "{}", color.r);
+        assert_eq!((color.g, color.b), (1.0, 0.0));
+        assert!((linear.color(&coord).r - 188.0 / 255.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn mix_factor_endpoints() {
+        let mix = |factor| Mix {
+            a: Texture::new(Constant { color: RED }),
+            b: Texture::new(Constant { color: BLUE }),
+            factor,
+        };
+        let coord = Coord { u: 0.2, v: 0.7 };
+        assert!(mix(MixFactor::Constant(0.0))
+            .color(&coord)
+            .approx_eq(RED, 0.0));
+        assert!(mix(MixFactor::Constant(1.0))
+            .color(&coord)
+            .approx_eq(BLUE, 0.0));
+        assert!(mix(MixFactor::Constant(0.25))
+            .color(&coord)
+            .approx_eq(RED.lerp(BLUE, 0.25), 1e-6));
+
+        // A mask uses the mean of its channels, so `UV` gives `(u + v) / 3`.
+        let masked = mix(MixFactor::Mask(Texture::new(UV())));
+        assert!(masked.color(&coord).approx_eq(RED.lerp(BLUE, 0.3), 1e-6));
+        let black = mix(MixFactor::Mask(Texture::new(Constant {
+            color: Color::BLACK,
+        })));
+        assert!(black.color(&coord).approx_eq(RED, 0.0));
+    }
+
+    #[test]
+    fn uv_transform_offsets_with_wraparound() {
+        let coords = [
+            Coord { u: 0.0, v: 0.0 },
+            Coord { u: 0.3, v: 0.9 },
+            Coord { u: 1.0, v: 1.0 },
+        ];
+        for coord in coords {
+            assert_eq!(UvTransform::default().apply(coord), coord);
+        }
+
+        let offset = UvTransform {
+            rotation: 0.0,
+            offset: Coord { u: 0.5, v: 0.0 },
+        };
+        for (u, expected) in [(0.2, 0.7), (0.5, 0.0), (0.75, 0.25)] {
+            let moved = offset.apply(Coord { u, v: 0.4 });
+            assert!((moved.u - expected).abs() < 1e-6, "{} -> {}", u, moved.u);
+            assert!((moved.v - 0.4).abs() < 1e-6);
         }
     }
+
+    #[test]
+    fn images_from_the_same_path_share_pixels() {
+        let path = std::env::temp_dir().join(format!(
+            "{}-images_from_the_same_path_share_pixels.png",
+            std::process::id()
+        ));
+        let path = path.to_str().unwrap();
+        RgbImage::from_pixel(2, 2, image::Rgb([10, 20, 30]))
+            .save(path)
+            .unwrap();
+
+        let first = Image::new(path, ColorSpace::Srgb);
+        // The color space is applied when reading, so it doesn't need a separate copy.
+        let second = Image::new(path, ColorSpace::Linear);
+        assert!(Arc::ptr_eq(first.pixels(), second.pixels()));
+
+        // Once nothing uses the image, it's decoded again.
+        let pixels = Arc::downgrade(first.pixels());
+        drop((first, second));
+        assert!(pixels.upgrade().is_none());
+        let third = Image::new(path, ColorSpace::Srgb);
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(third.pixels().get_pixel(1, 1), &image::Rgb([10, 20, 30]));
+
+        drop(third);
+        assert!(Image::try_new(path, ColorSpace::Srgb).is_err());
+    }
 }
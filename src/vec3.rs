@@ -1,12 +1,32 @@
 use rand::{distributions::uniform::SampleRange, Rng};
+use serde::{Deserialize, Serialize};
 
-use crate::axis::Axis3;
+use crate::{axis::Axis3, real::Real};
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// Asserts that two vectors are equal to within `epsilon` (`1e-6` if omitted) in every component.
+#[cfg(test)]
+macro_rules! assert_vec3_eq {
+    ($left:expr, $right:expr) => {
+        $crate::vec3::assert_vec3_eq!($left, $right, 1e-6)
+    };
+    ($left:expr, $right:expr, $epsilon:expr) => {{
+        let (left, right): ($crate::vec3::Vec3, $crate::vec3::Vec3) = ($left, $right);
+        assert!(
+            left.approx_eq(right, $epsilon),
+            "expected {:?} to be approximately {:?}",
+            left,
+            right
+        );
+    }};
+}
+#[cfg(test)]
+pub(crate) use assert_vec3_eq;
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub struct Vec3 {
-    pub x: f64,
-    pub y: f64,
-    pub z: f64,
+    pub x: Real,
+    pub y: Real,
+    pub z: Real,
 }
 
 impl Vec3 {
@@ -17,17 +37,17 @@ impl Vec3 {
     };
 
     /// Euclidian norm squared.
-    pub fn norm_squared(&self) -> f64 {
+    pub fn norm_squared(&self) -> Real {
         self.x.powi(2) + self.y.powi(2) + self.z.powi(2)
     }
 
     /// Euclidian norm.
-    pub fn norm(&self) -> f64 {
+    pub fn norm(&self) -> Real {
         self.norm_squared().sqrt()
     }
 
     /// Dot product.
-    pub fn dot(&self, v: Vec3) -> f64 {
+    pub fn dot(&self, v: Vec3) -> Real {
         self.x * v.x + self.y * v.y + self.z * v.z
     }
 
@@ -50,8 +70,59 @@ impl Vec3 {
         *self / norm
     }
 
+    /// Like [`Vec3::unit`], but returns `None` for the zero vector instead of dividing by zero.
+    pub fn try_unit(&self) -> Option<Vec3> {
+        let norm = self.norm();
+        if norm == 0.0 {
+            None
+        } else {
+            Some(*self / norm)
+        }
+    }
+
+    /// Linear interpolation: `self` when `t = 0`, `other` when `t = 1`.
+    #[must_use]
+    pub fn lerp(&self, other: Vec3, t: Real) -> Vec3 {
+        (1.0 - t) * *self + t * other
+    }
+
+    /// Component-wise minimum.
+    #[must_use]
+    pub fn min(&self, other: Vec3) -> Vec3 {
+        Vec3 {
+            x: self.x.min(other.x),
+            y: self.y.min(other.y),
+            z: self.z.min(other.z),
+        }
+    }
+
+    /// Component-wise maximum.
+    #[must_use]
+    pub fn max(&self, other: Vec3) -> Vec3 {
+        Vec3 {
+            x: self.x.max(other.x),
+            y: self.y.max(other.y),
+            z: self.z.max(other.z),
+        }
+    }
+
+    /// Component-wise absolute value.
+    #[must_use]
+    pub fn abs(&self) -> Vec3 {
+        Vec3 {
+            x: self.x.abs(),
+            y: self.y.abs(),
+            z: self.z.abs(),
+        }
+    }
+
+    /// The component along `axis`. Equivalent to `self[axis]`.
+    pub fn component(&self, axis: Axis3) -> Real {
+        self[axis]
+    }
+
     /// Generate a random vector with all components in the specified range.
-    pub fn gen_range<R: Rng, S: SampleRange<f64> + Clone>(rng: &mut R, range: S) -> Self {
+    pub fn gen_range<R: Rng + ?Sized, S: SampleRange<Real> + Clone>(rng: &mut R, range: S) -> Self {
         Vec3 {
             x: rng.gen_range(range.clone()),
             y: rng.gen_range(range.clone()),
@@ -61,10 +132,17 @@ impl Vec3 {
 
     /// Returns `true` if the vector is near zero in all dimensions.
     pub fn near_zero(&self) -> bool {
-        const TOLERANCE: f64 = 1e-8;
+        const TOLERANCE: Real = 1e-8;
         self.x.abs() < TOLERANCE && self.y.abs() < TOLERANCE && self.z.abs() < TOLERANCE
     }
 
+    /// Whether each component differs from `other`'s by at most `epsilon`.
+    pub fn approx_eq(&self, other: Vec3, epsilon: Real) -> bool {
+        (self.x - other.x).abs() <= epsilon
+            && (self.y - other.y).abs() <= epsilon
+            && (self.z - other.z).abs() <= epsilon
+    }
+
     /// Negate the vector.
     #[must_use]
     pub fn negate(&self) -> Self {
@@ -85,11 +163,11 @@ impl Vec3 {
 
     # Arguments
 
-    * `normal` - the surface normal (points toward the 'outside')
-    * `eta_from` - the refractive index of the outer substance
-    * `eta_to` - the refractive index of the inner substance
+    * `normal` - the surface normal (points against `self`, into the substance the ray is leaving)
+    * `eta_from` - the refractive index of the substance the ray is leaving
+    * `eta_to` - the refractive index of the substance the ray is entering
     */
-    pub fn refract(&self, normal: &Vec3, eta_from: f64, eta_to: f64) -> Option<Vec3> {
+    pub fn refract(&self, normal: &Vec3, eta_from: Real, eta_to: Real) -> Option<Vec3> {
         assert!(
             (1.0 - self.norm()).abs() < 0.001,
             "expected self to have a norm of 1.0, got {}",
@@ -102,7 +180,7 @@ impl Vec3 {
         );
 
         // theta is the angle between the `self` (the incoming ray) and the surface normal
-        let cos_theta: f64 = self.negate().dot(*normal);
+        let cos_theta: Real = self.negate().dot(*normal);
         let sin_theta = (1.0 - cos_theta.powi(2)).sqrt();
         let refraction_ratio = eta_from / eta_to;
         if refraction_ratio * sin_theta > 1.0 {
@@ -117,10 +195,54 @@ impl Vec3 {
         }
     }
 
+    /**
+    Two unit vectors that, together with `self`, form an orthonormal basis.
+
+    `self` must be a unit vector. Uses the branchless construction from Duff et al., "Building an
+    Orthonormal Basis, Revisited" (2017).
+    */
+    pub fn orthonormal_basis(&self) -> (Vec3, Vec3) {
+        let sign = Real::copysign(1.0, self.z);
+        let a = -1.0 / (sign + self.z);
+        let b = self.x * self.y * a;
+        (
+            Vec3 {
+                x: 1.0 + sign * self.x * self.x * a,
+                y: sign * b,
+                z: -sign * self.x,
+            },
+            Vec3 {
+                x: b,
+                y: sign + self.y * self.y * a,
+                z: -self.y,
+            },
+        )
+    }
+
+    /**
+    The vector rotated counter-clockwise (looking down `axis` toward the origin) by `angle` radians
+    around `axis`, which must be a unit vector.
+
+    Uses Rodrigues' rotation formula.
+    */
+    #[must_use]
+    pub fn rotate_around(&self, axis: Vec3, angle: Real) -> Self {
+        debug_assert!(
+            (1.0 - axis.norm()).abs() < 0.001,
+            "expected axis to have a norm of 1.0, got {}",
+            axis.norm()
+        );
+        let (sin, cos) = angle.sin_cos();
+        cos * *self + sin * axis.cross(*self) + (1.0 - cos) * axis.dot(*self) * axis
+    }
+
     pub fn contains_nan(&self) -> bool {
-        [Axis3::X, Axis3::Y, Axis3::Z]
-            .into_iter()
-            .any(|axis| self[axis].is_nan())
+        Axis3::all().into_iter().any(|axis| self[axis].is_nan())
+    }
+
+    /// Whether every component is a number, rather than NaN or infinite.
+    pub fn is_finite(&self) -> bool {
+        Axis3::all().into_iter().all(|axis| self[axis].is_finite())
     }
 }
 
@@ -150,8 +272,26 @@ impl std::ops::Sub<Vec3> for Vec3 {
     }
 }
 
+/// Pointwise mutable addition.
+impl std::ops::AddAssign<Vec3> for Vec3 {
+    fn add_assign(&mut self, rhs: Vec3) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+        self.z += rhs.z;
+    }
+}
+
+/// Pointwise mutable subtraction.
+impl std::ops::SubAssign<Vec3> for Vec3 {
+    fn sub_assign(&mut self, rhs: Vec3) {
+        self.x -= rhs.x;
+        self.y -= rhs.y;
+        self.z -= rhs.z;
+    }
+}
+
 /// Left-scalar multiplication.
-impl std::ops::Mul<Vec3> for f64 {
+impl std::ops::Mul<Vec3> for Real {
     type Output = Vec3;
 
     fn mul(self, rhs: Vec3) -> Self::Output {
@@ -164,7 +304,7 @@ impl std::ops::Mul<Vec3> for f64 {
 }
 
 /// Left-scalar multiplication.
-impl std::ops::Mul<&Vec3> for f64 {
+impl std::ops::Mul<&Vec3> for Real {
     type Output = Vec3;
 
     fn mul(self, rhs: &Vec3) -> Self::Output {
@@ -177,10 +317,10 @@ impl std::ops::Mul<&Vec3> for f64 {
 }
 
 /// Right-scalar multiplication.
-impl std::ops::Mul<f64> for Vec3 {
+impl std::ops::Mul<Real> for Vec3 {
     type Output = Vec3;
 
-    fn mul(self, rhs: f64) -> Self::Output {
+    fn mul(self, rhs: Real) -> Self::Output {
         Vec3 {
             x: self.x * rhs,
             y: self.y * rhs,
@@ -189,6 +329,15 @@ impl std::ops::Mul<f64> for Vec3 {
     }
 }
 
+/// Mutable scalar multiplication.
+impl std::ops::MulAssign<Real> for Vec3 {
+    fn mul_assign(&mut self, rhs: Real) {
+        self.x *= rhs;
+        self.y *= rhs;
+        self.z *= rhs;
+    }
+}
+
 /// Pointwise negation.
 impl std::ops::Neg for Vec3 {
     type Output = Vec3;
@@ -199,14 +348,21 @@ impl std::ops::Neg for Vec3 {
 }
 
 /// Scalar division.
-impl std::ops::Div<f64> for Vec3 {
+impl std::ops::Div<Real> for Vec3 {
     type Output = Vec3;
 
-    fn div(self, rhs: f64) -> Self::Output {
+    fn div(self, rhs: Real) -> Self::Output {
         1.0 / rhs * self
     }
 }
 
+/// Mutable scalar division.
+impl std::ops::DivAssign<Real> for Vec3 {
+    fn div_assign(&mut self, rhs: Real) {
+        *self *= 1.0 / rhs;
+    }
+}
+
 impl rand::distributions::Distribution<Vec3> for rand::distributions::Standard {
     fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Vec3 {
         Vec3 {
@@ -218,7 +374,7 @@ impl rand::distributions::Distribution<Vec3> for rand::distributions::Standard {
 }
 
 impl std::ops::Index<Axis3> for Vec3 {
-    type Output = f64;
+    type Output = Real;
 
     fn index(&self, index: Axis3) -> &Self::Output {
         match index {
@@ -228,3 +384,178 @@ impl std::ops::Index<Axis3> for Vec3 {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::real::consts;
+
+    const X: Vec3 = Vec3 {
+        x: 1.0,
+        y: 0.0,
+        z: 0.0,
+    };
+    const Y: Vec3 = Vec3 {
+        x: 0.0,
+        y: 1.0,
+        z: 0.0,
+    };
+    const Z: Vec3 = Vec3 {
+        x: 0.0,
+        y: 0.0,
+        z: 1.0,
+    };
+
+    #[test]
+    fn lerp_goes_from_self_to_other() {
+        let (a, b) = (X, Y + 2.0 * Z);
+        assert_vec3_eq!(a.lerp(b, 0.0), a);
+        assert_vec3_eq!(a.lerp(b, 1.0), b);
+        assert_vec3_eq!(
+            a.lerp(b, 0.5),
+            Vec3 {
+                x: 0.5,
+                y: 0.5,
+                z: 1.0
+            }
+        );
+    }
+
+    #[test]
+    fn reflect_flips_the_normal_component() {
+        let incoming = (X - Y).unit();
+        assert_vec3_eq!(incoming.reflect(&Y), (X + Y).unit());
+        assert_vec3_eq!(X.reflect(&Y), X);
+    }
+
+    #[test]
+    fn refract_without_index_change_passes_straight_through() {
+        let incoming = (X - Y).unit();
+        assert_vec3_eq!(incoming.refract(&Y, 1.5, 1.5).unwrap(), incoming);
+    }
+
+    #[test]
+    fn refract_obeys_snells_law() {
+        let incoming = (X - Y).unit();
+        let refracted = incoming.refract(&Y, 1.0, 1.5).unwrap();
+        assert!((refracted.norm() - 1.0).abs() < 1e-6);
+        // sin(theta) is the component along the surface, since both rays are unit vectors.
+        assert!((1.0 * incoming.x - 1.5 * refracted.x).abs() < 1e-6);
+        assert!(refracted.y < 0.0);
+    }
+
+    #[test]
+    fn refract_beyond_the_critical_angle_is_total_internal_reflection() {
+        let incoming = (X - Y).unit();
+        assert_eq!(incoming.refract(&Y, 1.5, 1.0), None);
+    }
+
+    #[test]
+    fn cross_is_right_handed() {
+        assert_vec3_eq!(X.cross(Y), Z);
+        assert_vec3_eq!(Y.cross(Z), X);
+        assert_vec3_eq!(Z.cross(X), Y);
+        assert_vec3_eq!(Y.cross(X), Z.negate());
+    }
+
+    #[test]
+    fn unit_has_norm_one_and_keeps_direction() {
+        let v = Vec3 {
+            x: 3.0,
+            y: 0.0,
+            z: 4.0,
+        };
+        assert_vec3_eq!(
+            v.unit(),
+            Vec3 {
+                x: 0.6,
+                y: 0.0,
+                z: 0.8
+            }
+        );
+    }
+
+    #[test]
+    fn try_unit_of_zero_is_none() {
+        assert_eq!(Vec3::ZERO.try_unit(), None);
+        assert_vec3_eq!((3.0 * Y).try_unit().unwrap(), Y);
+    }
+
+    #[test]
+    fn orthonormal_basis_is_orthonormal() {
+        let normals = [
+            X,
+            Y,
+            Z,
+            Z.negate(),
+            Vec3 {
+                x: 1.0,
+                y: -2.0,
+                z: 3.0,
+            }
+            .unit(),
+            Vec3 {
+                x: 0.0,
+                y: 0.0,
+                z: -1.0 + 1e-4,
+            }
+            .unit(),
+        ];
+        for n in normals {
+            let (b1, b2) = n.orthonormal_basis();
+            for v in [b1, b2] {
+                assert!((v.norm() - 1.0).abs() < 1e-5, "{:?} from {:?}", v, n);
+                assert!(v.dot(n).abs() < 1e-5, "{:?} from {:?}", v, n);
+            }
+            assert!(b1.dot(b2).abs() < 1e-5, "{:?} from {:?}", b1, n);
+            assert_vec3_eq!(b1.cross(b2), n, 1e-5);
+        }
+    }
+
+    #[test]
+    fn rotate_around_turns_counter_clockwise() {
+        assert_vec3_eq!(X.rotate_around(Z, consts::FRAC_PI_2), Y);
+        assert_vec3_eq!(Y.rotate_around(X, consts::FRAC_PI_2), Z);
+
+        let axis = Vec3 {
+            x: 1.0,
+            y: 2.0,
+            z: -2.0,
+        }
+        .unit();
+        for angle in [0.3, consts::FRAC_PI_2, 2.0] {
+            assert_vec3_eq!(axis.rotate_around(axis, angle), axis);
+            assert_vec3_eq!((3.0 * axis).rotate_around(axis, angle), 3.0 * axis, 1e-5);
+        }
+    }
+
+    #[test]
+    fn assignment_operators_match_the_binary_ones() {
+        let v = Vec3 {
+            x: 0.1,
+            y: -2.5,
+            z: 7.0,
+        };
+        let w = Vec3 {
+            x: 3.3,
+            y: 0.2,
+            z: -1.1,
+        };
+
+        let mut sum = v;
+        sum += w;
+        assert_eq!(sum, v + w);
+
+        let mut difference = v;
+        difference -= w;
+        assert_eq!(difference, v - w);
+
+        let mut product = v;
+        product *= 0.7;
+        assert_eq!(product, v * 0.7);
+
+        let mut quotient = v;
+        quotient /= 0.3;
+        assert_eq!(quotient, v / 0.3);
+    }
+}
@@ -0,0 +1,42 @@
+use crate::{
+    bounds::Bounds3,
+    hit::{Face, Hit},
+    interval::Interval,
+    material::Material,
+    object::IsObject,
+    ray::Ray,
+    real::Real,
+    texture,
+};
+
+/**
+The edges of a bounding box, `thickness` wide, for checking where objects and their bounds are.
+
+Only the edges can be hit; rays through the middle of a face pass straight through. The normal
+always faces the ray.
+*/
+pub struct Wireframe {
+    pub bounds: Bounds3,
+    pub thickness: Real,
+    pub material: Material,
+}
+
+impl IsObject for Wireframe {
+    fn hit(&self, ray: &Ray, t_range: Interval) -> Option<Hit> {
+        let t = self.bounds.edge_hit(ray, t_range, self.thickness)?;
+        Some(Hit {
+            point: ray.at(t),
+            normal: -ray.direction.unit(),
+            t,
+            face: Face::Front,
+            material: self.material.clone(),
+            texture_coord: texture::Coord { u: 0.0, v: 0.0 },
+            tangents: None,
+            object_id: None,
+        })
+    }
+
+    fn bounds(&self) -> Bounds3 {
+        self.bounds
+    }
+}
@@ -0,0 +1,149 @@
+/*!
+Renders a small fixed scene and compares it to `tests/golden/spheres.ppm`, to catch changes to
+what gets rendered.
+
+Each channel of each pixel may differ from the golden image by [`TOLERANCE`], to allow for
+floating-point differences between platforms. When a change to the output is intended, regenerate
+the golden image with:
+
+```text
+UPDATE_GOLDEN=1 cargo test --test golden
+```
+
+`f32` draws different random numbers, so the test only runs with `f64`.
+*/
+
+#![cfg(not(feature = "f32"))]
+
+use rt_weekend::{
+    background::Background,
+    camera::Camera,
+    color::Color,
+    material::{Dielectric, Lambertian, Material, Metal},
+    object::Object,
+    render::Renderer,
+    scene::Scene,
+    sphere::Sphere,
+    texture::{self, Texture, UvTransform},
+    vec3::Vec3,
+};
+use std::{env, fs};
+
+const GOLDEN: &str = "tests/golden/spheres.ppm";
+
+/// The most any channel (from 0 to 255) can differ from the golden image.
+const TOLERANCE: u8 = 2;
+
+fn lambertian(r: f64, g: f64, b: f64) -> Material {
+    Material::new(Lambertian {
+        albedo: Texture::new(texture::Constant {
+            color: Color { r, g, b },
+        }),
+    })
+}
+
+fn sphere(x: f64, y: f64, z: f64, radius: f64, material: Material) -> Object {
+    Object::new(Sphere {
+        center: Vec3 { x, y, z },
+        radius,
+        material,
+        uv_transform: UvTransform::default(),
+    })
+}
+
+/// The image as a PPM file.
+fn render() -> String {
+    let objects = vec![
+        sphere(0.0, -100.5, -1.0, 100.0, lambertian(0.8, 0.8, 0.0)),
+        sphere(0.0, 0.0, -1.0, 0.5, lambertian(0.1, 0.2, 0.5)),
+        sphere(
+            -1.0,
+            0.0,
+            -1.0,
+            0.5,
+            Material::new(Dielectric {
+                refractive_index: 1.5,
+                dispersion: 0.0,
+            }),
+        ),
+        sphere(
+            1.0,
+            0.0,
+            -1.0,
+            0.5,
+            Material::new(Metal::new(
+                Color {
+                    r: 0.8,
+                    g: 0.6,
+                    b: 0.2,
+                },
+                0.1,
+            )),
+        ),
+    ];
+    let camera = Camera::builder()
+        .aspect_ratio(4.0 / 3.0)
+        .look_from(Vec3 {
+            x: 0.0,
+            y: 0.5,
+            z: 1.5,
+        })
+        .look_at(Vec3 {
+            x: 0.0,
+            y: 0.0,
+            z: -1.0,
+        })
+        .v_fov(40.0)
+        .build()
+        .unwrap();
+    let image = Renderer::builder(camera, Scene::new(&objects, Vec::new()))
+        .background(Background::default())
+        .dimensions(32, 24)
+        .rays_per_pixel(16)
+        .seed(1)
+        .build()
+        .unwrap()
+        .render()
+        .gamma_corrected();
+
+    let mut ppm = Vec::new();
+    image.render(&mut ppm).unwrap();
+    String::from_utf8(ppm).unwrap()
+}
+
+/// The header (format, dimensions and maximum value) and channel values of a plain PPM file.
+fn parse(ppm: &str) -> (Vec<&str>, Vec<u8>) {
+    let mut tokens = ppm.split_whitespace();
+    let header = tokens.by_ref().take(4).collect();
+    let values = tokens.map(|token| token.parse().unwrap()).collect();
+    (header, values)
+}
+
+#[test]
+fn matches_golden_image() {
+    let rendered = render();
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(GOLDEN, &rendered).unwrap();
+        return;
+    }
+
+    let golden = fs::read_to_string(GOLDEN).unwrap();
+    let (golden_header, golden_values) = parse(&golden);
+    let (rendered_header, rendered_values) = parse(&rendered);
+    assert_eq!(rendered_header, golden_header);
+    assert_eq!(rendered_values.len(), golden_values.len());
+
+    let width: usize = golden_header[1].parse().unwrap();
+    for (index, (rendered, golden)) in rendered_values.iter().zip(&golden_values).enumerate() {
+        let pixel = index / 3;
+        assert!(
+            rendered.abs_diff(*golden) <= TOLERANCE,
+            "channel {} of pixel ({}, {}) is {}, but {} in the golden image",
+            index % 3,
+            pixel % width,
+            pixel / width,
+            rendered,
+            golden
+        );
+    }
+}
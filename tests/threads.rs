@@ -0,0 +1,61 @@
+/*!
+Checks that a seeded render comes out the same however many threads it is spread over, since each
+pixel draws its random numbers from its own generator.
+*/
+
+use rt_weekend::{
+    camera::Camera,
+    color::Color,
+    material::{Dielectric, Lambertian, Material},
+    object::Object,
+    real::Real,
+    render::Renderer,
+    scene::Scene,
+    sphere::Sphere,
+    texture::{self, Texture, UvTransform},
+    vec3::Vec3,
+};
+
+fn sphere(x: Real, y: Real, z: Real, radius: Real, material: Material) -> Object {
+    Object::new(Sphere {
+        center: Vec3 { x, y, z },
+        radius,
+        material,
+        uv_transform: UvTransform::default(),
+    })
+}
+
+fn render(num_threads: usize) -> Vec<u8> {
+    let lambertian = Material::new(Lambertian {
+        albedo: Texture::new(texture::Constant {
+            color: Color {
+                r: 0.5,
+                g: 0.7,
+                b: 0.3,
+            },
+        }),
+    });
+    let glass = Material::new(Dielectric {
+        refractive_index: 1.5,
+        dispersion: 0.0,
+    });
+    let objects = vec![
+        sphere(0.0, -100.5, -1.0, 100.0, lambertian.clone()),
+        sphere(-0.5, 0.0, -1.0, 0.5, lambertian),
+        sphere(0.5, 0.0, -1.0, 0.5, glass),
+    ];
+    let camera = Camera::builder().build().unwrap();
+    Renderer::builder(camera, Scene::new(&objects, Vec::new()))
+        .dimensions(24, 16)
+        .rays_per_pixel(8)
+        .seed(7)
+        .num_threads(num_threads)
+        .build()
+        .unwrap()
+        .render_to_rgba8()
+}
+
+#[test]
+fn output_does_not_depend_on_thread_count() {
+    assert_eq!(render(1), render(4));
+}